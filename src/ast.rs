@@ -1,6 +1,8 @@
-use crate::token::Token;
+use crate::token::{serialize_big_integer, Token};
+use num_bigint::BigInt;
+use serde::Serialize;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub enum UnaryOperation {
     Minus,
     Not,
@@ -18,7 +20,7 @@ impl From<Token> for UnaryOperation {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub enum BooleanOperation {
     And,
     Or,
@@ -34,29 +36,33 @@ impl From<Token> for BooleanOperation {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub enum Operator {
     Add,
     Sub,
     Mul,
     Div,
+    FloorDiv,
     Mod,
+    Pow,
 }
 
 impl From<Token> for Operator {
     fn from(tk: Token) -> Self {
         match tk {
-            Token::Plus => Operator::Add,
-            Token::Minus => Operator::Sub,
-            Token::Slash => Operator::Div,
-            Token::Star => Operator::Mul,
-            Token::Percent => Operator::Mod,
+            Token::Plus | Token::PlusEqual => Operator::Add,
+            Token::Minus | Token::MinusEqual => Operator::Sub,
+            Token::Slash | Token::SlashEqual => Operator::Div,
+            Token::SlashSlash | Token::SlashSlashEqual => Operator::FloorDiv,
+            Token::Star | Token::StarEqual => Operator::Mul,
+            Token::StarStar | Token::StarStarEqual => Operator::Pow,
+            Token::Percent | Token::PercentEqual => Operator::Mod,
             _ => panic!("unit type isn't a real type"),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub enum Comparison {
     Equal,
     NotEqual,
@@ -64,7 +70,10 @@ pub enum Comparison {
     GreaterThan,
     Less,
     LessThan,
+    // `em` and `nao em` are not parsed yet.
+    #[allow(dead_code)]
     In,
+    #[allow(dead_code)]
     NotIn,
     Is,
 }
@@ -84,19 +93,45 @@ impl From<Token> for Comparison {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum Number {
     Integer { value: i64 },
+    BigInteger {
+        #[serde(serialize_with = "serialize_big_integer")]
+        value: BigInt,
+    },
     Float { value: f64 },
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
 pub struct Keyword {
     pub name: String,
     pub value: Expression,
 }
 
-#[derive(Debug, PartialEq)]
+/// A name brought in by `importe`, optionally bound to another one with
+/// `como`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Alias {
+    pub name: String,
+    pub asname: Option<String>,
+}
+
+impl Alias {
+    /// The name the importing code sees.
+    pub fn bound_name(&self) -> &str {
+        self.asname.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// `expressao como alvo` in a `com` statement; the target is optional.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct WithItem {
+    pub context_expr: Expression,
+    pub optional_vars: Option<Expression>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
 pub enum Expression {
     Compare {
         a: Box<Expression>,
@@ -136,12 +171,23 @@ pub enum Expression {
     Identifier {
         name: String,
     },
+    Tuple {
+        elements: Vec<Expression>,
+    },
+    Subscript {
+        value: Box<Expression>,
+        index: Box<Expression>,
+    },
+    Attribute {
+        value: Box<Expression>,
+        name: String,
+    },
     True,
     False,
     None,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
 pub enum Statement {
     //    FunctionDef,
     //    ClassDef,
@@ -165,19 +211,28 @@ pub enum Statement {
         targets: Vec<Expression>,
         value: Expression,
     },
+    AugAssign {
+        target: Expression,
+        op: Operator,
+        value: Expression,
+    },
     Expr {
         expression: Expression,
     },
+    Import {
+        names: Vec<Alias>,
+    },
+    ImportFrom {
+        module: String,
+        names: Vec<Alias>,
+    },
+    With {
+        items: Vec<WithItem>,
+        body: Box<Statement>,
+    },
 }
 
-#[derive(Debug, PartialEq)]
-pub enum Top {
-    Program(Program),
-    Statement(Statement),
-    Expression(Expression),
-}
-
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
 pub struct Program {
     pub statements: Vec<Statement>,
 }
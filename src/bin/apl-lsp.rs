@@ -0,0 +1,8 @@
+use std::io;
+use std::process;
+
+fn main() {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    process::exit(apl::lsp::run(stdin.lock(), stdout.lock()));
+}
@@ -1,37 +1,702 @@
 use super::primitive::Primitive::*;
+use super::primitive::big_to_f64;
 use super::object::Object;
 use super::error::Error;
+use super::file::File;
+use super::native::{Args, Arity, NativeFunction};
 
+use num_bigint::BigInt;
+use num_integer::Integer as _;
+use num_traits::{FromPrimitive, Num, Signed};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
 use std::io::prelude::*;
-use std::io::{stdin, stdout};
+use std::io::{stdin, stdout, BufReader};
+use std::rc::Rc;
 
 
 type Result<T> = ::std::result::Result<T, Error>;
 
+/// Where `imprima` writes and `entrada` reads. An engine and the modules it
+/// imports share them, so the embedding application can capture the output
+/// of all of its code and script its input.
+pub(crate) struct Streams {
+    pub output: RefCell<Box<dyn Write>>,
+    pub input: RefCell<Box<dyn BufRead>>,
+}
 
-pub(crate) fn print(data: Object) -> Result<Object> {
-    match data {
-        Object::Primitive(Integer(n)) => println!("{}", n),
-        Object::Primitive(Str(s)) => println!("{}", s),
-        Object::Primitive(Boolean(b)) => println!("{}", b),
-        Object::Primitive(Float(f)) => println!("{}", f),
-        _ => return Err(Error::OtherError("invalid argument".into()))
+impl Streams {
+    pub fn standard() -> Self {
+        Streams {
+            output: RefCell::new(Box::new(stdout())),
+            input: RefCell::new(Box::new(BufReader::new(stdin()))),
+        }
     }
 
-    stdout().flush().expect("flush");
-    Ok(Object::Unit)
+    /// A line without its line ending, which may be `\n`, `\r\n` or none at
+    /// the end of the input.
+    fn read_line(&self) -> Result<String> {
+        let mut line = String::new();
+        let read = self
+            .input
+            .borrow_mut()
+            .read_line(&mut line)
+            .map_err(|e| Error::OtherError(format!("não foi possível ler a entrada: {}", e)))?;
+        if read == 0 {
+            return Err(Error::EndOfInput);
+        }
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(line)
+    }
+
+    fn write(&self, text: &str) -> Result<()> {
+        let mut output = self.output.borrow_mut();
+        output
+            .write_all(text.as_bytes())
+            .and_then(|_| output.flush())
+            .map_err(|e| Error::OtherError(format!("não foi possível escrever na saída: {}", e)))
+    }
 }
 
-pub(crate) fn input() -> Result<Object> {
-    let mut buf = ::std::string::String::new();
+impl fmt::Debug for Streams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Streams")
+    }
+}
 
-    stdin()
-        .read_line(&mut buf)
-        .map_err(|e| {
-            eprint!("cannot read stdin: {}", e);
-            Error::OtherError("cannot read stdin".into())
+pub(crate) fn globals(streams: &Rc<Streams>) -> HashMap<String, Object> {
+    builtins(streams)
+        .into_iter()
+        .map(|function| (function.name().to_string(), function.into()))
+        .collect()
+}
+
+/// The functions every program starts with.
+pub(crate) fn builtins(streams: &Rc<Streams>) -> Vec<NativeFunction> {
+    let output = Rc::clone(streams);
+    let help_output = Rc::clone(streams);
+    let input = |convert: fn(String) -> Result<Object>| {
+        let streams = Rc::clone(streams);
+        move |args: Args| convert(read(&streams, args)?)
+    };
+    vec![
+        NativeFunction::new("imprima", move |args| print(&output, args))
+            .rest("valores")
+            .keywords(&["sep", "fim", "arquivo"])
+            .doc("Escreve os valores na saída padrão, separados por `sep` (um espaço) \
+                  e seguidos de `fim` (uma quebra de linha). Com `arquivo`, escreve \
+                  chamando `arquivo.escreva(texto)`."),
+        NativeFunction::new("entrada", input(|line| Ok(line.into())))
+            .params(&["mensagem"])
+            .arity(Arity::Range(0, 1))
+            .doc("Escreve a mensagem, se houver, e lê uma linha da entrada padrão, \
+                  sem a quebra de linha. No fim da entrada, provoca ErroDeFimDeEntrada."),
+        NativeFunction::new("entrada_inteiro", input(integer))
+            .params(&["mensagem"])
+            .arity(Arity::Range(0, 1))
+            .doc("Como `entrada`, mas converte a linha para inteiro."),
+        NativeFunction::new("entrada_real", input(real))
+            .params(&["mensagem"])
+            .arity(Arity::Range(0, 1))
+            .doc("Como `entrada`, mas converte a linha para real."),
+        NativeFunction::new("abra", open)
+            .params(&["caminho", "modo"])
+            .keywords(&["codificacao"])
+            .arity(Arity::Range(1, 2))
+            .doc("Abre o arquivo para ler ('r', o padrão), escrever ('w'), acrescentar \
+                  ('a') ou criar ('x'), como texto em `codificacao` (utf-8, latin-1 \
+                  ou ascii). Use com `com abra(caminho) como f: ...` para fechá-lo."),
+        NativeFunction::new("ajuda", move |args| help(&help_output, args))
+            .params(&["objeto"])
+            .arity(Arity::Range(0, 1))
+            .doc("Escreve a documentação do objeto. Sem argumentos, lista as funções \
+                  disponíveis."),
+        NativeFunction::new("tipo", |args| Ok(argument(&args, 0)?.type_name().into()))
+            .params(&["objeto"])
+            .doc("O nome do tipo do objeto, como \"inteiro\" ou \"tupla\"."),
+        NativeFunction::new("tamanho", length)
+            .params(&["objeto"])
+            .doc("O número de elementos de uma tupla ou de caracteres de um texto."),
+        NativeFunction::new("inteiro", to_integer)
+            .params(&["valor", "base"])
+            .arity(Arity::Range(0, 2))
+            .doc("Converte o valor para inteiro. Reais são truncados e textos são lidos \
+                  na `base` dada, 10 se omitida. Sem argumentos, 0."),
+        NativeFunction::new("real", to_real)
+            .params(&["valor"])
+            .arity(Arity::Range(0, 1))
+            .doc("Converte o valor para real. Sem argumentos, 0.0."),
+        NativeFunction::new("texto", |args| {
+            Ok(args.get(0).map_or_else(String::new, Object::to_string).into())
+        })
+        .params(&["valor"])
+        .arity(Arity::Range(0, 1))
+        .doc("O texto que `imprima` escreveria para o valor. Sem argumentos, \"\"."),
+        NativeFunction::new("logico", |args| Ok(args.get(0).is_some_and(Object::truthy).into()))
+            .params(&["valor"])
+            .arity(Arity::Range(0, 1))
+            .doc("Falso para zero, textos e tuplas vazios e Vazio; Verdadeiro para o \
+                  resto. Sem argumentos, Falso."),
+        NativeFunction::new("abs", absolute)
+            .params(&["numero"])
+            .doc("O valor absoluto do número."),
+        NativeFunction::new("arredonde", round)
+            .params(&["numero", "casas"])
+            .arity(Arity::Range(1, 2))
+            .doc("Arredonda o número para o inteiro mais próximo ou, com `casas`, para \
+                  tantas casas decimais. Os empates vão para o vizinho par."),
+        NativeFunction::new("min", |args| extreme("min", args, less))
+            .rest("valores")
+            .keywords(&["chave"])
+            .arity(Arity::AtLeast(1))
+            .doc("O menor dos valores ou, com um só argumento, o menor de seus \
+                  elementos. Com `chave`, compara `chave(valor)`."),
+        NativeFunction::new("max", |args| extreme("max", args, |value, best| less(best, value)))
+            .rest("valores")
+            .keywords(&["chave"])
+            .arity(Arity::AtLeast(1))
+            .doc("O maior dos valores ou, com um só argumento, o maior de seus \
+                  elementos. Com `chave`, compara `chave(valor)`."),
+        NativeFunction::new("soma", sum)
+            .params(&["iteravel", "inicio"])
+            .arity(Arity::Range(1, 2))
+            .doc("A soma dos elementos, a partir de `inicio` (0)."),
+        NativeFunction::new("qualquer", |args| {
+            Ok(argument(&args, 0)?.items()?.iter().any(Object::truthy).into())
+        })
+        .params(&["iteravel"])
+        .doc("Verdadeiro se algum elemento é verdadeiro."),
+        NativeFunction::new("todos", |args| {
+            Ok(argument(&args, 0)?.items()?.iter().all(Object::truthy).into())
         })
-        .and_then(|_| {
-            Ok(Object::Primitive(Str(buf.trim_right_matches("\r\n").into())))
+        .params(&["iteravel"])
+        .doc("Verdadeiro se todos os elementos são verdadeiros, ou se não há nenhum."),
+        NativeFunction::new("ordenado", sort)
+            .params(&["iteravel"])
+            .keywords(&["chave", "invertido"])
+            .doc("Uma tupla com os elementos em ordem crescente ou, com \
+                  `invertido=Verdadeiro`, decrescente. Com `chave`, compara \
+                  `chave(elemento)`. Elementos iguais mantêm a ordem em que estavam."),
+        NativeFunction::new("invertido", |args| {
+            let mut items = argument(&args, 0)?.items()?;
+            items.reverse();
+            Ok(Object::Tuple(items))
         })
-}
\ No newline at end of file
+        .params(&["sequencia"])
+        .doc("Uma tupla com os elementos na ordem inversa."),
+        NativeFunction::new("intervalo", range)
+            .params(&["inicio", "fim", "passo"])
+            .arity(Arity::Range(1, 3))
+            .doc("Uma tupla com os inteiros de `inicio` (0) até `fim`, sem ele, de \
+                  `passo` (1) em `passo`. Com um só argumento, ele é o fim."),
+        NativeFunction::new("enumere", enumerate)
+            .params(&["iteravel", "inicio"])
+            .arity(Arity::Range(1, 2))
+            .doc("Uma tupla de pares (índice, elemento), com os índices a partir de \
+                  `inicio` (0)."),
+        NativeFunction::new("zipe", |args| {
+            let rows = transpose(args.positional())?;
+            Ok(Object::Tuple(rows.into_iter().map(Object::Tuple).collect()))
+        })
+        .rest("iteraveis")
+        .doc("Uma tupla com as tuplas dos elementos de mesma posição, até o fim do \
+              menor iterável."),
+        NativeFunction::new("mapeie", map)
+            .params(&["funcao"])
+            .rest("iteraveis")
+            .arity(Arity::AtLeast(2))
+            .doc("Uma tupla com o resultado de `funcao` para cada elemento ou, com \
+                  vários iteráveis, para os elementos de mesma posição."),
+        NativeFunction::new("filtre", filter)
+            .params(&["funcao", "iteravel"])
+            .doc("Uma tupla com os elementos para os quais `funcao(elemento)` é \
+                  verdadeiro. Com `funcao` Vazio, os elementos verdadeiros."),
+    ]
+}
+
+/// Python's `print`: every value is shown with its `Display`.
+pub(crate) fn print(streams: &Streams, args: Args) -> Result<Object> {
+    let sep = text_keyword(&args, "sep", " ")?;
+    let end = text_keyword(&args, "fim", "\n")?;
+    let values: Vec<String> = args.positional().iter().map(Object::to_string).collect();
+    let text = values.join(&sep) + &end;
+
+    match args.keyword("arquivo") {
+        None | Some(Object::Unit) => streams.write(&text)?,
+        Some(file) => {
+            file.get_attribute("escreva")?.call(vec![text.into()], vec![])?;
+        }
+    }
+    Ok(Object::Unit)
+}
+
+/// A keyword that takes a text, or `Vazio` for the default.
+fn text_keyword(args: &Args, name: &str, default: &str) -> Result<String> {
+    match args.keyword(name) {
+        None | Some(Object::Unit) => Ok(default.into()),
+        Some(Object::Primitive(Str(s))) => Ok(s.clone()),
+        Some(other) => Err(Error::InvalidArgument(format!(
+            "{} deve ser texto ou Vazio, não {}",
+            name,
+            other.type_name()
+        ))),
+    }
+}
+
+fn open(args: Args) -> Result<Object> {
+    let text = |value: Option<&Object>, default: &str| match value {
+        None => Ok(default.to_string()),
+        Some(Object::Primitive(Str(s))) => Ok(s.clone()),
+        Some(other) => Err(type_error("abra", "um texto", other)),
+    };
+    let file = File::open(
+        &text(args.get(0), "")?,
+        &text(args.get(1), "r")?,
+        &text(args.keyword("codificacao"), "utf-8")?,
+    )?;
+    Ok(file.into())
+}
+
+/// Python's `input`: the prompt is shown with its `Display`.
+fn read(streams: &Streams, args: Args) -> Result<String> {
+    if let Some(prompt) = args.get(0) {
+        streams.write(&prompt.to_string())?;
+    }
+    streams.read_line()
+}
+
+/// The number on the line, ignoring the spaces around it.
+fn integer(line: String) -> Result<Object> {
+    match line.trim().parse::<BigInt>() {
+        Ok(n) => Ok(Object::Primitive(n.into())),
+        Err(_) => Err(unreadable(line.into(), "inteiro")),
+    }
+}
+
+/// Also reads the infinities the way `imprima` writes them.
+fn real(line: String) -> Result<Object> {
+    let n = match line.trim() {
+        "infinito" | "+infinito" => Ok(f64::INFINITY),
+        "-infinito" => Ok(f64::NEG_INFINITY),
+        text => text.parse::<f64>(),
+    };
+    match n {
+        Ok(n) => Ok(n.into()),
+        Err(_) => Err(unreadable(line.into(), "real")),
+    }
+}
+
+/// A text that does not hold a number of the type: the type is right, so
+/// the error is in the value.
+fn unreadable(text: Object, target: &str) -> Error {
+    Error::InvalidValue(format!(
+        "não é possível converter {} para {}",
+        text.describe(),
+        target
+    ))
+}
+
+/// A required argument, which the arity should guarantee is there.
+pub(crate) fn argument(args: &Args, index: usize) -> Result<&Object> {
+    args.get(index).ok_or_else(|| {
+        Error::InvalidArgument(match args.param(index) {
+            Some(name) => format!("o argumento '{}' é obrigatório", name),
+            None => format!("o argumento {} é obrigatório", index + 1),
+        })
+    })
+}
+
+pub(crate) fn type_error(function: &str, expected: &str, got: &Object) -> Error {
+    Error::InvalidArgument(format!(
+        "{}() esperava {}, não {}",
+        function,
+        expected,
+        got.type_name()
+    ))
+}
+
+fn less(left: &Object, right: &Object) -> Result<bool> {
+    left.less_than(right)?.test()
+}
+
+/// `funcao(valor)`, or the value itself when there is no function.
+fn apply(function: Option<&Object>, value: &Object) -> Result<Object> {
+    match function {
+        None | Some(Object::Unit) => Ok(value.clone()),
+        Some(function) => function.call(vec![value.clone()], vec![]),
+    }
+}
+
+fn help(streams: &Rc<Streams>, args: Args) -> Result<Object> {
+    let text = match args.get(0) {
+        None => {
+            let signatures: Vec<String> = builtins(streams)
+                .iter()
+                .map(|function| format!("    {}\n", function.signature()))
+                .collect();
+            format!("Funções disponíveis:\n{}", signatures.concat())
+        }
+        Some(Object::Native(function)) => describe(function),
+        Some(Object::Module(module)) => {
+            let mut text = format!("módulo {}\n", module.name());
+            for name in module.names() {
+                match module.get(&name) {
+                    Some(Object::Native(function)) => text += &describe(&function),
+                    Some(value) => text += &format!("{} = {}\n", name, value.repr()),
+                    None => {}
+                }
+            }
+            text
+        }
+        Some(other) => format!("{} é do tipo {}\n", other.repr(), other.type_name()),
+    };
+    streams.write(&text)?;
+    Ok(Object::Unit)
+}
+
+fn describe(function: &NativeFunction) -> String {
+    match function.get_doc() {
+        "" => format!("{}\n", function.signature()),
+        doc => format!("{}\n    {}\n", function.signature(), doc),
+    }
+}
+
+fn length(args: Args) -> Result<Object> {
+    let length = match argument(&args, 0)? {
+        Object::Primitive(Str(s)) => s.chars().count(),
+        Object::Tuple(elements) => elements.len(),
+        other => {
+            return Err(Error::InvalidArgument(format!(
+                "{} não tem tamanho",
+                other.type_name()
+            )))
+        }
+    };
+    Ok((length as i64).into())
+}
+
+fn to_integer(args: Args) -> Result<Object> {
+    let value = match args.get(0) {
+        Some(value) => value,
+        None if args.get(1).is_some() => {
+            return Err(Error::InvalidArgument("inteiro() recebeu uma base sem o valor".into()))
+        }
+        None => return Ok(0.into()),
+    };
+    match (value, args.get(1)) {
+        (Object::Primitive(Str(s)), base) => {
+            let base = match base {
+                None => 10,
+                Some(Object::Primitive(Integer(base))) if (2..=36).contains(base) => *base as u32,
+                Some(Object::Primitive(Integer(_))) => {
+                    return Err(Error::InvalidValue("a base deve estar entre 2 e 36".into()))
+                }
+                Some(other) => return Err(type_error("inteiro", "uma base inteira", other)),
+            };
+            match BigInt::from_str_radix(s.trim(), base) {
+                Ok(n) => Ok(Object::Primitive(n.into())),
+                Err(_) => Err(unreadable(value.clone(), "inteiro")),
+            }
+        }
+        (_, Some(_)) => Err(Error::InvalidArgument(
+            "inteiro() só aceita uma base para textos".into(),
+        )),
+        (Object::Primitive(Integer(_)), None) | (Object::Primitive(BigInteger(_)), None) => {
+            Ok(value.clone())
+        }
+        (Object::Primitive(Float(n)), None) => float_to_integer(n.trunc()),
+        (Object::Primitive(Boolean(b)), None) => Ok((*b as i64).into()),
+        (other, None) => Err(type_error("inteiro", "um número, texto ou logico", other)),
+    }
+}
+
+pub(crate) fn float_to_integer(n: f64) -> Result<Object> {
+    match BigInt::from_f64(n) {
+        Some(n) => Ok(Object::Primitive(n.into())),
+        None => Err(Error::InvalidValue(format!(
+            "não é possível converter {} para inteiro",
+            Object::from(n)
+        ))),
+    }
+}
+
+fn to_real(args: Args) -> Result<Object> {
+    match args.get(0) {
+        None => Ok(0.0.into()),
+        Some(Object::Primitive(Integer(n))) => Ok((*n as f64).into()),
+        Some(Object::Primitive(BigInteger(n))) => Ok(big_to_f64(n).into()),
+        Some(Object::Primitive(Float(n))) => Ok((*n).into()),
+        Some(Object::Primitive(Boolean(b))) => Ok(if *b { 1.0 } else { 0.0 }.into()),
+        Some(Object::Primitive(Str(s))) => real(s.clone()),
+        Some(other) => Err(type_error("real", "um número, texto ou logico", other)),
+    }
+}
+
+fn absolute(args: Args) -> Result<Object> {
+    match argument(&args, 0)? {
+        Object::Primitive(Integer(n)) => match n.checked_abs() {
+            Some(n) => Ok(n.into()),
+            None => Ok(Object::Primitive(BigInt::from(*n).abs().into())),
+        },
+        Object::Primitive(BigInteger(n)) => Ok(Object::Primitive(n.abs().into())),
+        Object::Primitive(Float(n)) => Ok(n.abs().into()),
+        other => Err(type_error("abs", "um número", other)),
+    }
+}
+
+fn round(args: Args) -> Result<Object> {
+    let digits = match args.get(1) {
+        None | Some(Object::Unit) => None,
+        Some(Object::Primitive(Integer(digits))) => Some(*digits),
+        Some(other) => return Err(type_error("arredonde", "casas inteiras", other)),
+    };
+    match (argument(&args, 0)?, digits) {
+        (Object::Primitive(Float(n)), None) => float_to_integer(round_half_even(*n)),
+        (Object::Primitive(Float(n)), Some(digits)) => Ok(round_float(*n, digits)?.into()),
+        (Object::Primitive(Integer(n)), Some(digits)) if digits < 0 => {
+            Ok(round_integer(BigInt::from(*n), digits))
+        }
+        (Object::Primitive(BigInteger(n)), Some(digits)) if digits < 0 => {
+            Ok(round_integer(n.clone(), digits))
+        }
+        (value @ Object::Primitive(Integer(_)), _)
+        | (value @ Object::Primitive(BigInteger(_)), _) => Ok(value.clone()),
+        (other, _) => Err(type_error("arredonde", "um número", other)),
+    }
+}
+
+/// As in Python, a half goes to the even neighbour: 0.5 and 2.5 become 0 and
+/// 2, so that rounding many values does not drift upwards.
+fn round_half_even(n: f64) -> f64 {
+    if (n - n.trunc()).abs() == 0.5 {
+        2.0 * (n / 2.0).round()
+    } else {
+        n.round()
+    }
+}
+
+/// Rounds the exact value of the real, as Python does: 2.675 is stored as
+/// 2.67499999999999982236431605997495353221893310546875, so it rounds to
+/// 2.67, where scaling by a power of ten would give 2.68.
+fn round_float(n: f64, digits: i64) -> Result<f64> {
+    // no finite real is as large as 10 ** 309, so all of them round to zero
+    if digits < -308 {
+        return Ok(0.0f64.copysign(n));
+    }
+    // nor has any a nonzero digit past the 1074th decimal place
+    if !n.is_finite() || digits > 1074 {
+        return Ok(n);
+    }
+    if digits >= 0 {
+        // the formatting rounds the exact expansion, ties to even
+        return Ok(format!("{:.*}", digits as usize, n).parse().unwrap_or(n));
+    }
+
+    let factor = num_traits::pow(BigInt::from(10), digits.unsigned_abs() as usize);
+    let whole = BigInt::from_f64(n.abs().trunc()).unwrap_or_default();
+    let (quotient, remainder) = whole.div_mod_floor(&factor);
+    let twice: BigInt = remainder * 2;
+    // past the half by any fraction is past it, not a tie
+    let up = twice > factor || (twice == factor && (n.fract() != 0.0 || quotient.is_odd()));
+    let quotient = if up { quotient + 1 } else { quotient };
+    let rounded = big_to_f64(&(quotient * factor)).copysign(n);
+    if rounded.is_infinite() {
+        return Err(Error::InvalidValue(format!(
+            "arredonde({}, {}) é grande demais para um real",
+            Object::from(n),
+            digits
+        )));
+    }
+    Ok(rounded)
+}
+
+/// Rounds to a multiple of `10 ** -digits`.
+fn round_integer(n: BigInt, digits: i64) -> Object {
+    let places = digits.unsigned_abs() as usize;
+    if places > n.to_string().len() {
+        return 0.into();
+    }
+    let factor = num_traits::pow(BigInt::from(10), places);
+    let (quotient, remainder) = n.div_mod_floor(&factor);
+    let twice: BigInt = remainder * 2;
+    let quotient = if twice > factor || (twice == factor && quotient.is_odd()) {
+        quotient + 1
+    } else {
+        quotient
+    };
+    Object::Primitive((quotient * factor).into())
+}
+
+/// `min` and `max`, which differ in when a value `replaces` the one found so
+/// far. Only a strictly better value does, so the first of equals wins.
+fn extreme(
+    name: &str,
+    args: Args,
+    replaces: fn(&Object, &Object) -> Result<bool>,
+) -> Result<Object> {
+    let values = match args.positional() {
+        [iterable] => iterable.items()?,
+        values => values.to_vec(),
+    };
+    let key = args.keyword("chave");
+
+    let mut best: Option<(Object, Object)> = None;
+    for value in values {
+        let value_key = apply(key, &value)?;
+        best = match best {
+            Some((best_key, best)) if !replaces(&value_key, &best_key)? => Some((best_key, best)),
+            _ => Some((value_key, value)),
+        };
+    }
+    best.map(|(_, value)| value)
+        .ok_or_else(|| Error::InvalidValue(format!("{}() recebeu um iterável vazio", name)))
+}
+
+fn sum(args: Args) -> Result<Object> {
+    let start = args.get(1).cloned().unwrap_or_else(|| 0.into());
+    argument(&args, 0)?
+        .items()?
+        .iter()
+        .try_fold(start, |total, value| total.add(value))
+}
+
+fn sort(args: Args) -> Result<Object> {
+    let key = args.keyword("chave");
+    let reverse = args.keyword("invertido").is_some_and(Object::truthy);
+
+    let mut keyed = Vec::new();
+    for value in argument(&args, 0)?.items()? {
+        keyed.push((apply(key, &value)?, value));
+    }
+    let before = |left: &Object, right: &Object| {
+        if reverse {
+            less(right, left)
+        } else {
+            less(left, right)
+        }
+    };
+    let sorted = merge_sort(keyed, &before)?;
+    Ok(Object::Tuple(sorted.into_iter().map(|(_, value)| value).collect()))
+}
+
+/// A stable sort of `(chave, valor)` pairs that stops at the first comparison
+/// that fails, which `slice::sort_by` has no way to do.
+fn merge_sort(
+    mut pairs: Vec<(Object, Object)>,
+    before: &dyn Fn(&Object, &Object) -> Result<bool>,
+) -> Result<Vec<(Object, Object)>> {
+    if pairs.len() < 2 {
+        return Ok(pairs);
+    }
+    let right = pairs.split_off(pairs.len() / 2);
+    let mut left = merge_sort(pairs, before)?.into_iter().peekable();
+    let mut right = merge_sort(right, before)?.into_iter().peekable();
+
+    let mut merged = Vec::new();
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        // on a tie the left one goes first, which keeps the sort stable
+        let next = if before(&r.0, &l.0)? { &mut right } else { &mut left };
+        merged.extend(next.next());
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+/// The most numbers `intervalo` builds, since they are all kept in memory.
+const MAX_RANGE: usize = 10_000_000;
+
+fn range(args: Args) -> Result<Object> {
+    let integer = |value: Option<&Object>, default| match value {
+        None => Ok(default),
+        Some(Object::Primitive(Integer(n))) => Ok(*n),
+        Some(other) => Err(type_error("intervalo", "inteiros", other)),
+    };
+    let (start, end) = match (args.get(0), args.get(1)) {
+        (Some(end), None) => (0, integer(Some(end), 0)?),
+        (start, end) => (integer(start, 0)?, integer(end, 0)?),
+    };
+    let step = integer(args.get(2), 1)?;
+    if step == 0 {
+        return Err(Error::InvalidValue("o passo de intervalo() não pode ser zero".into()));
+    }
+
+    let length = if step > 0 {
+        (i128::from(end) - i128::from(start) + i128::from(step) - 1) / i128::from(step)
+    } else {
+        (i128::from(start) - i128::from(end) - i128::from(step) - 1) / -i128::from(step)
+    };
+    if length > MAX_RANGE as i128 {
+        return Err(Error::InvalidValue(format!(
+            "intervalo() teria {} números, mais que o máximo de {}",
+            length, MAX_RANGE
+        )));
+    }
+
+    let mut numbers = Vec::with_capacity(length.max(0) as usize);
+    let mut n = start;
+    while (step > 0 && n < end) || (step < 0 && n > end) {
+        numbers.push(Object::from(n));
+        n = match n.checked_add(step) {
+            Some(n) => n,
+            None => break,
+        };
+    }
+    Ok(Object::Tuple(numbers))
+}
+
+fn enumerate(args: Args) -> Result<Object> {
+    let start = match args.get(1) {
+        None => 0,
+        Some(Object::Primitive(Integer(start))) => *start,
+        Some(other) => return Err(type_error("enumere", "um início inteiro", other)),
+    };
+    let pairs = argument(&args, 0)?
+        .items()?
+        .into_iter()
+        .zip(start..)
+        .map(|(value, index)| Object::Tuple(vec![index.into(), value]))
+        .collect();
+    Ok(Object::Tuple(pairs))
+}
+
+/// The elements of the same position in each iterable, up to the end of the
+/// shortest.
+fn transpose(iterables: &[Object]) -> Result<Vec<Vec<Object>>> {
+    let columns = iterables
+        .iter()
+        .map(Object::items)
+        .collect::<Result<Vec<_>>>()?;
+    let length = columns.iter().map(Vec::len).min().unwrap_or(0);
+    Ok((0..length)
+        .map(|i| columns.iter().map(|column| column[i].clone()).collect())
+        .collect())
+}
+
+fn map(args: Args) -> Result<Object> {
+    // the arity makes the function and at least one iterable positional
+    let (function, iterables) = args.positional().split_first().expect("checked arity");
+    let results = transpose(iterables)?
+        .into_iter()
+        .map(|row| function.call(row, vec![]))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Object::Tuple(results))
+}
+
+fn filter(args: Args) -> Result<Object> {
+    let function = argument(&args, 0)?;
+    let mut kept = Vec::new();
+    for value in argument(&args, 1)?.items()? {
+        if apply(Some(function), &value)?.truthy() {
+            kept.push(value);
+        }
+    }
+    Ok(Object::Tuple(kept))
+}
@@ -0,0 +1,246 @@
+use crate::engine::{Engine, EngineError};
+use crate::error::Error;
+use crate::formatter::format_source;
+use crate::linter::lint_source;
+use crate::parse::{parse_program, ParseError};
+use crate::token::Token;
+use crate::tokenizer::scan_spanned;
+
+use serde_json::{json, Value};
+
+use std::env;
+use std::fs;
+
+const USAGE: &str = "uso: apl <arquivo.apl> [argumento]...
+     apl fmt [--check] <arquivo.apl>...
+     apl lint <arquivo.apl>...
+     apl tokens [--json] <arquivo.apl>
+     apl ast [--json] <arquivo.apl>
+
+os módulos são procurados ao lado do arquivo e depois nos diretórios de APLPATH";
+
+/// Runs `apl <arquivo.apl> [argumento]...` or one of the subcommands and
+/// returns the process exit status.
+pub fn run(args: &[String]) -> i32 {
+    match args.get(1).map(String::as_str) {
+        Some("fmt") => format_files(&args[2..]),
+        Some("lint") => lint_files(&args[2..]),
+        Some("tokens") => dump(&args[2..], dump_tokens),
+        Some("ast") => dump(&args[2..], dump_ast),
+        Some(_) => run_file(&args[1..]),
+        None => {
+            eprintln!("{}", USAGE);
+            2
+        }
+    }
+}
+
+/// Runs the file, with itself and what follows it as `sistema.argumentos`.
+/// A program that calls `sistema.saia` exits with its code.
+fn run_file(args: &[String]) -> i32 {
+    let path = &args[0];
+    let mut engine = Engine::new().arguments(args);
+    if let Some(directories) = env::var_os("APLPATH") {
+        for directory in env::split_paths(&directories) {
+            engine = engine.search_path(directory);
+        }
+    }
+
+    match engine.run_file(path) {
+        Ok(_) => 0,
+        Err(EngineError::Io(error)) => {
+            eprintln!("apl: não foi possível ler '{}': {}", path, error);
+            1
+        }
+        Err(EngineError::Parse(error)) => {
+            report_parse_error(path, error);
+            1
+        }
+        Err(EngineError::Runtime(Error::Exit(code))) => code,
+        Err(EngineError::Runtime(error)) => {
+            eprintln!("{}", error);
+            1
+        }
+    }
+}
+
+fn report_parse_error(path: &str, error: ParseError) {
+    match error {
+        ParseError::Lex(errors) => {
+            for error in errors {
+                eprintln!("{}:{}", path, error);
+            }
+        }
+        error @ ParseError::Syntax { .. } => eprintln!("{}:{}", path, error),
+        error => eprintln!("{}: {}", path, error),
+    }
+}
+
+fn read_source(path: &str) -> Option<String> {
+    fs::read_to_string(path)
+        .map_err(|error| eprintln!("apl: não foi possível ler '{}': {}", path, error))
+        .ok()
+}
+
+/// Rewrites every file in the canonical style. With `--check` nothing is
+/// written, and the status is 1 when some file is not formatted.
+fn format_files(args: &[String]) -> i32 {
+    let check = args.iter().any(|arg| arg == "--check");
+    let paths: Vec<&String> = args.iter().filter(|arg| *arg != "--check").collect();
+    if paths.is_empty() {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+
+    let mut status = 0;
+    for path in paths {
+        let source = match read_source(path) {
+            Some(source) => source,
+            None => {
+                status = 1;
+                continue;
+            }
+        };
+        let formatted = match format_source(&source) {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                report_parse_error(path, error);
+                status = 1;
+                continue;
+            }
+        };
+
+        if formatted == source {
+            continue;
+        }
+        if check {
+            eprintln!("{}: precisa ser formatado", path);
+            status = 1;
+        } else if let Err(error) = fs::write(path, formatted) {
+            eprintln!("apl: não foi possível escrever '{}': {}", path, error);
+            status = 1;
+        }
+    }
+    status
+}
+
+/// Prints the findings of every file. The status is 1 when there is any.
+fn lint_files(paths: &[String]) -> i32 {
+    if paths.is_empty() {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+
+    let mut status = 0;
+    for path in paths {
+        let source = match read_source(path) {
+            Some(source) => source,
+            None => {
+                status = 1;
+                continue;
+            }
+        };
+        match lint_source(&source) {
+            Ok(findings) => {
+                for finding in &findings {
+                    println!("{}:{}", path, finding);
+                }
+                if !findings.is_empty() {
+                    status = 1;
+                }
+            }
+            Err(error) => {
+                report_parse_error(path, error);
+                status = 1;
+            }
+        }
+    }
+    status
+}
+
+/// Prints what `dumper` makes of the file, as JSON with `--json` and as
+/// indented text otherwise.
+fn dump(args: &[String], dumper: fn(&str, bool) -> Result<String, ParseError>) -> i32 {
+    let json = args.iter().any(|arg| arg == "--json");
+    let paths: Vec<&String> = args.iter().filter(|arg| *arg != "--json").collect();
+    let path = match paths.as_slice() {
+        [path] => path,
+        _ => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    };
+
+    let source = match read_source(path) {
+        Some(source) => source,
+        None => return 1,
+    };
+    match dumper(&source, json) {
+        Ok(output) => {
+            println!("{}", output);
+            0
+        }
+        Err(error) => {
+            report_parse_error(path, error);
+            1
+        }
+    }
+}
+
+/// The tokens the parser sees, one per line with where it starts and ends.
+fn dump_tokens(source: &str, json: bool) -> Result<String, ParseError> {
+    let tokens = scan_spanned(source)
+        .map_err(ParseError::Lex)?
+        .into_iter()
+        .filter(|(token, _)| !matches!(token, Token::Comment(_)));
+
+    if json {
+        let tokens: Vec<_> = tokens
+            .map(|(token, span)| json!({ "token": token, "start": span.start, "end": span.end }))
+            .collect();
+        return Ok(serde_json::to_string_pretty(&tokens).expect("tokens are serializable"));
+    }
+
+    let lines: Vec<String> = tokens
+        .map(|(token, span)| match token {
+            Token::InteiroGrande(value) => {
+                format!("{}-{} InteiroGrande({})", span.start, span.end, value)
+            }
+            token => format!("{}-{} {:?}", span.start, span.end, token),
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+/// The syntax tree, either as JSON or as the indented tree of the same JSON.
+fn dump_ast(source: &str, json: bool) -> Result<String, ParseError> {
+    let program = parse_program(source)?;
+    if json {
+        return Ok(serde_json::to_string_pretty(&program).expect("the syntax tree is serializable"));
+    }
+
+    let mut lines = Vec::new();
+    tree(&json!(program), 0, &mut lines);
+    Ok(lines.join("\n"))
+}
+
+/// One line per field, with the fields of a node or the items of a list
+/// indented under its name.
+fn tree(value: &Value, depth: usize, lines: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                match value {
+                    Value::Object(_) | Value::Array(_) => {
+                        lines.push(format!("{}{}", indent, name));
+                        tree(value, depth + 1, lines);
+                    }
+                    value => lines.push(format!("{}{}: {}", indent, name, value)),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| tree(item, depth, lines)),
+        value => lines.push(format!("{}{}", indent, value)),
+    }
+}
@@ -0,0 +1,385 @@
+use crate::ast::{BooleanOperation, Comparison, Expression, Operator, UnaryOperation};
+use crate::ast::{Program, Statement};
+use crate::object::Object;
+use crate::primitive::Primitive::Float;
+
+/// One step of the virtual machine. Operands are indexes into the pools of
+/// the `Chunk` or, for jumps, into its code.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Instruction {
+    LoadConst(usize),
+    LoadName(usize),
+    StoreName(usize),
+    Dup,
+    Swap,
+    Binary(Operator),
+    Compare(Comparison),
+    Boolean(BooleanOperation),
+    Unary(UnaryOperation),
+    BuildTuple(usize),
+    Unpack(usize),
+    Subscript,
+    LoadAttribute(usize),
+    StoreAttribute(usize),
+    /// Number of positional arguments and the index of the keyword names.
+    Call(usize, usize),
+    /// Loads the module named `Chunk::modules[i]`.
+    Import(usize),
+    /// Loads an attribute of a module, failing as `de ... importe` does.
+    ImportFrom(usize, usize),
+    /// Pops the value of a `com` item and enters it.
+    EnterWith,
+    /// Exits the innermost entered value.
+    ExitWith,
+    Jump(usize),
+    JumpIfFalse(usize),
+    /// Pops the value of an expression statement, the result of the program so far.
+    SetResult,
+    ClearResult,
+    /// Raises an error with one of the messages in `Chunk::failures`.
+    Fail(usize),
+}
+
+/// A compiled program.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Chunk {
+    pub code: Vec<Instruction>,
+    pub constants: Vec<Object>,
+    /// Variable names, one slot each.
+    pub names: Vec<String>,
+    pub attributes: Vec<String>,
+    pub modules: Vec<String>,
+    pub keywords: Vec<Vec<String>>,
+    pub failures: Vec<String>,
+}
+
+pub(crate) fn compile(program: Program) -> Chunk {
+    let mut compiler = Compiler {
+        chunk: Chunk::default(),
+    };
+    for statement in program.statements {
+        compiler.statement(statement);
+    }
+    compiler.chunk
+}
+
+struct Compiler {
+    chunk: Chunk,
+}
+
+impl Compiler {
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.chunk.code.push(instruction);
+        self.chunk.code.len() - 1
+    }
+
+    /// Points the jump at `at` to the next instruction.
+    fn patch(&mut self, at: usize) {
+        let target = self.chunk.code.len();
+        self.chunk.code[at] = match self.chunk.code[at] {
+            Instruction::Jump(_) => Instruction::Jump(target),
+            Instruction::JumpIfFalse(_) => Instruction::JumpIfFalse(target),
+            other => panic!("{:?} is not a jump", other),
+        };
+    }
+
+    fn constant(&mut self, value: Object) -> usize {
+        match self
+            .chunk
+            .constants
+            .iter()
+            .position(|c| same_constant(c, &value))
+        {
+            Some(index) => index,
+            None => {
+                self.chunk.constants.push(value);
+                self.chunk.constants.len() - 1
+            }
+        }
+    }
+
+    fn slot(&mut self, name: String) -> usize {
+        Self::intern(&mut self.chunk.names, name)
+    }
+
+    fn attribute(&mut self, name: String) -> usize {
+        Self::intern(&mut self.chunk.attributes, name)
+    }
+
+    fn intern(pool: &mut Vec<String>, name: String) -> usize {
+        match pool.iter().position(|n| *n == name) {
+            Some(index) => index,
+            None => {
+                pool.push(name);
+                pool.len() - 1
+            }
+        }
+    }
+
+    fn fail(&mut self, message: &str) {
+        let message = Self::intern(&mut self.chunk.failures, message.into());
+        self.emit(Instruction::Fail(message));
+    }
+
+    fn statement(&mut self, statement: Statement) {
+        match statement {
+            Statement::Expr { expression } => {
+                self.expression(expression);
+                self.emit(Instruction::SetResult);
+            }
+            Statement::Assign { targets, value } => {
+                self.expression(value);
+                let last = targets.len() - 1;
+                for (i, target) in targets.into_iter().enumerate() {
+                    if i < last {
+                        self.emit(Instruction::Dup);
+                    }
+                    self.store(target);
+                }
+                self.emit(Instruction::ClearResult);
+            }
+            Statement::AugAssign { target, op, value } => {
+                self.augmented_assignment(target, op, value);
+                self.emit(Instruction::ClearResult);
+            }
+            Statement::Pass => {
+                self.emit(Instruction::ClearResult);
+            }
+            Statement::Import { names } => {
+                for alias in names {
+                    let module = Self::intern(&mut self.chunk.modules, alias.name.clone());
+                    self.emit(Instruction::Import(module));
+                    self.store_name(alias.bound_name());
+                }
+                self.emit(Instruction::ClearResult);
+            }
+            Statement::ImportFrom { module, names } => {
+                let module = Self::intern(&mut self.chunk.modules, module);
+                for alias in names {
+                    let attribute = self.attribute(alias.name.clone());
+                    self.emit(Instruction::ImportFrom(module, attribute));
+                    self.store_name(alias.bound_name());
+                }
+                self.emit(Instruction::ClearResult);
+            }
+            Statement::With { items, body } => {
+                let count = items.len();
+                for item in items {
+                    self.expression(item.context_expr);
+                    match item.optional_vars {
+                        Some(target) => {
+                            self.emit(Instruction::Dup);
+                            self.emit(Instruction::EnterWith);
+                            self.store(target);
+                        }
+                        None => {
+                            self.emit(Instruction::EnterWith);
+                        }
+                    }
+                }
+                self.statement(*body);
+                for _ in 0..count {
+                    self.emit(Instruction::ExitWith);
+                }
+                self.emit(Instruction::ClearResult);
+            }
+            _ => self.fail("statement not implemented yet"),
+        }
+    }
+
+    /// Mirrors the tree-walker: the target is evaluated once, before the value.
+    fn augmented_assignment(&mut self, target: Expression, op: Operator, value: Expression) {
+        match target {
+            Expression::Identifier { name } => {
+                let slot = self.slot(name);
+                self.emit(Instruction::LoadName(slot));
+                self.expression(value);
+                self.emit(Instruction::Binary(op));
+                self.emit(Instruction::StoreName(slot));
+            }
+            Expression::Attribute {
+                value: object,
+                name,
+            } => {
+                let attribute = self.attribute(name);
+                self.expression(*object);
+                self.emit(Instruction::Dup);
+                self.emit(Instruction::LoadAttribute(attribute));
+                self.expression(value);
+                self.emit(Instruction::Binary(op));
+                self.emit(Instruction::Swap);
+                self.emit(Instruction::StoreAttribute(attribute));
+            }
+            _ => self.fail("alvo de atribuição inválido"),
+        }
+    }
+
+    fn store_name(&mut self, name: &str) {
+        let slot = self.slot(name.into());
+        self.emit(Instruction::StoreName(slot));
+    }
+
+    /// Stores the value on top of the stack into `target`.
+    fn store(&mut self, target: Expression) {
+        match target {
+            Expression::Identifier { name } => {
+                let slot = self.slot(name);
+                self.emit(Instruction::StoreName(slot));
+            }
+            Expression::Tuple { elements } => {
+                self.emit(Instruction::Unpack(elements.len()));
+                for element in elements {
+                    self.store(element);
+                }
+            }
+            Expression::Attribute { value, name } => {
+                let attribute = self.attribute(name);
+                self.expression(*value);
+                self.emit(Instruction::StoreAttribute(attribute));
+            }
+            _ => self.fail("alvo de atribuição inválido"),
+        }
+    }
+
+    fn expression(&mut self, expression: Expression) {
+        match expression {
+            Expression::IfExpression { test, body, orelse } => {
+                self.expression(*test);
+                let to_orelse = self.emit(Instruction::JumpIfFalse(0));
+                self.expression(*body);
+                let to_end = self.emit(Instruction::Jump(0));
+                self.patch(to_orelse);
+                self.expression(*orelse);
+                self.patch(to_end);
+            }
+            Expression::Call {
+                function,
+                args,
+                keywords,
+            } => {
+                self.expression(*function);
+                let count = args.len();
+                for arg in args {
+                    self.expression(arg);
+                }
+                let mut names = Vec::new();
+                for keyword in keywords {
+                    names.push(keyword.name);
+                    self.expression(keyword.value);
+                }
+                self.chunk.keywords.push(names);
+                let names = self.chunk.keywords.len() - 1;
+                self.emit(Instruction::Call(count, names));
+            }
+            Expression::BinOp { a, op, b } => {
+                self.expression(*a);
+                self.expression(*b);
+                self.emit(Instruction::Binary(op));
+            }
+            Expression::Compare { a, op, b } => {
+                self.expression(*a);
+                self.expression(*b);
+                self.emit(Instruction::Compare(op));
+            }
+            Expression::BoolOp { a, op, b } => {
+                self.expression(*a);
+                self.expression(*b);
+                self.emit(Instruction::Boolean(op));
+            }
+            Expression::UnOp { op, a } => {
+                self.expression(*a);
+                self.emit(Instruction::Unary(op));
+            }
+            Expression::Str { value } => self.load_constant(value.into()),
+            Expression::Num { value } => self.load_constant(value.into()),
+            Expression::True => self.load_constant(true.into()),
+            Expression::False => self.load_constant(false.into()),
+            Expression::None => self.load_constant(Object::Unit),
+            Expression::Identifier { name } => {
+                let slot = self.slot(name);
+                self.emit(Instruction::LoadName(slot));
+            }
+            Expression::Tuple { elements } => {
+                let count = elements.len();
+                for element in elements {
+                    self.expression(element);
+                }
+                self.emit(Instruction::BuildTuple(count));
+            }
+            Expression::Subscript { value, index } => {
+                self.expression(*value);
+                self.expression(*index);
+                self.emit(Instruction::Subscript);
+            }
+            Expression::Attribute { value, name } => {
+                let attribute = self.attribute(name);
+                self.expression(*value);
+                self.emit(Instruction::LoadAttribute(attribute));
+            }
+        }
+    }
+
+    fn load_constant(&mut self, value: Object) {
+        let index = self.constant(value);
+        self.emit(Instruction::LoadConst(index));
+    }
+}
+
+/// Whether two constants can share a slot. `==` would merge `0.0` with
+/// `-0.0`, so reals are compared bit by bit.
+fn same_constant(a: &Object, b: &Object) -> bool {
+    match (a, b) {
+        (Object::Primitive(Float(a)), Object::Primitive(Float(b))) => a.to_bits() == b.to_bits(),
+        (Object::Tuple(a), Object::Tuple(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_constant(a, b))
+        }
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_program;
+    use crate::primitive::Primitive;
+
+    #[test]
+    fn constants_are_shared() {
+        let chunk = compile(parse_program("1 + 1").unwrap());
+        assert_eq!(
+            vec![Object::Primitive(Primitive::Integer(1))],
+            chunk.constants
+        );
+        assert_eq!(
+            vec![
+                Instruction::LoadConst(0),
+                Instruction::LoadConst(0),
+                Instruction::Binary(Operator::Add),
+                Instruction::SetResult,
+            ],
+            chunk.code
+        );
+    }
+
+    #[test]
+    fn names_get_one_slot_each() {
+        let chunk = compile(parse_program("a = 1 b = a a += b").unwrap());
+        assert_eq!(vec!["a".to_string(), "b".to_string()], chunk.names);
+    }
+
+    #[test]
+    fn if_expression_jumps() {
+        let chunk = compile(parse_program("se Verdadeiro: 1 senao: 2").unwrap());
+        assert_eq!(
+            vec![
+                Instruction::LoadConst(0),
+                Instruction::JumpIfFalse(4),
+                Instruction::LoadConst(1),
+                Instruction::Jump(5),
+                Instruction::LoadConst(2),
+                Instruction::SetResult,
+            ],
+            chunk.code
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! The `datahora` values: a date and a time of day, to the microsecond, in
+//! the proleptic Gregorian calendar and without a time zone.
+//!
+//! Durations are reais of seconds, so `d + 3600` is an hour later and the
+//! difference of two datahoras is the number of seconds between them.
+
+use crate::error::Error;
+use crate::native::NativeFunction;
+use crate::object::Object;
+use crate::primitive::Primitive;
+
+use std::fmt;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_DAY: i64 = 86_400 * MICROS_PER_SECOND;
+
+const WEEKDAYS: [&str; 7] = [
+    "segunda-feira",
+    "terça-feira",
+    "quarta-feira",
+    "quinta-feira",
+    "sexta-feira",
+    "sábado",
+    "domingo",
+];
+
+const MONTHS: [&str; 12] = [
+    "janeiro",
+    "fevereiro",
+    "março",
+    "abril",
+    "maio",
+    "junho",
+    "julho",
+    "agosto",
+    "setembro",
+    "outubro",
+    "novembro",
+    "dezembro",
+];
+
+/// A moment as microseconds since 1970-01-01 00:00:00, between the years 1
+/// and 9999 as in Python.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    micros: i64,
+}
+
+/// The fields of a `DateTime`, as they are written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Parts {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    micro: i64,
+}
+
+impl DateTime {
+    pub(crate) fn new(
+        year: i64,
+        month: i64,
+        day: i64,
+        hour: i64,
+        minute: i64,
+        second: i64,
+        micro: i64,
+    ) -> Result<Self> {
+        let check = |name: &str, value: i64, min: i64, max: i64| {
+            if value < min || value > max {
+                return Err(Error::InvalidValue(format!(
+                    "{} deve estar entre {} e {}, não {}",
+                    name, min, max, value
+                )));
+            }
+            Ok(())
+        };
+        check("ano", year, 1, 9999)?;
+        check("mes", month, 1, 12)?;
+        check("dia", day, 1, days_in_month(year, month))?;
+        check("hora", hour, 0, 23)?;
+        check("minuto", minute, 0, 59)?;
+        check("segundo", second, 0, 59)?;
+        check("microssegundo", micro, 0, 999_999)?;
+
+        let seconds = ((days_from_civil(year, month, day) * 24 + hour) * 60 + minute) * 60 + second;
+        Ok(DateTime {
+            micros: seconds * MICROS_PER_SECOND + micro,
+        })
+    }
+
+    /// The moment `micros` after 1970, if it is within the years 1 to 9999.
+    pub(crate) fn from_micros(micros: i64) -> Result<Self> {
+        let min = days_from_civil(1, 1, 1) * MICROS_PER_DAY;
+        let max = days_from_civil(10000, 1, 1) * MICROS_PER_DAY;
+        if micros < min || micros >= max {
+            return Err(Error::InvalidValue("datahora fora do intervalo".into()));
+        }
+        Ok(DateTime { micros })
+    }
+
+    /// Seconds since 1970-01-01 00:00:00, with a fraction.
+    pub fn timestamp(&self) -> f64 {
+        self.micros as f64 / MICROS_PER_SECOND as f64
+    }
+
+    fn parts(&self) -> Parts {
+        let days = self.micros.div_euclid(MICROS_PER_DAY);
+        let time = self.micros.rem_euclid(MICROS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        let seconds = time / MICROS_PER_SECOND;
+        Parts {
+            year,
+            month,
+            day,
+            hour: seconds / 3600,
+            minute: seconds / 60 % 60,
+            second: seconds % 60,
+            micro: time % MICROS_PER_SECOND,
+        }
+    }
+
+    /// 0 for Monday, as Python's `weekday()`.
+    fn weekday(&self) -> usize {
+        (self.micros.div_euclid(MICROS_PER_DAY) + 3).rem_euclid(7) as usize
+    }
+
+    /// `d + segundos`, the only arithmetic besides subtraction.
+    pub(crate) fn add_seconds(&self, seconds: &Primitive) -> Result<Self> {
+        let micros = match seconds {
+            Primitive::Integer(n) => n.checked_mul(MICROS_PER_SECOND),
+            Primitive::Float(n) if n.is_finite() => {
+                let micros = (n * MICROS_PER_SECOND as f64).round();
+                if micros.abs() < i64::MAX as f64 {
+                    Some(micros as i64)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        micros
+            .and_then(|micros| self.micros.checked_add(micros))
+            .ok_or_else(|| Error::InvalidValue("datahora fora do intervalo".into()))
+            .and_then(Self::from_micros)
+    }
+
+    /// The seconds from `other` to `self`.
+    pub(crate) fn seconds_since(&self, other: &Self) -> f64 {
+        (self.micros - other.micros) as f64 / MICROS_PER_SECOND as f64
+    }
+
+    /// ISO 8601, as `2024-03-05T14:30:00`, with the microseconds only when
+    /// there are any.
+    pub fn iso(&self) -> String {
+        self.format_with('T')
+    }
+
+    fn format_with(&self, separator: char) -> String {
+        let p = self.parts();
+        let mut text = format!(
+            "{:04}-{:02}-{:02}{}{:02}:{:02}:{:02}",
+            p.year, p.month, p.day, separator, p.hour, p.minute, p.second
+        );
+        if p.micro != 0 {
+            text += &format!(".{:06}", p.micro);
+        }
+        text
+    }
+
+    /// `strftime`, with the names of days and months in Portuguese.
+    pub(crate) fn format(&self, format: &str) -> Result<String> {
+        let p = self.parts();
+        let mut text = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                text.push(c);
+                continue;
+            }
+            let directive = chars.next();
+            let field = match directive {
+                Some('Y') => format!("{:04}", p.year),
+                Some('y') => format!("{:02}", p.year % 100),
+                Some('m') => format!("{:02}", p.month),
+                Some('d') => format!("{:02}", p.day),
+                Some('H') => format!("{:02}", p.hour),
+                Some('M') => format!("{:02}", p.minute),
+                Some('S') => format!("{:02}", p.second),
+                Some('f') => format!("{:06}", p.micro),
+                Some('j') => format!(
+                    "{:03}",
+                    days_from_civil(p.year, p.month, p.day) - days_from_civil(p.year, 1, 1) + 1
+                ),
+                Some('A') => WEEKDAYS[self.weekday()].into(),
+                Some('a') => WEEKDAYS[self.weekday()].chars().take(3).collect(),
+                Some('B') => MONTHS[p.month as usize - 1].into(),
+                Some('b') => MONTHS[p.month as usize - 1].chars().take(3).collect(),
+                Some('%') => "%".into(),
+                Some(other) => return Err(invalid_directive(&format!("%{}", other))),
+                None => return Err(invalid_directive("%")),
+            };
+            text += &field;
+        }
+        Ok(text)
+    }
+
+    /// `strptime` for the numeric directives of `format`. Each number may
+    /// have fewer digits than it is written with, as in `5/3/2024`.
+    pub(crate) fn parse(text: &str, format: &str) -> Result<Self> {
+        let mismatch = || {
+            Error::InvalidValue(format!(
+                "'{}' não corresponde ao formato '{}'",
+                text, format
+            ))
+        };
+        let mut p = Parts {
+            year: 1900,
+            month: 1,
+            day: 1,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            micro: 0,
+        };
+        let mut input = text.chars().peekable();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                if input.next() != Some(c) {
+                    return Err(mismatch());
+                }
+                continue;
+            }
+            let (field, digits) = match chars.next() {
+                Some('Y') => (&mut p.year, 4),
+                Some('y') => (&mut p.year, 2),
+                Some('m') => (&mut p.month, 2),
+                Some('d') => (&mut p.day, 2),
+                Some('H') => (&mut p.hour, 2),
+                Some('M') => (&mut p.minute, 2),
+                Some('S') => (&mut p.second, 2),
+                Some('f') => (&mut p.micro, 6),
+                Some('%') => {
+                    if input.next() != Some('%') {
+                        return Err(mismatch());
+                    }
+                    continue;
+                }
+                Some(other) => return Err(invalid_directive(&format!("%{}", other))),
+                None => return Err(invalid_directive("%")),
+            };
+            let mut number = String::new();
+            while number.len() < digits {
+                match input.peek() {
+                    Some(c) if c.is_ascii_digit() => number.push(input.next().unwrap()),
+                    _ => break,
+                }
+            }
+            if number.is_empty() {
+                return Err(mismatch());
+            }
+            *field = number.parse().expect("digits");
+            if digits == 6 {
+                // `.5` is half a second
+                *field *= 10i64.pow(6 - number.len() as u32);
+            }
+        }
+        if input.next().is_some() {
+            return Err(mismatch());
+        }
+        if format.contains("%y") {
+            // as POSIX: 69 to 99 are the 1900s, 00 to 68 the 2000s
+            p.year += if p.year < 69 { 2000 } else { 1900 };
+        }
+        Self::new(p.year, p.month, p.day, p.hour, p.minute, p.second, p.micro)
+    }
+
+    /// The fields and methods of the value, as `d.ano` finds them.
+    pub(crate) fn attribute(&self, name: &str) -> Option<Object> {
+        let p = self.parts();
+        let value = *self;
+        let field = match name {
+            "ano" => p.year,
+            "mes" => p.month,
+            "dia" => p.day,
+            "hora" => p.hour,
+            "minuto" => p.minute,
+            "segundo" => p.second,
+            "microssegundo" => p.micro,
+            "dia_da_semana" => self.weekday() as i64,
+            "iso" => {
+                return Some(
+                    NativeFunction::new(name, move |_| Ok(value.iso().into()))
+                        .params(&[])
+                        .doc("A datahora em ISO 8601, como 2024-03-05T14:30:00.")
+                        .into(),
+                )
+            }
+            "formate" => {
+                return Some(
+                    NativeFunction::new(name, move |args| match args.get(0) {
+                        Some(Object::Primitive(Primitive::Str(format))) => {
+                            Ok(value.format(format)?.into())
+                        }
+                        Some(other) => Err(Error::InvalidArgument(format!(
+                            "formate() esperava um texto, não {}",
+                            other.type_name()
+                        ))),
+                        None => unreachable!("the arity is checked"),
+                    })
+                    .params(&["formato"])
+                    .doc(
+                        "A datahora escrita no formato, em que %d, %m, %Y, %H, %M e %S \
+                         são o dia, o mês, o ano, a hora, o minuto e o segundo.",
+                    )
+                    .into(),
+                )
+            }
+            "timestamp" => return Some(self.timestamp().into()),
+            _ => return None,
+        };
+        Some(field.into())
+    }
+}
+
+/// As Python's `str()`: ISO 8601 with a space between the date and the time.
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format_with(' '))
+    }
+}
+
+fn invalid_directive(directive: &str) -> Error {
+    Error::InvalidValue(format!("diretiva de formato inválida: '{}'", directive))
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01, by Howard Hinnant's algorithm, which counts in eras
+/// of 400 years starting on March 1st.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_days_round_trip() {
+        assert_eq!(0, days_from_civil(1970, 1, 1));
+        assert_eq!(19_787, days_from_civil(2024, 3, 5));
+        assert_eq!(-719_162, days_from_civil(1, 1, 1));
+        for days in (-719_162..2_932_897).step_by(97) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days, days_from_civil(year, month, day));
+        }
+    }
+
+    #[test]
+    fn format_and_parse() {
+        let d = DateTime::new(2024, 2, 29, 7, 5, 3, 250_000).unwrap();
+        assert_eq!("2024-02-29T07:05:03.250000", d.iso());
+        assert_eq!("2024-02-29 07:05:03.250000", d.to_string());
+        assert_eq!(
+            "quinta-feira, 29 de fevereiro de 24 (060) 100%",
+            d.format("%A, %d de %B de %y (%j) 100%%").unwrap()
+        );
+        assert_eq!(
+            d,
+            DateTime::parse("29/2/2024 7:05:03.25", "%d/%m/%Y %H:%M:%S.%f").unwrap()
+        );
+        assert_eq!(
+            Err(Error::InvalidValue(
+                "dia deve estar entre 1 e 28, não 29".into()
+            )),
+            DateTime::parse("29/02/2023", "%d/%m/%Y")
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::ast::{Program, Statement};
+use crate::builtin::{self, Streams};
+use crate::error::Error;
+use crate::importer::Importer;
+use crate::interpreter::Interpreter;
+use crate::native::{Module, NativeFunction};
+use crate::object::Object;
+use crate::parse::ParseError;
+use crate::stdlib;
+use crate::vm::Vm;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+type Result<T> = ::std::result::Result<T, EngineError>;
+
+/// Everything that can go wrong when running APL code through an `Engine`.
+#[derive(Debug)]
+pub enum EngineError {
+    Io(io::Error),
+    Parse(ParseError),
+    Runtime(Error),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EngineError::Io(error) => write!(f, "{}", error),
+            EngineError::Parse(error) => write!(f, "{}", error),
+            EngineError::Runtime(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+impl From<io::Error> for EngineError {
+    fn from(error: io::Error) -> Self {
+        EngineError::Io(error)
+    }
+}
+
+impl From<ParseError> for EngineError {
+    fn from(error: ParseError) -> Self {
+        EngineError::Parse(error)
+    }
+}
+
+impl From<Error> for EngineError {
+    fn from(error: Error) -> Self {
+        EngineError::Runtime(error)
+    }
+}
+
+/// Which implementation of the language runs the code.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Backend {
+    /// Compiles to bytecode and runs it on a stack machine.
+    #[default]
+    Bytecode,
+    /// Walks the syntax tree directly. Slower, kept as the reference.
+    TreeWalker,
+}
+
+pub(crate) trait Evaluator: fmt::Debug {
+    fn eval(&self, program: Program) -> ::std::result::Result<Object, Error>;
+    fn set_global(&self, name: &str, value: Object);
+    fn get_global(&self, name: &str) -> Option<Object>;
+    fn globals(&self) -> HashMap<String, Object>;
+}
+
+/// A fresh evaluator of the backend, importing modules through `importer`.
+pub(crate) fn evaluator(backend: Backend, importer: Rc<Importer>) -> Box<dyn Evaluator> {
+    match backend {
+        Backend::Bytecode => Box::new(Vm::with_importer(importer)),
+        Backend::TreeWalker => Box::new(Interpreter::with_importer(importer)),
+    }
+}
+
+impl Evaluator for Interpreter {
+    fn eval(&self, program: Program) -> ::std::result::Result<Object, Error> {
+        Interpreter::eval(self, program)
+    }
+
+    fn set_global(&self, name: &str, value: Object) {
+        Interpreter::set_global(self, name, value)
+    }
+
+    fn get_global(&self, name: &str) -> Option<Object> {
+        Interpreter::get_global(self, name)
+    }
+
+    fn globals(&self) -> HashMap<String, Object> {
+        Interpreter::globals(self)
+    }
+}
+
+impl Evaluator for Vm {
+    fn eval(&self, program: Program) -> ::std::result::Result<Object, Error> {
+        Vm::eval(self, program)
+    }
+
+    fn set_global(&self, name: &str, value: Object) {
+        Vm::set_global(self, name, value)
+    }
+
+    fn get_global(&self, name: &str) -> Option<Object> {
+        Vm::get_global(self, name)
+    }
+
+    fn globals(&self) -> HashMap<String, Object> {
+        Vm::globals(self)
+    }
+}
+
+/// Runs APL code from a Rust application.
+///
+/// Globals survive between runs, so values set with `set_global` can be used
+/// by the code and values assigned by the code can be read back:
+///
+/// ```
+/// let engine = apl::Engine::new();
+/// engine.set_global("x", 20);
+/// engine.run_str("y = x * 2 + 2").unwrap();
+/// assert_eq!(Some(apl::Object::from(42)), engine.get_global("y"));
+/// ```
+#[derive(Debug)]
+pub struct Engine {
+    evaluator: Box<dyn Evaluator>,
+    importer: Rc<Importer>,
+    streams: Rc<Streams>,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self::with_backend(Backend::default())
+    }
+
+    pub fn with_backend(backend: Backend) -> Self {
+        let streams = Rc::new(Streams::standard());
+        let importer = Rc::new(Importer::new(backend, builtin::globals(&streams)));
+        for module in stdlib::modules() {
+            importer.register(module);
+        }
+        Engine {
+            evaluator: evaluator(backend, Rc::clone(&importer)),
+            importer,
+            streams,
+        }
+    }
+
+    /// Folds operations on literals and drops the branches of `se` that can
+    /// never be taken before running the code. Off by default.
+    pub fn optimize(self, enabled: bool) -> Self {
+        self.importer.set_optimize(enabled);
+        self
+    }
+
+    /// Sends what `imprima` and the prompts of `entrada` write to `writer`
+    /// instead of the standard output.
+    pub fn output<W: Write + 'static>(self, writer: W) -> Self {
+        *self.streams.output.borrow_mut() = Box::new(writer);
+        self
+    }
+
+    /// Makes `entrada` read from `reader` instead of the standard input.
+    pub fn input<R: BufRead + 'static>(self, reader: R) -> Self {
+        *self.streams.input.borrow_mut() = Box::new(reader);
+        self
+    }
+
+    /// Adds a directory where `importe` looks for modules that are not next
+    /// to the importing file. Directories are searched in the order added.
+    pub fn search_path<P: Into<PathBuf>>(self, directory: P) -> Self {
+        self.importer.add_search_path(directory.into());
+        self
+    }
+
+    /// Sets `sistema.argumentos`, which is empty otherwise. `apl` passes the
+    /// path of the file and the arguments after it, as Python's `sys.argv`.
+    pub fn arguments<I, S>(self, arguments: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let arguments = arguments
+            .into_iter()
+            .map(|argument| Object::from(argument.into()))
+            .collect();
+        if let Ok(module) = self.importer.import("sistema") {
+            module.set("argumentos", Object::Tuple(arguments));
+        }
+        self
+    }
+
+    /// Runs a whole program and returns the value of its last statement.
+    pub fn run_str(&self, source: &str) -> Result<Object> {
+        let program = self.parse(source)?;
+        Ok(self.evaluator.eval(program)?)
+    }
+
+    /// Runs a program from a file; the modules it imports are looked up
+    /// from the directory of the file.
+    pub fn run_file<P: AsRef<Path>>(&self, path: P) -> Result<Object> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        self.importer.within(path, || self.run_str(&source))
+    }
+
+    /// Evaluates a single expression, rejecting statements such as assignments.
+    pub fn eval_expr(&self, source: &str) -> Result<Object> {
+        let program = self.parse(source)?;
+        match program.statements.as_slice() {
+            [Statement::Expr { .. }] => Ok(self.evaluator.eval(program)?),
+            _ => Err(EngineError::Parse(ParseError::Message(
+                "esperava uma única expressão".into(),
+            ))),
+        }
+    }
+
+    pub fn set_global<T: Into<Object>>(&self, name: &str, value: T) {
+        self.evaluator.set_global(name, value.into());
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Object> {
+        self.evaluator.get_global(name)
+    }
+
+    /// Makes a native function callable from APL code under its own name.
+    pub fn register(&self, function: NativeFunction) {
+        let name = function.name().to_string();
+        self.set_global(&name, function);
+    }
+
+    /// Adds a native function to the module `module`, creating it when the
+    /// name is not bound to a module yet. It is called as `module.nome(...)`,
+    /// and the module can be imported by modules written in APL.
+    pub fn register_in(&self, module: &str, function: NativeFunction) {
+        self.module(module).register(function);
+    }
+
+    pub fn module(&self, name: &str) -> Module {
+        match self.get_global(name) {
+            Some(Object::Module(module)) => module,
+            _ => {
+                let module = Module::new(name);
+                self.set_global(name, module.clone());
+                self.importer.register(module.clone());
+                module
+            }
+        }
+    }
+
+    fn parse(&self, source: &str) -> Result<Program> {
+        Ok(self.importer.parse(source)?)
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
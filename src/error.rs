@@ -1,26 +1,100 @@
 use crate::primitive::Primitive;
 use crate::object::Object;
 
-#[derive(Debug)]
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
 pub enum Error {
     InvalidOperation(OperatorError, Primitive, Option<Primitive>),
     InvalidType(OperatorError, Object, Option<Object>),
+    ZeroDivision(OperatorError, Primitive, Primitive),
+    UndefinedName(String),
+    IndexOutOfRange(i64),
+    InvalidAttribute(Object, String),
+    InvalidConversion(Object, &'static str),
+    InvalidArgument(String),
+    InvalidValue(String),
+    ImportError(String),
+    FileError(String),
+    EndOfInput,
+    /// Raised by `sistema.saia`; it ends the program with the code.
+    Exit(i32),
     OtherError(String),
 }
 
-
-#[derive(Debug, PartialEq)]
-pub enum InterpreterError {
-    Message(String),
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidOperation(op, left, Some(right)) => write!(
+                f,
+                "ErroDeTipo: operação '{}' inválida entre {} e {}",
+                op,
+                left.describe(),
+                right.describe()
+            ),
+            Error::InvalidOperation(op, left, None) => write!(
+                f,
+                "ErroDeTipo: operação '{}' inválida para {}",
+                op,
+                left.describe()
+            ),
+            Error::InvalidType(op, left, Some(right)) => write!(
+                f,
+                "ErroDeTipo: operação '{}' inválida entre {} e {}",
+                op,
+                left.describe(),
+                right.describe()
+            ),
+            Error::InvalidType(op, left, None) => write!(
+                f,
+                "ErroDeTipo: operação '{}' inválida para {}",
+                op,
+                left.describe()
+            ),
+            Error::ZeroDivision(op, left, right) => write!(
+                f,
+                "ErroDeDivisaoPorZero: {} {} {}: divisão por zero",
+                left.describe(),
+                op,
+                right.describe()
+            ),
+            Error::UndefinedName(name) => {
+                write!(f, "ErroDeNome: nome '{}' não está definido", name)
+            }
+            Error::IndexOutOfRange(index) => {
+                write!(f, "ErroDeIndice: índice {} fora do intervalo", index)
+            }
+            Error::InvalidAttribute(object, name) => write!(
+                f,
+                "ErroDeAtributo: {} não possui o atributo '{}'",
+                object.describe(),
+                name
+            ),
+            Error::InvalidConversion(object, target) => write!(
+                f,
+                "ErroDeTipo: não é possível converter {} para {}",
+                object.describe(),
+                target
+            ),
+            Error::InvalidArgument(msg) => write!(f, "ErroDeTipo: {}", msg),
+            Error::InvalidValue(msg) => write!(f, "ErroDeValor: {}", msg),
+            Error::ImportError(msg) => write!(f, "ErroDeImportacao: {}", msg),
+            Error::FileError(msg) => write!(f, "ErroDeArquivo: {}", msg),
+            Error::EndOfInput => write!(f, "ErroDeFimDeEntrada: a entrada terminou"),
+            Error::Exit(code) => write!(f, "SaidaDoSistema: código {}", code),
+            Error::OtherError(msg) => write!(f, "Erro: {}", msg),
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum OperatorError {
     Add,
     Sub,
     Mul,
     IntDiv,
     RealDiv,
+    Pow,
     UnarySub,
     UnaryPlus,
     Negate,
@@ -35,4 +109,32 @@ pub enum OperatorError {
     Is,
     NotIs,
     Module,
-}
\ No newline at end of file
+    Subscript,
+}
+
+impl fmt::Display for OperatorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            OperatorError::Add | OperatorError::UnaryPlus => "+",
+            OperatorError::Sub | OperatorError::UnarySub => "-",
+            OperatorError::Mul => "*",
+            OperatorError::IntDiv => "//",
+            OperatorError::RealDiv => "/",
+            OperatorError::Pow => "**",
+            OperatorError::Negate => "nao",
+            OperatorError::And => "e",
+            OperatorError::Or => "ou",
+            OperatorError::LessThan => "<",
+            OperatorError::GreaterThan => ">",
+            OperatorError::LessThanEqual => "<=",
+            OperatorError::GreaterThanEqual => ">=",
+            OperatorError::Equal => "==",
+            OperatorError::NotEqual => "!=",
+            OperatorError::Is => "é",
+            OperatorError::NotIs => "nao é",
+            OperatorError::Module => "%",
+            OperatorError::Subscript => "[]",
+        };
+        write!(f, "{}", symbol)
+    }
+}
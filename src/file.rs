@@ -0,0 +1,316 @@
+//! The files `abra` opens.
+//!
+//! A file is always read and written as text in one of a few encodings, and
+//! `\r\n` line endings read as `\n`. Closing it, by `feche` or at the end of
+//! a `com` statement, flushes what was written.
+
+use crate::error::Error;
+use crate::native::NativeFunction;
+use crate::object::Object;
+use crate::primitive::Primitive;
+
+use std::cell::RefCell;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::rc::Rc;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+/// An open file. Clones share the same handle, so closing one closes all.
+#[derive(Clone)]
+pub struct File {
+    state: Rc<State>,
+}
+
+struct State {
+    name: String,
+    mode: Mode,
+    encoding: Encoding,
+    handle: RefCell<Option<Handle>>,
+}
+
+enum Handle {
+    Reader(BufReader<fs::File>),
+    Writer(BufWriter<fs::File>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Read,
+    Write,
+    Append,
+    Create,
+}
+
+impl Mode {
+    fn parse(mode: &str) -> Result<Self> {
+        match mode {
+            "r" => Ok(Mode::Read),
+            "w" => Ok(Mode::Write),
+            "a" => Ok(Mode::Append),
+            "x" => Ok(Mode::Create),
+            other => Err(Error::InvalidValue(format!(
+                "modo inválido: '{}'; use 'r', 'w', 'a' ou 'x'",
+                other
+            ))),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Mode::Read => "r",
+            Mode::Write => "w",
+            Mode::Append => "a",
+            Mode::Create => "x",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Utf8,
+    Latin1,
+    Ascii,
+}
+
+impl Encoding {
+    /// Accepts the usual spellings, such as `UTF8` or `iso-8859-1`.
+    fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Ok(Encoding::Utf8),
+            "latin-1" | "latin1" | "iso-8859-1" => Ok(Encoding::Latin1),
+            "ascii" | "us-ascii" => Ok(Encoding::Ascii),
+            _ => Err(Error::InvalidValue(format!(
+                "codificação desconhecida: '{}'",
+                name
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf-8",
+            Encoding::Latin1 => "latin-1",
+            Encoding::Ascii => "ascii",
+        }
+    }
+
+    fn decode(self, bytes: Vec<u8>) -> Result<String> {
+        let error = || Error::InvalidValue(format!("o conteúdo não é {} válido", self.name()));
+        match self {
+            Encoding::Utf8 => String::from_utf8(bytes).map_err(|_| error()),
+            Encoding::Latin1 => Ok(bytes.into_iter().map(char::from).collect()),
+            Encoding::Ascii if bytes.is_ascii() => Ok(bytes.into_iter().map(char::from).collect()),
+            Encoding::Ascii => Err(error()),
+        }
+    }
+
+    fn encode(self, text: &str) -> Result<Vec<u8>> {
+        let limit = match self {
+            Encoding::Utf8 => return Ok(text.as_bytes().to_vec()),
+            Encoding::Latin1 => 0xff,
+            Encoding::Ascii => 0x7f,
+        };
+        text.chars()
+            .map(|c| match c as u32 {
+                code if code <= limit => Ok(code as u8),
+                _ => Err(Error::InvalidValue(format!(
+                    "'{}' não pode ser escrito em {}",
+                    c,
+                    self.name()
+                ))),
+            })
+            .collect()
+    }
+}
+
+impl File {
+    /// Python's `open(path, mode, encoding=…)`, for text only.
+    pub(crate) fn open(path: &str, mode: &str, encoding: &str) -> Result<Self> {
+        let mode = Mode::parse(mode)?;
+        let encoding = Encoding::parse(encoding)?;
+        let mut options = fs::OpenOptions::new();
+        match mode {
+            Mode::Read => options.read(true),
+            Mode::Write => options.write(true).create(true).truncate(true),
+            Mode::Append => options.append(true).create(true),
+            Mode::Create => options.write(true).create_new(true),
+        };
+        let file = options
+            .open(path)
+            .map_err(|error| open_error(path, error))?;
+        let handle = match mode {
+            Mode::Read => Handle::Reader(BufReader::new(file)),
+            _ => Handle::Writer(BufWriter::new(file)),
+        };
+        Ok(File {
+            state: Rc::new(State {
+                name: path.into(),
+                mode,
+                encoding,
+                handle: RefCell::new(Some(handle)),
+            }),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.state.name
+    }
+
+    pub fn mode(&self) -> &str {
+        self.state.mode.as_str()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.handle.borrow().is_none()
+    }
+
+    /// The rest of the file.
+    pub(crate) fn read(&self) -> Result<String> {
+        let mut bytes = Vec::new();
+        self.with_reader(|reader| reader.read_to_end(&mut bytes))?;
+        Ok(self.state.encoding.decode(bytes)?.replace("\r\n", "\n"))
+    }
+
+    /// The next line, with its `\n`, or an empty text at the end of the file.
+    pub(crate) fn read_line(&self) -> Result<String> {
+        let mut bytes = Vec::new();
+        self.with_reader(|reader| reader.read_until(b'\n', &mut bytes))?;
+        let mut line = self.state.encoding.decode(bytes)?;
+        if line.ends_with("\r\n") {
+            line.truncate(line.len() - 2);
+            line.push('\n');
+        }
+        Ok(line)
+    }
+
+    /// The remaining lines, each with its `\n`.
+    pub(crate) fn lines(&self) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+        loop {
+            match self.read_line()? {
+                line if line.is_empty() => return Ok(lines),
+                line => lines.push(line),
+            }
+        }
+    }
+
+    /// Writes the text and returns how many characters it has.
+    pub(crate) fn write(&self, text: &str) -> Result<usize> {
+        let bytes = self.state.encoding.encode(text)?;
+        match self.state.handle.borrow_mut().as_mut() {
+            None => Err(closed()),
+            Some(Handle::Reader(_)) => Err(Error::FileError(format!(
+                "'{}' não foi aberto para escrita",
+                self.state.name
+            ))),
+            Some(Handle::Writer(writer)) => writer
+                .write_all(&bytes)
+                .map(|_| text.chars().count())
+                .map_err(|error| self.io_error("escrever em", error)),
+        }
+    }
+
+    /// Flushes what was written and releases the file. Closing it again does
+    /// nothing.
+    pub(crate) fn close(&self) -> Result<()> {
+        match self.state.handle.borrow_mut().take() {
+            Some(Handle::Writer(mut writer)) => writer
+                .flush()
+                .map_err(|error| self.io_error("escrever em", error)),
+            _ => Ok(()),
+        }
+    }
+
+    /// The attributes and methods of the file, as `f.leia` finds them.
+    pub(crate) fn attribute(&self, name: &str) -> Option<Object> {
+        let file = self.clone();
+        let method = match name {
+            "nome" => return Some(self.name().into()),
+            "modo" => return Some(self.mode().into()),
+            "codificacao" => return Some(self.state.encoding.name().into()),
+            "fechado" => return Some(self.is_closed().into()),
+            "leia" => NativeFunction::new(name, move |_| Ok(file.read()?.into()))
+                .params(&[])
+                .doc("O restante do arquivo."),
+            "leia_linha" => NativeFunction::new(name, move |_| Ok(file.read_line()?.into()))
+                .params(&[])
+                .doc("A próxima linha, com o '\\n', ou \"\" no fim do arquivo."),
+            "linhas" => NativeFunction::new(name, move |_| Ok(file.lines()?.into()))
+                .params(&[])
+                .doc("Uma tupla com as linhas restantes."),
+            "escreva" => NativeFunction::new(name, move |args| match args.get(0) {
+                Some(Object::Primitive(Primitive::Str(text))) => {
+                    Ok((file.write(text)? as i64).into())
+                }
+                Some(other) => Err(Error::InvalidArgument(format!(
+                    "escreva() esperava um texto, não {}",
+                    other.type_name()
+                ))),
+                None => unreachable!("the arity is checked"),
+            })
+            .params(&["texto"])
+            .doc("Escreve o texto e retorna quantos caracteres ele tem."),
+            "feche" => NativeFunction::new(name, move |_| {
+                file.close()?;
+                Ok(Object::Unit)
+            })
+            .params(&[])
+            .doc("Fecha o arquivo; fechá-lo de novo não faz nada."),
+            _ => return None,
+        };
+        Some(method.into())
+    }
+
+    fn with_reader<T>(
+        &self,
+        read: impl FnOnce(&mut BufReader<fs::File>) -> io::Result<T>,
+    ) -> Result<T> {
+        match self.state.handle.borrow_mut().as_mut() {
+            None => Err(closed()),
+            Some(Handle::Writer(_)) => Err(Error::FileError(format!(
+                "'{}' não foi aberto para leitura",
+                self.state.name
+            ))),
+            Some(Handle::Reader(reader)) => {
+                read(reader).map_err(|error| self.io_error("ler", error))
+            }
+        }
+    }
+
+    fn io_error(&self, action: &str, error: io::Error) -> Error {
+        Error::FileError(format!(
+            "não foi possível {} '{}': {}",
+            action, self.state.name, error
+        ))
+    }
+}
+
+impl fmt::Debug for File {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<arquivo '{}' modo '{}'>", self.name(), self.mode())
+    }
+}
+
+impl PartialEq for File {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+fn closed() -> Error {
+    Error::InvalidValue("operação em arquivo fechado".into())
+}
+
+fn open_error(path: &str, error: io::Error) -> Error {
+    let reason = match error.kind() {
+        io::ErrorKind::NotFound => "arquivo não encontrado",
+        io::ErrorKind::PermissionDenied => "permissão negada",
+        io::ErrorKind::AlreadyExists => "o arquivo já existe",
+        _ => {
+            return Error::FileError(format!("não foi possível abrir '{}': {}", path, error));
+        }
+    };
+    Error::FileError(format!("{}: '{}'", reason, path))
+}
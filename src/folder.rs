@@ -0,0 +1,181 @@
+//! A rewriting traversal of the syntax tree.
+
+use crate::ast::{Expression, Keyword, Program, Statement, WithItem};
+
+/// Rebuilds the tree, letting a pass replace any node. The default of each
+/// method is the `fold_*` function of the same node, which folds the children
+/// and keeps the node itself.
+pub(crate) trait Folder: Sized {
+    fn fold_program(&mut self, program: Program) -> Program {
+        fold_program(self, program)
+    }
+
+    fn fold_statement(&mut self, statement: Statement) -> Statement {
+        fold_statement(self, statement)
+    }
+
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        fold_expression(self, expression)
+    }
+
+    fn fold_keyword(&mut self, keyword: Keyword) -> Keyword {
+        fold_keyword(self, keyword)
+    }
+}
+
+pub(crate) fn fold_program<F: Folder>(folder: &mut F, program: Program) -> Program {
+    Program {
+        statements: program
+            .statements
+            .into_iter()
+            .map(|statement| folder.fold_statement(statement))
+            .collect(),
+    }
+}
+
+pub(crate) fn fold_statement<F: Folder>(folder: &mut F, statement: Statement) -> Statement {
+    match statement {
+        Statement::Return { value } => Statement::Return {
+            value: value.map(|value| fold_all(folder, value)),
+        },
+        Statement::Assert { test, msg } => Statement::Assert {
+            test: folder.fold_expression(test),
+            msg: msg.map(|msg| folder.fold_expression(msg)),
+        },
+        Statement::Assign { targets, value } => Statement::Assign {
+            targets: fold_all(folder, targets),
+            value: folder.fold_expression(value),
+        },
+        Statement::AugAssign { target, op, value } => Statement::AugAssign {
+            target: folder.fold_expression(target),
+            op,
+            value: folder.fold_expression(value),
+        },
+        Statement::Expr { expression } => Statement::Expr {
+            expression: folder.fold_expression(expression),
+        },
+        Statement::With { items, body } => Statement::With {
+            items: items
+                .into_iter()
+                .map(|item| WithItem {
+                    context_expr: folder.fold_expression(item.context_expr),
+                    optional_vars: item
+                        .optional_vars
+                        .map(|target| folder.fold_expression(target)),
+                })
+                .collect(),
+            body: Box::new(folder.fold_statement(*body)),
+        },
+        statement @ Statement::Break
+        | statement @ Statement::Continue
+        | statement @ Statement::Pass
+        | statement @ Statement::Import { .. }
+        | statement @ Statement::ImportFrom { .. } => statement,
+    }
+}
+
+pub(crate) fn fold_expression<F: Folder>(folder: &mut F, expression: Expression) -> Expression {
+    match expression {
+        Expression::Compare { a, op, b } => Expression::Compare {
+            a: fold_boxed(folder, a),
+            op,
+            b: fold_boxed(folder, b),
+        },
+        Expression::BoolOp { a, op, b } => Expression::BoolOp {
+            a: fold_boxed(folder, a),
+            op,
+            b: fold_boxed(folder, b),
+        },
+        Expression::BinOp { a, op, b } => Expression::BinOp {
+            a: fold_boxed(folder, a),
+            op,
+            b: fold_boxed(folder, b),
+        },
+        Expression::UnOp { op, a } => Expression::UnOp {
+            op,
+            a: fold_boxed(folder, a),
+        },
+        Expression::IfExpression { test, body, orelse } => Expression::IfExpression {
+            test: fold_boxed(folder, test),
+            body: fold_boxed(folder, body),
+            orelse: fold_boxed(folder, orelse),
+        },
+        Expression::Call {
+            function,
+            args,
+            keywords,
+        } => Expression::Call {
+            function: fold_boxed(folder, function),
+            args: fold_all(folder, args),
+            keywords: keywords
+                .into_iter()
+                .map(|keyword| folder.fold_keyword(keyword))
+                .collect(),
+        },
+        Expression::Tuple { elements } => Expression::Tuple {
+            elements: fold_all(folder, elements),
+        },
+        Expression::Subscript { value, index } => Expression::Subscript {
+            value: fold_boxed(folder, value),
+            index: fold_boxed(folder, index),
+        },
+        Expression::Attribute { value, name } => Expression::Attribute {
+            value: fold_boxed(folder, value),
+            name,
+        },
+        leaf @ Expression::Str { .. }
+        | leaf @ Expression::Num { .. }
+        | leaf @ Expression::Identifier { .. }
+        | leaf @ Expression::True
+        | leaf @ Expression::False
+        | leaf @ Expression::None => leaf,
+    }
+}
+
+pub(crate) fn fold_keyword<F: Folder>(folder: &mut F, keyword: Keyword) -> Keyword {
+    Keyword {
+        name: keyword.name,
+        value: folder.fold_expression(keyword.value),
+    }
+}
+
+/// Folds the expression in place, keeping its allocation.
+fn fold_boxed<F: Folder>(folder: &mut F, mut expression: Box<Expression>) -> Box<Expression> {
+    let inner = std::mem::replace(&mut *expression, Expression::None);
+    *expression = folder.fold_expression(inner);
+    expression
+}
+
+fn fold_all<F: Folder>(folder: &mut F, expressions: Vec<Expression>) -> Vec<Expression> {
+    expressions
+        .into_iter()
+        .map(|expression| folder.fold_expression(expression))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_program;
+
+    /// Renames `a` to `b` everywhere.
+    struct Rename;
+
+    impl Folder for Rename {
+        fn fold_expression(&mut self, expression: Expression) -> Expression {
+            match expression {
+                Expression::Identifier { ref name } if name == "a" => {
+                    Expression::Identifier { name: "b".into() }
+                }
+                other => fold_expression(self, other),
+            }
+        }
+    }
+
+    #[test]
+    fn folder_rewrites_nested_nodes() {
+        let program = parse_program("a = (a + 1, f(k=a))").unwrap();
+        let expected = parse_program("b = (b + 1, f(k=b))").unwrap();
+        assert_eq!(expected, Rename.fold_program(program));
+    }
+}
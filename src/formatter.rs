@@ -0,0 +1,728 @@
+//! Reprints source code in the canonical style used by `apl fmt`.
+//!
+//! Each statement is turned into a `Doc`, a tree of text and possible line
+//! breaks, which is then laid out in at most `WIDTH` columns: a group is kept
+//! on one line when it fits and is broken at its `Line`s otherwise. Comments
+//! are not in the syntax tree, so they are attached to the statement before or
+//! after them by position and put back around it.
+
+use crate::ast::{Alias, Statement, UnaryOperation, WithItem};
+use crate::ast::{BooleanOperation, Comparison, Expression, Keyword, Number, Operator};
+use crate::parse::{parse_source, ParseError};
+use crate::visitor::Visitor;
+
+const WIDTH: usize = 80;
+const INDENT: usize = 4;
+
+/// Reformats a whole program, keeping its comments.
+pub(crate) fn format_source(source: &str) -> Result<String, ParseError> {
+    let parsed = parse_source(source)?;
+    let statements = &parsed.program.statements;
+    let spans = &parsed.spans;
+
+    // comments before each statement, the last entry is the end of the file
+    let mut leading: Vec<Vec<(String, usize)>> = vec![Vec::new(); statements.len() + 1];
+    let mut trailing: Vec<Option<String>> = vec![None; statements.len()];
+    let mut next = 0;
+    for (text, span) in &parsed.comments {
+        while next < spans.len() && spans[next].end <= span.start {
+            next += 1;
+        }
+
+        let text = comment(text);
+        if next < spans.len() && spans[next].start < span.start {
+            // a comment in the middle of a statement goes right before it
+            leading[next].push((text, spans[next].start.line));
+        } else if next > 0 && spans[next - 1].end.line == span.start.line {
+            trailing[next - 1] = Some(text);
+        } else {
+            leading[next].push((text, span.start.line));
+        }
+    }
+
+    let mut printer = Printer {
+        literals: parsed.literals.iter(),
+    };
+    let mut output = Output::default();
+    for (i, statement) in statements.iter().enumerate() {
+        for (text, line) in &leading[i] {
+            output.push(text, *line, *line);
+        }
+
+        let mut text = render(&printer.visit_statement(statement));
+        if let Some(comment) = &trailing[i] {
+            text.push_str("  ");
+            text.push_str(comment);
+        }
+        output.push(&text, spans[i].start.line, spans[i].end.line);
+    }
+    for (text, line) in &leading[statements.len()] {
+        output.push(text, *line, *line);
+    }
+
+    Ok(output.text)
+}
+
+/// `#text` becomes `# text`; a shebang (`#!`) is left alone.
+fn comment(text: &str) -> String {
+    let text = text.trim_end();
+    if text.is_empty() || text.starts_with('!') || text.starts_with(char::is_whitespace) {
+        format!("#{}", text)
+    } else {
+        format!("# {}", text)
+    }
+}
+
+/// The formatted file, one item per line. Blank lines of the original are
+/// kept between items, but never more than one in a row.
+#[derive(Default)]
+struct Output {
+    text: String,
+    last_line: Option<usize>,
+}
+
+impl Output {
+    fn push(&mut self, text: &str, start_line: usize, end_line: usize) {
+        if let Some(last_line) = self.last_line {
+            if start_line > last_line + 1 {
+                self.text.push('\n');
+            }
+        }
+        self.text.push_str(text);
+        self.text.push('\n');
+        self.last_line = Some(end_line);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Doc {
+    Text(String),
+    /// A space, or a line break when its group does not fit.
+    Line,
+    /// Nothing, or a line break when its group does not fit.
+    SoftLine,
+    /// Text that is only printed when its group is broken.
+    IfBreak(&'static str),
+    /// Printed on one line if it fits, broken at every `Line` otherwise.
+    Group(Vec<Doc>),
+    /// Indents the lines broken inside it.
+    Nest(Vec<Doc>),
+}
+
+fn text(text: &str) -> Doc {
+    Doc::Text(text.into())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+fn render(doc: &Doc) -> String {
+    let mut output = String::new();
+    let mut column = 0;
+    let mut stack = vec![(0, Mode::Break, doc)];
+    while let Some((indent, mode, doc)) = stack.pop() {
+        match doc {
+            Doc::Text(text) => {
+                output.push_str(text);
+                column += text.chars().count();
+            }
+            Doc::Line | Doc::SoftLine if mode == Mode::Flat => {
+                if *doc == Doc::Line {
+                    output.push(' ');
+                    column += 1;
+                }
+            }
+            Doc::Line | Doc::SoftLine => {
+                output.push('\n');
+                output.push_str(&" ".repeat(indent));
+                column = indent;
+            }
+            Doc::IfBreak(text) => {
+                if mode == Mode::Break {
+                    output.push_str(text);
+                    column += text.chars().count();
+                }
+            }
+            Doc::Group(docs) => {
+                let mode =
+                    if mode == Mode::Flat || fits(WIDTH as isize - column as isize, docs, &stack) {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+                stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+            }
+            Doc::Nest(docs) => {
+                stack.extend(docs.iter().rev().map(|doc| (indent + INDENT, mode, doc)));
+            }
+        }
+    }
+    output
+}
+
+/// Whether the group, on one line, and whatever follows it up to the next
+/// line break fit in the remaining width.
+fn fits(mut remaining: isize, group: &[Doc], rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut pending: Vec<(Mode, &Doc)> = group.iter().rev().map(|doc| (Mode::Flat, doc)).collect();
+    let mut rest = rest.iter().rev();
+    loop {
+        if remaining < 0 {
+            return false;
+        }
+
+        let (mode, doc) = match pending.pop() {
+            Some(next) => next,
+            None => match rest.next() {
+                Some(&(_, mode, doc)) => (mode, doc),
+                None => return true,
+            },
+        };
+        match doc {
+            Doc::Text(text) => remaining -= text.chars().count() as isize,
+            Doc::Line | Doc::SoftLine if mode == Mode::Break => return true,
+            Doc::Line => remaining -= 1,
+            Doc::SoftLine => {}
+            Doc::IfBreak(text) => {
+                if mode == Mode::Break {
+                    remaining -= text.len() as isize;
+                }
+            }
+            Doc::Group(docs) => pending.extend(docs.iter().rev().map(|doc| (Mode::Flat, doc))),
+            Doc::Nest(docs) => pending.extend(docs.iter().rev().map(|doc| (mode, doc))),
+        }
+    }
+}
+
+// How tightly each kind of expression binds, from the loosest to the tightest,
+// following the rules of the parser.
+const TUPLE: u8 = 0;
+const IF: u8 = 1;
+const OR: u8 = 2;
+const AND: u8 = 3;
+const NOT: u8 = 4;
+const COMPARE: u8 = 5;
+const SUM: u8 = 6;
+const TERM: u8 = 7;
+const UNARY: u8 = 8;
+const POWER: u8 = 9;
+const PRIMARY: u8 = 10;
+
+fn binding(expression: &Expression) -> u8 {
+    match expression {
+        // `()` and `(x,)` bring their own parentheses
+        Expression::Tuple { elements } if elements.len() > 1 => TUPLE,
+        Expression::IfExpression { .. } => IF,
+        Expression::BoolOp {
+            op: BooleanOperation::Or,
+            ..
+        } => OR,
+        Expression::BoolOp {
+            op: BooleanOperation::And,
+            ..
+        } => AND,
+        Expression::UnOp {
+            op: UnaryOperation::Not,
+            ..
+        } => NOT,
+        Expression::UnOp { .. } => UNARY,
+        Expression::Compare { .. } => COMPARE,
+        Expression::BinOp {
+            op: Operator::Add, ..
+        }
+        | Expression::BinOp {
+            op: Operator::Sub, ..
+        } => SUM,
+        Expression::BinOp {
+            op: Operator::Pow, ..
+        } => POWER,
+        Expression::BinOp { .. } => TERM,
+        _ => PRIMARY,
+    }
+}
+
+fn operator(op: Operator) -> &'static str {
+    match op {
+        Operator::Add => "+",
+        Operator::Sub => "-",
+        Operator::Mul => "*",
+        Operator::Div => "/",
+        Operator::FloorDiv => "//",
+        Operator::Mod => "%",
+        Operator::Pow => "**",
+    }
+}
+
+fn aliases(names: &[Alias]) -> String {
+    let names: Vec<String> = names
+        .iter()
+        .map(|alias| match &alias.asname {
+            Some(asname) => format!("{} como {}", alias.name, asname),
+            None => alias.name.clone(),
+        })
+        .collect();
+    names.join(", ")
+}
+
+/// Turns the syntax tree into a `Doc`, adding only the parentheses the
+/// parser needs to read it back the same way.
+struct Printer<'a> {
+    /// The numbers and texts of the source still to print; the tree is
+    /// visited in the order it was written, so they are taken one by one.
+    literals: std::slice::Iter<'a, String>,
+}
+
+impl<'a> Printer<'a> {
+    /// The expression, in parentheses when it binds looser than `min`.
+    fn operand(&mut self, expression: &Expression, min: u8) -> Doc {
+        let doc = self.visit_expression(expression);
+        if binding(expression) < min {
+            Self::parenthesized(doc)
+        } else {
+            doc
+        }
+    }
+
+    fn parenthesized(doc: Doc) -> Doc {
+        Doc::Group(vec![
+            text("("),
+            Doc::Nest(vec![Doc::SoftLine, doc]),
+            Doc::SoftLine,
+            text(")"),
+        ])
+    }
+
+    /// A left associative operation. `a + b - c` is laid out as a single
+    /// group, so it breaks before every operator or none of them.
+    fn chain(&mut self, a: &Expression, op: &str, b: &Expression, level: u8) -> Doc {
+        let mut docs = match self.operand(a, level) {
+            Doc::Group(docs) if binding(a) == level => docs,
+            doc => vec![doc],
+        };
+        docs.push(Doc::Nest(vec![
+            Doc::Line,
+            text(op),
+            text(" "),
+            self.operand(b, level + 1),
+        ]));
+        Doc::Group(docs)
+    }
+
+    /// Comma separated items, broken one per line when they do not fit.
+    fn list(open: &str, items: Vec<Doc>, trailing_comma: bool, close: &str) -> Doc {
+        let mut nested = vec![Doc::SoftLine];
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                nested.push(text(","));
+                nested.push(Doc::Line);
+            }
+            nested.push(item);
+        }
+        if trailing_comma {
+            nested.push(Doc::IfBreak(","));
+        }
+        Doc::Group(vec![
+            text(open),
+            Doc::Nest(nested),
+            Doc::SoftLine,
+            text(close),
+        ])
+    }
+}
+
+impl<'a> Visitor for Printer<'a> {
+    type Output = Doc;
+
+    fn default_output(&mut self) -> Doc {
+        Doc::Group(vec![])
+    }
+
+    fn visit_break(&mut self) -> Doc {
+        text("interrompa")
+    }
+
+    fn visit_continue(&mut self) -> Doc {
+        text("continue")
+    }
+
+    fn visit_pass(&mut self) -> Doc {
+        text("passe")
+    }
+
+    fn visit_return(&mut self, value: Option<&[Expression]>) -> Doc {
+        let mut docs = vec![text("retorne")];
+        for (i, expression) in value.unwrap_or_default().iter().enumerate() {
+            docs.push(text(if i == 0 { " " } else { ", " }));
+            docs.push(self.operand(expression, IF));
+        }
+        Doc::Group(docs)
+    }
+
+    fn visit_assert(&mut self, test: &Expression, msg: Option<&Expression>) -> Doc {
+        let mut docs = vec![text("verifique "), self.operand(test, IF)];
+        if let Some(msg) = msg {
+            docs.push(text(", "));
+            docs.push(self.operand(msg, IF));
+        }
+        Doc::Group(docs)
+    }
+
+    fn visit_assign(&mut self, targets: &[Expression], value: &Expression) -> Doc {
+        let mut docs = Vec::new();
+        for target in targets {
+            docs.push(self.operand(target, TUPLE));
+            docs.push(text(" = "));
+        }
+        docs.push(self.operand(value, TUPLE));
+        Doc::Group(docs)
+    }
+
+    fn visit_aug_assign(&mut self, target: &Expression, op: Operator, value: &Expression) -> Doc {
+        Doc::Group(vec![
+            self.operand(target, TUPLE),
+            text(&format!(" {}= ", operator(op))),
+            self.operand(value, TUPLE),
+        ])
+    }
+
+    fn visit_expr_statement(&mut self, expression: &Expression) -> Doc {
+        self.operand(expression, TUPLE)
+    }
+
+    fn visit_import(&mut self, names: &[Alias]) -> Doc {
+        Doc::Text(format!("importe {}", aliases(names)))
+    }
+
+    fn visit_import_from(&mut self, module: &str, names: &[Alias]) -> Doc {
+        Doc::Text(format!("de {} importe {}", module, aliases(names)))
+    }
+
+    fn visit_with(&mut self, items: &[WithItem], body: &Statement) -> Doc {
+        let mut docs = vec![text("com ")];
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                docs.push(text(", "));
+            }
+            docs.push(self.operand(&item.context_expr, IF));
+            if let Some(target) = &item.optional_vars {
+                docs.push(text(" como "));
+                docs.push(self.operand(target, IF));
+            }
+        }
+        docs.push(text(": "));
+        docs.push(self.visit_statement(body));
+        Doc::Group(docs)
+    }
+
+    fn visit_compare(&mut self, a: &Expression, op: Comparison, b: &Expression) -> Doc {
+        let op = match op {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::Greater => ">",
+            Comparison::GreaterThan => ">=",
+            Comparison::Less => "<",
+            Comparison::LessThan => "<=",
+            Comparison::In => "em",
+            Comparison::NotIn => "nao em",
+            Comparison::Is => "é",
+        };
+        self.chain(a, op, b, COMPARE)
+    }
+
+    fn visit_bool_op(&mut self, a: &Expression, op: BooleanOperation, b: &Expression) -> Doc {
+        match op {
+            BooleanOperation::Or => self.chain(a, "ou", b, OR),
+            BooleanOperation::And => self.chain(a, "e", b, AND),
+        }
+    }
+
+    fn visit_bin_op(&mut self, a: &Expression, op: Operator, b: &Expression) -> Doc {
+        match op {
+            // right associative, and its left operand binds tighter than `-`
+            Operator::Pow => Doc::Group(vec![
+                self.operand(a, PRIMARY),
+                text(" ** "),
+                self.operand(b, UNARY),
+            ]),
+            Operator::Add | Operator::Sub => self.chain(a, operator(op), b, SUM),
+            _ => self.chain(a, operator(op), b, TERM),
+        }
+    }
+
+    fn visit_un_op(&mut self, op: UnaryOperation, a: &Expression) -> Doc {
+        match op {
+            UnaryOperation::Not => Doc::Group(vec![text("nao "), self.operand(a, NOT)]),
+            UnaryOperation::Minus => Doc::Group(vec![text("-"), self.operand(a, UNARY)]),
+            UnaryOperation::Plus => Doc::Group(vec![text("+"), self.operand(a, UNARY)]),
+        }
+    }
+
+    /// Texts are printed as written, escapes included.
+    fn visit_str(&mut self, value: &str) -> Doc {
+        if let Some(literal) = self.literals.next() {
+            return text(literal);
+        }
+        Doc::Text(format!("{:?}", value))
+    }
+
+    /// Numbers are printed as written, so `0xff` stays `0xff`.
+    fn visit_num(&mut self, value: &Number) -> Doc {
+        if let Some(literal) = self.literals.next() {
+            return text(literal);
+        }
+        Doc::Text(match value {
+            Number::Integer { value } => value.to_string(),
+            Number::BigInteger { value } => value.to_string(),
+            Number::Float { value } => format!("{:?}", value),
+        })
+    }
+
+    fn visit_if_expression(
+        &mut self,
+        test: &Expression,
+        body: &Expression,
+        orelse: &Expression,
+    ) -> Doc {
+        // a nested `se` is parenthesized, so a `senao` always belongs to
+        // the `se` it is printed with
+        let mut docs = vec![
+            text("se "),
+            self.operand(test, OR),
+            text(":"),
+            Doc::Nest(vec![Doc::Line, self.operand(body, OR)]),
+        ];
+        if *orelse != Expression::None {
+            docs.push(Doc::Line);
+            docs.push(text("senao: "));
+            docs.push(self.operand(orelse, IF));
+        }
+        Doc::Group(docs)
+    }
+
+    fn visit_call(
+        &mut self,
+        function: &Expression,
+        args: &[Expression],
+        keywords: &[Keyword],
+    ) -> Doc {
+        let function = self.operand(function, PRIMARY);
+        let mut items: Vec<Doc> = args.iter().map(|arg| self.operand(arg, IF)).collect();
+        items.extend(keywords.iter().map(|keyword| self.visit_keyword(keyword)));
+        Doc::Group(vec![function, Self::list("(", items, true, ")")])
+    }
+
+    fn visit_keyword(&mut self, keyword: &Keyword) -> Doc {
+        Doc::Group(vec![
+            Doc::Text(format!("{}=", keyword.name)),
+            self.operand(&keyword.value, IF),
+        ])
+    }
+
+    fn visit_identifier(&mut self, name: &str) -> Doc {
+        text(name)
+    }
+
+    fn visit_tuple(&mut self, elements: &[Expression]) -> Doc {
+        match elements {
+            [] => return text("()"),
+            [element] => {
+                let element = self.operand(element, IF);
+                return Doc::Group(vec![text("("), element, text(",)")]);
+            }
+            _ => {}
+        }
+
+        let mut docs = Vec::new();
+        for (i, element) in elements.iter().enumerate() {
+            if i > 0 {
+                docs.push(text(","));
+                docs.push(Doc::Line);
+            }
+            docs.push(self.operand(element, IF));
+        }
+        Doc::Group(docs)
+    }
+
+    fn visit_subscript(&mut self, value: &Expression, index: &Expression) -> Doc {
+        Doc::Group(vec![
+            self.operand(value, PRIMARY),
+            text("["),
+            self.operand(index, TUPLE),
+            text("]"),
+        ])
+    }
+
+    fn visit_attribute(&mut self, value: &Expression, name: &str) -> Doc {
+        // `1.x` would be read as a malformed number
+        let value = match value {
+            Expression::Num { .. } => Self::parenthesized(self.visit_expression(value)),
+            _ => self.operand(value, PRIMARY),
+        };
+        Doc::Group(vec![value, Doc::Text(format!(".{}", name))])
+    }
+
+    fn visit_true(&mut self) -> Doc {
+        text("Verdadeiro")
+    }
+
+    fn visit_false(&mut self) -> Doc {
+        text("Falso")
+    }
+
+    fn visit_none(&mut self) -> Doc {
+        text("Vazio")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_source;
+    use crate::parse::parse_program;
+
+    fn format(source: &str) -> String {
+        format_source(source).unwrap()
+    }
+
+    /// Formatting keeps the meaning of the program and a second pass changes
+    /// nothing.
+    fn assert_stable(source: &str) {
+        let formatted = format(source);
+        assert_eq!(
+            parse_program(source).unwrap(),
+            parse_program(&formatted).unwrap(),
+            "{}",
+            formatted
+        );
+        assert_eq!(formatted, format(&formatted));
+    }
+
+    #[test]
+    fn spacing_is_normalised() {
+        assert_eq!("x = 1 + 2 * 3\n", format("x=1+2*3"));
+        assert_eq!("a, b = b, a\n", format("a ,b=b,a"));
+        assert_eq!("t[i].nome += -2 ** 2\n", format("t [ i ] . nome+= - 2**2"));
+        assert_eq!("f(1, x=nao a)\n", format("f( 1 ,x = nao a , )"));
+        assert_eq!("y = ()\n", format("y=( )"));
+        assert_eq!(
+            "com abra(p) como f, g: f.escreva(g.leia())\n",
+            format("com abra( p )como f ,g : f.escreva(g.leia())")
+        );
+    }
+
+    #[test]
+    fn literals_are_kept_as_written() {
+        assert_eq!(
+            "x = 0xff + 1_000_000 * 0b101\n",
+            format("x=0xff+1_000_000*0b101")
+        );
+        assert_eq!(
+            "y = .5, 1e3, 2.50, -0o17\n",
+            format("y=(.5,1e3,2.50,-0o17)")
+        );
+        assert_eq!(
+            "f(1, x=se a: 2 senao: 3)[4]\n",
+            format("f(1,x=se a:2 senao:3)[4]")
+        );
+        assert_eq!(
+            "imprima(\"a\\tb\\n\", \"\\d+\", 1, fim=\"\\\"\")\n",
+            format("imprima( \"a\\tb\\n\",\"\\d+\",1,fim=\"\\\"\")")
+        );
+    }
+
+    #[test]
+    fn one_statement_per_line() {
+        assert_eq!(
+            "a = 1\nb = 2\nimprima(a)\n",
+            format("a = 1 b = 2 imprima(a)")
+        );
+        assert_eq!("", format("  \n\n"));
+    }
+
+    #[test]
+    fn only_needed_parentheses_are_kept() {
+        assert_eq!("x = (a + b) * c\n", format("x = ((a + b)) * (c)"));
+        assert_eq!("x = a - b - (c - d)\n", format("x = (a - b) - (c - d)"));
+        assert_eq!("x = (-2) ** 2 ** 3\n", format("x = (-2) ** (2 ** 3)"));
+        assert_eq!(
+            "x = (se a: b senao: c) + 1\n",
+            format("x = (se a: b senao: c) + 1")
+        );
+        assert_eq!("x = se (se a: b): c\n", format("x = se (se a: b): c"));
+        assert_eq!(
+            "x = (1).real, (a, b)[0]\n",
+            format("x = (1).real, (a, b)[0]")
+        );
+        assert_eq!("f((a, b))\n", format("f((a, b))"));
+    }
+
+    #[test]
+    fn long_lines_are_wrapped() {
+        let source =
+            "resultado = calcular(primeiro_argumento, segundo_argumento, terceiro=argumento_longo)";
+        assert_eq!(
+            "resultado = calcular(\n    primeiro_argumento,\n    segundo_argumento,\n    terceiro=argumento_longo,\n)\n",
+            format(source)
+        );
+
+        let source =
+            "total = primeira_parcela + segunda_parcela + terceira_parcela + quarta_parcela_longa";
+        assert_eq!(
+            "total = primeira_parcela\n    + segunda_parcela\n    + terceira_parcela\n    + quarta_parcela_longa\n",
+            format(source)
+        );
+    }
+
+    #[test]
+    fn comments_are_kept() {
+        let source = "#!/usr/bin/env apl\n#cabeçalho\nx = 1 #um\n\n\n\n# antes\ny = f(x, # dentro\n  2)\n# fim\n";
+        assert_eq!(
+            "#!/usr/bin/env apl\n# cabeçalho\nx = 1  # um\n\n# antes\n# dentro\ny = f(x, 2)\n# fim\n",
+            format(source)
+        );
+    }
+
+    #[test]
+    fn imports() {
+        assert_eq!(
+            "importe a, b como c\nde d importe h como f, g\n",
+            format("importe a,b  como c de d importe h como f ,g")
+        );
+    }
+
+    #[test]
+    fn trailing_comment_goes_to_the_last_statement_of_the_line() {
+        assert_eq!(
+            "a = 1\nb = 2  # c\nd = 3\n",
+            format("a = 1 b = 2 # c\nd = 3")
+        );
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let sources = [
+            "x=1+2*3 y=(x-1)//2%3 z = -x ** 2",
+            "a, b = b, a\nc = d = a",
+            "imprima(se a > b e nao c ou d é Vazio: \"sim\" senao: \"não\")",
+            "t.y , m.x = (1, 2) ,3",
+            "u = (1 ,) v = ((1, 2),) w = (a,)[0]",
+            "x **= 2 y //= 3\n\n\n\nz %= 4",
+            "n = 12345678901234567890123 f = 1.5e20 g = .5",
+            "resultado = calcular(primeiro_argumento, segundo_argumento, terceiro=se condicao: valor_verdadeiro senao: valor_falso)",
+            "# só comentários\n\n#outro",
+            "x = 1 # um\n# dois\n\n\ny = t[x]",
+            "passe interrompa continue retorne x verifique x < 1",
+            "importe a,b  como c de d importe h como f ,g",
+        ];
+        for source in sources.iter() {
+            assert_stable(source);
+        }
+    }
+
+    #[test]
+    fn parse_errors_are_reported() {
+        assert!(format_source("x = (1").is_err());
+        assert!(format_source("x = \"1").is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! Loading of the modules named by `importe` and `de ... importe`.
+//!
+//! A module is a `.apl` file, looked up first in the directory of the file
+//! that imports it, or the current directory for code that is not in a file,
+//! and then in each directory of the search path. Its code runs once, with
+//! globals of its own, and what it defines becomes the attributes of the
+//! module. Native modules made with `Engine::module` are found before files.
+
+use crate::ast::Program;
+use crate::engine::{evaluator, Backend};
+use crate::error::Error;
+use crate::native::Module;
+use crate::object::Object;
+use crate::optimizer::optimize;
+use crate::parse::{parse_program, ParseError};
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub(crate) struct Importer {
+    backend: Backend,
+    // what the globals of every module start with
+    builtins: HashMap<String, Object>,
+    optimize: Cell<bool>,
+    search_path: RefCell<Vec<PathBuf>>,
+    native: RefCell<HashMap<String, Module>>,
+    // by canonical path, so two ways of naming a file share the module
+    loaded: RefCell<HashMap<PathBuf, Module>>,
+    // the files being run, the innermost last
+    running: RefCell<Vec<PathBuf>>,
+}
+
+impl Importer {
+    pub fn new(backend: Backend, builtins: HashMap<String, Object>) -> Self {
+        Importer {
+            backend,
+            builtins,
+            optimize: Cell::new(false),
+            search_path: RefCell::new(Vec::new()),
+            native: RefCell::new(HashMap::new()),
+            loaded: RefCell::new(HashMap::new()),
+            running: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn builtins(&self) -> HashMap<String, Object> {
+        self.builtins.clone()
+    }
+
+    pub fn set_optimize(&self, enabled: bool) {
+        self.optimize.set(enabled);
+    }
+
+    pub fn add_search_path(&self, directory: PathBuf) {
+        self.search_path.borrow_mut().push(directory);
+    }
+
+    pub fn register(&self, module: Module) {
+        self.native
+            .borrow_mut()
+            .insert(module.name().to_string(), module);
+    }
+
+    /// Parses code for any evaluator sharing this importer, the modules
+    /// included.
+    pub fn parse(&self, source: &str) -> ::std::result::Result<Program, ParseError> {
+        let program = parse_program(source)?;
+        if self.optimize.get() {
+            Ok(optimize(program))
+        } else {
+            Ok(program)
+        }
+    }
+
+    /// Runs `run` as the code of `file`, so the imports in it are looked up
+    /// from its directory.
+    pub fn within<T>(&self, file: &Path, run: impl FnOnce() -> T) -> T {
+        let file = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+        self.running.borrow_mut().push(file);
+        let result = run();
+        self.running.borrow_mut().pop();
+        result
+    }
+
+    pub fn import(self: &Rc<Self>, name: &str) -> Result<Module> {
+        if let Some(module) = self.native.borrow().get(name) {
+            return Ok(module.clone());
+        }
+
+        let path = self
+            .find(name)
+            .ok_or_else(|| Error::ImportError(format!("módulo '{}' não encontrado", name)))?;
+        if let Some(module) = self.loaded.borrow().get(&path) {
+            return Ok(module.clone());
+        }
+        if let Some(start) = self.running.borrow().iter().position(|file| *file == path) {
+            let cycle: Vec<String> = self.running.borrow()[start..]
+                .iter()
+                .chain(Some(&path))
+                .map(|file| file_name(file))
+                .collect();
+            return Err(Error::ImportError(format!(
+                "importação circular: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let module = self.load(name, &path)?;
+        self.loaded.borrow_mut().insert(path, module.clone());
+        Ok(module)
+    }
+
+    /// The canonical path of the file of the module.
+    fn find(&self, name: &str) -> Option<PathBuf> {
+        let file = format!("{}.apl", name);
+        let importing = self
+            .running
+            .borrow()
+            .last()
+            .and_then(|file| file.parent())
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+
+        Some(importing)
+            .into_iter()
+            .chain(self.search_path.borrow().iter().cloned())
+            .map(|directory| directory.join(&file))
+            .find(|path| path.is_file())
+            .and_then(|path| path.canonicalize().ok())
+    }
+
+    fn load(self: &Rc<Self>, name: &str, path: &Path) -> Result<Module> {
+        let source = fs::read_to_string(path).map_err(|error| {
+            Error::ImportError(format!(
+                "não foi possível ler '{}': {}",
+                path.display(),
+                error
+            ))
+        })?;
+        let program = self
+            .parse(&source)
+            .map_err(|error| Error::ImportError(format!("{}: {}", file_name(path), error)))?;
+
+        let evaluator = evaluator(self.backend, Rc::clone(self));
+        self.within(path, || evaluator.eval(program))?;
+
+        let module = Module::new(name);
+        for (name, value) in evaluator.globals() {
+            if self.builtins.get(&name) != Some(&value) {
+                module.set(&name, value);
+            }
+        }
+        Ok(module)
+    }
+}
+
+/// The value `de modulo importe nome` binds.
+pub(crate) fn import_name(module: &Module, name: &str) -> Result<Object> {
+    module.get(name).ok_or_else(|| {
+        Error::ImportError(format!(
+            "não é possível importar '{}' de '{}'",
+            name,
+            module.name()
+        ))
+    })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
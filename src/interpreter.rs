@@ -1,102 +1,305 @@
-use crate::ast::Statement;
-use crate::ast::{BooleanOperation, Comparison, Expression, Operator, Program, UnaryOperation};
+use crate::ast::{Alias, BooleanOperation, Comparison, Expression, Keyword, Number, Operator};
+use crate::ast::{Program, Statement, UnaryOperation, WithItem};
+#[cfg(test)]
+use crate::builtin;
 use crate::error::Error;
 use crate::error::Error::OtherError;
+use crate::importer::{import_name, Importer};
 use crate::object::Object;
-use crate::primitive::Primitive;
+use crate::visitor::Visitor;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
 
 type Result<T> = ::std::result::Result<T, Error>;
 
-#[derive(Debug, PartialEq)]
-struct Interpreter;
+/// Evaluates the syntax tree directly. It is the reference implementation of
+/// the language; `vm::Vm` must agree with it.
+#[derive(Debug)]
+pub(crate) struct Interpreter {
+    globals: RefCell<HashMap<String, Object>>,
+    importer: Rc<Importer>,
+}
 
 impl Interpreter {
+    #[cfg(test)]
     pub fn init() -> Self {
-        Self
+        let builtins = builtin::globals(&Rc::new(builtin::Streams::standard()));
+        Self::with_importer(Rc::new(Importer::new(
+            crate::engine::Backend::TreeWalker,
+            builtins,
+        )))
+    }
+
+    pub fn with_importer(importer: Rc<Importer>) -> Self {
+        Self {
+            globals: RefCell::new(importer.builtins()),
+            importer,
+        }
     }
 
     pub fn eval(&self, program: Program) -> Result<Object> {
-        Ok(self.visit_program(program)?)
+        let mut visitor = self;
+        visitor.visit_program(&program)
+    }
+
+    pub fn set_global(&self, name: &str, value: Object) {
+        self.globals.borrow_mut().insert(name.into(), value);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Object> {
+        self.globals.borrow().get(name).cloned()
+    }
+
+    pub fn globals(&self) -> HashMap<String, Object> {
+        self.globals.borrow().clone()
+    }
+
+    fn evaluate(&self, expression: &Expression) -> Result<Object> {
+        let mut visitor = self;
+        visitor.visit_expression(expression)
+    }
+
+    fn lookup(&self, name: &str) -> Result<Object> {
+        self.globals
+            .borrow()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::UndefinedName(name.into()))
+    }
+
+    fn assign(&self, target: &Expression, value: Object) -> Result<()> {
+        match target {
+            Expression::Identifier { name } => {
+                self.globals.borrow_mut().insert(name.clone(), value);
+                Ok(())
+            }
+            Expression::Tuple { elements } => {
+                let values = value.unpack(elements.len())?;
+                elements
+                    .iter()
+                    .zip(values)
+                    .try_for_each(|(target, value)| self.assign(target, value))
+            }
+            Expression::Attribute {
+                value: object,
+                name,
+            } => self.evaluate(object)?.set_attribute(name, value),
+            _ => Err(OtherError("alvo de atribuição inválido".into())),
+        }
     }
 
-    fn visit_program(&self, program: Program) -> Result<Object> {
-        let Program { statements, .. } = program;
+    /// Enters the first item, runs the rest inside it and exits it whatever
+    /// happens; an error inside wins over one from exiting.
+    fn with_items(&self, items: &[WithItem], body: &Statement) -> Result<()> {
+        let (item, rest) = match items.split_first() {
+            Some(split) => split,
+            None => {
+                let mut visitor = self;
+                return visitor.visit_statement(body).map(|_| ());
+            }
+        };
+        let context = self.evaluate(&item.context_expr)?;
+        let value = context.enter()?;
+        let result = match &item.optional_vars {
+            Some(target) => self.assign(target, value),
+            None => Ok(()),
+        }
+        .and_then(|_| self.with_items(rest, body));
+        let exited = context.exit();
+        result.and(exited)
+    }
+
+    fn not_implemented(&self) -> Result<Object> {
+        Err(OtherError("statement not implemented yet".into()))
+    }
+}
+
+/// The interpreter keeps its state behind a `RefCell`, so a shared reference
+/// is enough to walk the tree.
+impl Visitor for &Interpreter {
+    type Output = Result<Object>;
 
+    fn default_output(&mut self) -> Result<Object> {
+        Ok(Object::Unit)
+    }
+
+    fn visit_program(&mut self, program: &Program) -> Result<Object> {
         let mut obj = Object::Unit;
-        for statement in statements {
+        for statement in &program.statements {
             obj = self.visit_statement(statement)?;
         }
 
         Ok(obj)
     }
 
-    fn visit_statement(&self, statement: Statement) -> Result<Object> {
-        match statement {
-            Statement::Expr { expression } => self.visit_expression(expression),
-            _ => Err(Error::OtherError("statement not implemented yet".into())),
+    fn visit_break(&mut self) -> Result<Object> {
+        self.not_implemented()
+    }
+
+    fn visit_continue(&mut self) -> Result<Object> {
+        self.not_implemented()
+    }
+
+    fn visit_return(&mut self, _value: Option<&[Expression]>) -> Result<Object> {
+        self.not_implemented()
+    }
+
+    fn visit_assert(&mut self, _test: &Expression, _msg: Option<&Expression>) -> Result<Object> {
+        self.not_implemented()
+    }
+
+    fn visit_assign(&mut self, targets: &[Expression], value: &Expression) -> Result<Object> {
+        let value = self.evaluate(value)?;
+        for target in targets {
+            self.assign(target, value.clone())?;
         }
+        Ok(Object::Unit)
     }
 
-    fn visit_expression(&self, expression: Expression) -> Result<Object> {
-        match expression {
-            Expression::IfExpression { test, body, orelse } => {
-                match self.visit_expression(*test)? {
-                    Object::Primitive(Primitive::Boolean(true)) => self.visit_expression(*body),
-                    Object::Primitive(Primitive::Boolean(false)) => self.visit_expression(*orelse),
-                    _ => Err(OtherError("should be true or false".into())),
-                }
-            }
-            //            Expression::Call { function, args, keywords } => {}
-            Expression::BinOp { a, op, b } => {
-                let a_obj = self.visit_expression(*a)?;
-                let b_obj = self.visit_expression(*b)?;
-                match op {
-                    Operator::Add => a_obj.add(&b_obj),
-                    Operator::Sub => a_obj.subtract(&b_obj),
-                    Operator::Mul => a_obj.multiply(&b_obj),
-                    Operator::Div => a_obj.real_divide(&b_obj),
-                    Operator::Mod => a_obj.module(&b_obj),
-                }
-            }
-            Expression::Compare { a, op, b } => {
-                let a_obj = self.visit_expression(*a)?;
-                let b_obj = self.visit_expression(*b)?;
-                match op {
-                    Comparison::Equal => a_obj.equal(&b_obj),
-                    Comparison::NotEqual => a_obj.not_equal(&b_obj),
-                    Comparison::Greater => a_obj.greater_than(&b_obj),
-                    Comparison::Less => a_obj.less_than(&b_obj),
-                    Comparison::GreaterThan => a_obj.greater_than_equal(&b_obj),
-                    Comparison::LessThan => a_obj.less_than_equal(&b_obj),
-                    Comparison::Is => a_obj.is(&b_obj),
-                    _ => Err(Error::OtherError("comparison not implemented yet".into())),
-                }
-            }
-            Expression::BoolOp { a, op, b } => {
-                let a_obj = self.visit_expression(*a)?;
-                let b_obj = self.visit_expression(*b)?;
-                match op {
-                    BooleanOperation::And => a_obj.and(&b_obj),
-                    BooleanOperation::Or => a_obj.or(&b_obj),
-                }
+    /// The parser lets only names and attributes through as targets.
+    fn visit_aug_assign(
+        &mut self,
+        target: &Expression,
+        op: Operator,
+        value: &Expression,
+    ) -> Result<Object> {
+        match target {
+            Expression::Identifier { name } => {
+                let current = self.lookup(name)?;
+                let value = self.evaluate(value)?;
+                let result = current.binary(op, &value)?;
+                self.globals.borrow_mut().insert(name.clone(), result);
             }
-            Expression::UnOp { op, a } => {
-                let a_obj = self.visit_expression(*a)?;
-                match op {
-                    UnaryOperation::Not => a_obj.negate(),
-                    UnaryOperation::Minus => a_obj.unary_minus(),
-                    UnaryOperation::Plus => a_obj.unary_plus(),
-                }
+            Expression::Attribute {
+                value: object,
+                name,
+            } => {
+                let object = self.evaluate(object)?;
+                let current = object.get_attribute(name)?;
+                let value = self.evaluate(value)?;
+                object.set_attribute(name, current.binary(op, &value)?)?;
             }
-            Expression::Str { value } => Ok(value.into()),
-            Expression::Num { value } => Ok(value.into()),
-            Expression::True => Ok(true.into()),
-            Expression::False => Ok(false.into()),
-            //            Expression::Identifier { name } => {
-            //                println!("identifier: {:?}", name);
-            //            }
-            _ => Err(Error::OtherError("expression not implemented yet".into())),
+            _ => return Err(OtherError("alvo de atribuição inválido".into())),
         }
+        Ok(Object::Unit)
+    }
+
+    fn visit_import(&mut self, names: &[Alias]) -> Result<Object> {
+        for alias in names {
+            let module = self.importer.import(&alias.name)?;
+            self.set_global(alias.bound_name(), module.into());
+        }
+        Ok(Object::Unit)
+    }
+
+    fn visit_import_from(&mut self, module: &str, names: &[Alias]) -> Result<Object> {
+        let module = self.importer.import(module)?;
+        for alias in names {
+            self.set_global(alias.bound_name(), import_name(&module, &alias.name)?);
+        }
+        Ok(Object::Unit)
+    }
+
+    fn visit_with(&mut self, items: &[WithItem], body: &Statement) -> Result<Object> {
+        self.with_items(items, body)?;
+        Ok(Object::Unit)
+    }
+
+    fn visit_compare(&mut self, a: &Expression, op: Comparison, b: &Expression) -> Result<Object> {
+        let a_obj = self.evaluate(a)?;
+        let b_obj = self.evaluate(b)?;
+        a_obj.compare(op, &b_obj)
+    }
+
+    fn visit_bool_op(
+        &mut self,
+        a: &Expression,
+        op: BooleanOperation,
+        b: &Expression,
+    ) -> Result<Object> {
+        let a_obj = self.evaluate(a)?;
+        let b_obj = self.evaluate(b)?;
+        a_obj.boolean(op, &b_obj)
+    }
+
+    fn visit_bin_op(&mut self, a: &Expression, op: Operator, b: &Expression) -> Result<Object> {
+        let a_obj = self.evaluate(a)?;
+        let b_obj = self.evaluate(b)?;
+        a_obj.binary(op, &b_obj)
+    }
+
+    fn visit_un_op(&mut self, op: UnaryOperation, a: &Expression) -> Result<Object> {
+        self.evaluate(a)?.unary(op)
+    }
+
+    fn visit_str(&mut self, value: &str) -> Result<Object> {
+        Ok(value.into())
+    }
+
+    fn visit_num(&mut self, value: &Number) -> Result<Object> {
+        Ok(value.clone().into())
+    }
+
+    fn visit_if_expression(
+        &mut self,
+        test: &Expression,
+        body: &Expression,
+        orelse: &Expression,
+    ) -> Result<Object> {
+        if self.evaluate(test)?.test()? {
+            self.evaluate(body)
+        } else {
+            self.evaluate(orelse)
+        }
+    }
+
+    fn visit_call(
+        &mut self,
+        function: &Expression,
+        args: &[Expression],
+        keywords: &[Keyword],
+    ) -> Result<Object> {
+        let function = self.evaluate(function)?;
+        let args = args
+            .iter()
+            .map(|arg| self.evaluate(arg))
+            .collect::<Result<Vec<_>>>()?;
+        let keywords = keywords
+            .iter()
+            .map(|keyword| Ok((keyword.name.clone(), self.visit_keyword(keyword)?)))
+            .collect::<Result<Vec<_>>>()?;
+        function.call(args, keywords)
+    }
+
+    fn visit_identifier(&mut self, name: &str) -> Result<Object> {
+        self.lookup(name)
+    }
+
+    fn visit_tuple(&mut self, elements: &[Expression]) -> Result<Object> {
+        Ok(Object::Tuple(
+            elements
+                .iter()
+                .map(|element| self.evaluate(element))
+                .collect::<Result<Vec<_>>>()?,
+        ))
+    }
+
+    fn visit_subscript(&mut self, value: &Expression, index: &Expression) -> Result<Object> {
+        let value = self.evaluate(value)?;
+        value.subscript(&self.evaluate(index)?)
+    }
+
+    fn visit_attribute(&mut self, value: &Expression, name: &str) -> Result<Object> {
+        self.evaluate(value)?.get_attribute(name)
+    }
+
+    fn visit_true(&mut self) -> Result<Object> {
+        Ok(true.into())
+    }
+
+    fn visit_false(&mut self) -> Result<Object> {
+        Ok(false.into())
     }
 }
 
@@ -109,7 +312,7 @@ mod comparison {
 
     #[test]
     fn equal() {
-        let mut parser_ast = parse_program(r#"1 == 0"#);
+        let parser_ast = parse_program(r#"1 == 0"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Boolean(false)), result.unwrap())
@@ -117,7 +320,7 @@ mod comparison {
 
     #[test]
     fn not_equal() {
-        let mut parser_ast = parse_program(r#"1 != 0"#);
+        let parser_ast = parse_program(r#"1 != 0"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Boolean(true)), result.unwrap())
@@ -125,7 +328,7 @@ mod comparison {
 
     #[test]
     fn greater() {
-        let mut parser_ast = parse_program(r#"1 > 0"#);
+        let parser_ast = parse_program(r#"1 > 0"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Boolean(true)), result.unwrap())
@@ -133,7 +336,7 @@ mod comparison {
 
     #[test]
     fn less() {
-        let mut parser_ast = parse_program(r#"1 < 0"#);
+        let parser_ast = parse_program(r#"1 < 0"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Boolean(false)), result.unwrap())
@@ -141,7 +344,7 @@ mod comparison {
 
     #[test]
     fn greater_equal() {
-        let mut parser_ast = parse_program(r#"1 >= 0"#);
+        let parser_ast = parse_program(r#"1 >= 0"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Boolean(true)), result.unwrap())
@@ -149,7 +352,7 @@ mod comparison {
 
     #[test]
     fn less_equal() {
-        let mut parser_ast = parse_program(r#"1 <= 0"#);
+        let parser_ast = parse_program(r#"1 <= 0"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Boolean(false)), result.unwrap())
@@ -157,7 +360,7 @@ mod comparison {
 
     #[test]
     fn is() {
-        let mut parser_ast = parse_program(r#"1 é 0"#);
+        let parser_ast = parse_program(r#"1 é 0"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Boolean(true)), result.unwrap())
@@ -173,7 +376,7 @@ mod bool_operation {
 
     #[test]
     fn and() {
-        let mut parser_ast = parse_program(r#"Verdadeiro e Verdadeiro"#);
+        let parser_ast = parse_program(r#"Verdadeiro e Verdadeiro"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Boolean(true)), result.unwrap())
@@ -181,7 +384,7 @@ mod bool_operation {
 
     #[test]
     fn or() {
-        let mut parser_ast = parse_program(r#"Verdadeiro e Verdadeiro"#);
+        let parser_ast = parse_program(r#"Verdadeiro e Verdadeiro"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Boolean(true)), result.unwrap())
@@ -197,7 +400,7 @@ mod unary_operation {
 
     #[test]
     fn not() {
-        let mut parser_ast = parse_program(r#"nao Falso"#);
+        let parser_ast = parse_program(r#"nao Falso"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Boolean(true)), result.unwrap())
@@ -205,7 +408,7 @@ mod unary_operation {
 
     #[test]
     fn minus() {
-        let mut parser_ast = parse_program(r#"-1"#);
+        let parser_ast = parse_program(r#"-1"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Integer(-1)), result.unwrap())
@@ -214,7 +417,7 @@ mod unary_operation {
     #[test]
     fn plus() {
         // FIXME: Add capacity of work with (), e.g: +(-1)
-        let mut parser_ast = parse_program(r#"1"#);
+        let parser_ast = parse_program(r#"1"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Integer(1)), result.unwrap())
@@ -223,14 +426,15 @@ mod unary_operation {
 
 #[cfg(test)]
 mod binary_operation {
+    use crate::error::{Error, OperatorError};
     use crate::interpreter::Interpreter;
     use crate::object::Object;
     use crate::parse::parse_program;
-    use crate::primitive::Primitive::{Boolean, Float, Integer};
+    use crate::primitive::Primitive::{Float, Integer};
 
     #[test]
     fn add() {
-        let mut parser_ast = parse_program(r#"9 + 3"#);
+        let parser_ast = parse_program(r#"9 + 3"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Integer(12)), result.unwrap())
@@ -238,7 +442,7 @@ mod binary_operation {
 
     #[test]
     fn sub() {
-        let mut parser_ast = parse_program(r#"9 - 3"#);
+        let parser_ast = parse_program(r#"9 - 3"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Integer(6)), result.unwrap())
@@ -246,7 +450,7 @@ mod binary_operation {
 
     #[test]
     fn mul() {
-        let mut parser_ast = parse_program(r#"9 * 3"#);
+        let parser_ast = parse_program(r#"9 * 3"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Integer(27)), result.unwrap())
@@ -254,7 +458,7 @@ mod binary_operation {
 
     #[test]
     fn div() {
-        let mut parser_ast = parse_program(r#"9 / 3"#);
+        let parser_ast = parse_program(r#"9 / 3"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Float(3.0)), result.unwrap())
@@ -262,11 +466,81 @@ mod binary_operation {
 
     #[test]
     fn module() {
-        let mut parser_ast = parse_program(r#"9 % 3"#);
+        let parser_ast = parse_program(r#"9 % 3"#);
         let interpreter = Interpreter::init();
         let result = interpreter.eval(parser_ast.unwrap());
         assert_eq!(Object::Primitive(Integer(0)), result.unwrap())
     }
+
+    #[test]
+    fn floor_div() {
+        let parser_ast = parse_program(r#"-9 // 2"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Object::Primitive(Integer(-5)), result.unwrap())
+    }
+
+    #[test]
+    fn power() {
+        let parser_ast = parse_program(r#"-2 ** 2 + 2 ** 3 ** 2"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Object::Primitive(Integer(508)), result.unwrap())
+    }
+
+    #[test]
+    fn power_float() {
+        let parser_ast = parse_program(r#"2 ** -2"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Object::Primitive(Float(0.25)), result.unwrap())
+    }
+
+    #[test]
+    fn number_literals() {
+        let parser_ast = parse_program(r#"0x10 + 0b11 * 1_000 + 0o7"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Object::Primitive(Integer(3023)), result.unwrap())
+    }
+
+    #[test]
+    fn precedence() {
+        let parser_ast = parse_program(r#"(1 + 2) * 3 - 4 / 2"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Object::Primitive(Float(7.0)), result.unwrap())
+    }
+
+    #[test]
+    fn div_by_zero() {
+        let parser_ast = parse_program(r#"9 / 0"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::RealDiv,
+                Integer(9),
+                Integer(0)
+            )),
+            result
+        )
+    }
+
+    #[test]
+    fn module_by_zero() {
+        let parser_ast = parse_program(r#"9 % 0.0"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::Module,
+                Integer(9),
+                Float(0.0)
+            )),
+            result
+        )
+    }
 }
 
 #[cfg(test)]
@@ -300,3 +574,79 @@ mod ifexpression {
         assert_eq!(Object::Primitive(Integer(4)), result.unwrap());
     }
 }
+
+#[cfg(test)]
+mod assignment {
+    use crate::error::Error;
+    use crate::interpreter::Interpreter;
+    use crate::object::Object;
+    use crate::parse::parse_program;
+    use crate::primitive::Primitive::Integer;
+
+    #[test]
+    fn chained() {
+        let parser_ast = parse_program(r#"a = b = 2 a + b"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Object::Primitive(Integer(4)), result.unwrap());
+    }
+
+    #[test]
+    fn augmented() {
+        let parser_ast = parse_program(r#"a = 10 a -= 3 a **= 2 a //= 5 a"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Object::Primitive(Integer(9)), result.unwrap());
+    }
+
+    #[test]
+    fn swap() {
+        let parser_ast = parse_program(r#"a, b = 1, 2 a, b = b, a a - b"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Object::Primitive(Integer(1)), result.unwrap());
+    }
+
+    #[test]
+    fn nested_unpacking() {
+        let parser_ast = parse_program(r#"a, (b, c) = 1, (2, 3) c"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Object::Primitive(Integer(3)), result.unwrap());
+    }
+
+    #[test]
+    fn unpacking_length_mismatch() {
+        let parser_ast = parse_program(r#"a, b = 1, 2, 3"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(
+            Err(Error::OtherError(
+                "esperava 2 valores para desempacotar, recebeu 3".into()
+            )),
+            result
+        );
+    }
+
+    #[test]
+    fn undefined_name() {
+        let parser_ast = parse_program(r#"x += 1"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Err(Error::UndefinedName("x".into())), result);
+    }
+
+    #[test]
+    fn subscript() {
+        let parser_ast = parse_program(r#"t = (1, 2, 3) t[-1]"#);
+        let interpreter = Interpreter::init();
+        let result = interpreter.eval(parser_ast.unwrap());
+        assert_eq!(Object::Primitive(Integer(3)), result.unwrap());
+    }
+
+    #[test]
+    fn tuple_subscript_is_read_only() {
+        assert!(parse_program(r#"t = (1, 2) t[0] = 5"#).is_err());
+        assert!(parse_program(r#"t = (1, 2) t[0] += 5"#).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::upper_case_acronyms, clippy::enum_variant_names)]
+
 extern crate itertools;
 
 mod token;
@@ -8,5 +10,33 @@ mod tokens;
 mod error;
 mod primitive;
 mod interpreter;
+mod compiler;
+mod vm;
+mod visitor;
+mod folder;
+mod formatter;
+mod linter;
+mod optimizer;
 mod object;
-mod builtin;
\ No newline at end of file
+mod builtin;
+mod engine;
+mod importer;
+mod stdlib;
+mod native;
+mod file;
+mod datetime;
+mod regex;
+pub mod cli;
+pub mod lsp;
+
+pub use crate::engine::{Backend, Engine, EngineError};
+pub use crate::datetime::DateTime;
+pub use crate::error::{Error, OperatorError};
+pub use crate::file::File;
+pub use crate::native::{Args, Arity, Module, NativeFunction};
+pub use crate::object::Object;
+pub use crate::parse::ParseError;
+pub use crate::primitive::Primitive;
+pub use crate::regex::{Match, Pattern};
+pub use crate::token::Token;
+pub use crate::tokenizer::{scan, scan_spanned, LexError, LexErrorKind, Position, Span};
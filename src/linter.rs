@@ -0,0 +1,471 @@
+//! Static checks run by `apl lint` before a program reaches the runtime.
+//!
+//! The program is walked in order, like the interpreter would run it, keeping
+//! track of which names are defined and which values were never read. Every
+//! finding points at the statement it was found in and names its rule, which
+//! can be turned off with a comment:
+//!
+//! ```text
+//! x = y  # lint: desative nome-indefinido
+//! # lint: desative-tudo variavel-nao-usada
+//! ```
+//!
+//! `desative` covers the line of the comment and the one after it,
+//! `desative-tudo` the whole file; without rules, they turn off all of them.
+
+use crate::ast::{Alias, Comparison, Expression, Operator, Program, Statement, WithItem};
+use crate::builtin::{self, Streams};
+use crate::parse::{parse_source, ParseError};
+use crate::tokenizer::Span;
+use crate::visitor::{walk_compare, walk_if_expression, Visitor};
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Rule {
+    UndefinedName,
+    UnusedVariable,
+    UnreachableCode,
+    NoneComparison,
+    ShadowedBuiltin,
+    /// Only `se` for now, `enquanto` is not parsed yet.
+    ConstantCondition,
+}
+
+impl Rule {
+    const ALL: [Rule; 6] = [
+        Rule::UndefinedName,
+        Rule::UnusedVariable,
+        Rule::UnreachableCode,
+        Rule::NoneComparison,
+        Rule::ShadowedBuiltin,
+        Rule::ConstantCondition,
+    ];
+
+    /// The name used in the output and in `# lint: desative` comments.
+    pub(crate) fn id(self) -> &'static str {
+        match self {
+            Rule::UndefinedName => "nome-indefinido",
+            Rule::UnusedVariable => "variavel-nao-usada",
+            Rule::UnreachableCode => "codigo-inalcancavel",
+            Rule::NoneComparison => "comparacao-com-vazio",
+            Rule::ShadowedBuiltin => "sombreia-embutida",
+            Rule::ConstantCondition => "condicao-constante",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Rule> {
+        Rule::ALL.iter().copied().find(|rule| rule.id() == id)
+    }
+
+    pub(crate) fn severity(self) -> Severity {
+        match self {
+            Rule::UndefinedName => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Severity {
+    /// The program fails when it gets there.
+    Error,
+    /// Probably a mistake, but the program runs.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "erro"),
+            Severity::Warning => write!(f, "aviso"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Finding {
+    pub(crate) rule: Rule,
+    pub(crate) span: Span,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}[{}]: {}",
+            self.span.start,
+            self.rule.severity(),
+            self.rule.id(),
+            self.message
+        )
+    }
+}
+
+/// Every finding of the source that is not disabled, in the order they appear.
+pub(crate) fn lint_source(source: &str) -> Result<Vec<Finding>, ParseError> {
+    let parsed = parse_source(source)?;
+    let mut linter = Linter::new(&parsed.spans);
+    linter.visit_program(&parsed.program);
+
+    let disabled = Disabled::new(&parsed.comments);
+    let mut findings: Vec<Finding> = linter
+        .findings
+        .into_iter()
+        .filter(|finding| !disabled.covers(finding))
+        .collect();
+    findings.sort_by_key(|finding| finding.span.start);
+    Ok(findings)
+}
+
+/// The rules turned off by `# lint:` comments.
+struct Disabled {
+    file: HashSet<Rule>,
+    lines: HashMap<usize, HashSet<Rule>>,
+}
+
+impl Disabled {
+    fn new(comments: &[(String, Span)]) -> Self {
+        let mut disabled = Disabled {
+            file: HashSet::new(),
+            lines: HashMap::new(),
+        };
+        for (text, span) in comments {
+            let directive = match text.trim().strip_prefix("lint:") {
+                Some(directive) => directive.trim(),
+                None => continue,
+            };
+            let (command, rules) =
+                directive.split_at(directive.find(' ').unwrap_or(directive.len()));
+            let rules: Vec<Rule> = if rules.trim().is_empty() {
+                Rule::ALL.to_vec()
+            } else {
+                rules
+                    .split(',')
+                    .filter_map(|id| Rule::from_id(id.trim()))
+                    .collect()
+            };
+            match command {
+                "desative" => {
+                    for line in span.start.line..=span.start.line + 1 {
+                        disabled
+                            .lines
+                            .entry(line)
+                            .or_default()
+                            .extend(rules.iter().copied());
+                    }
+                }
+                "desative-tudo" => disabled.file.extend(rules),
+                _ => {}
+            }
+        }
+        disabled
+    }
+
+    fn covers(&self, finding: &Finding) -> bool {
+        let span = finding.span;
+        self.file.contains(&finding.rule)
+            || (span.start.line..=span.end.line).any(|line| {
+                self.lines
+                    .get(&line)
+                    .is_some_and(|rules| rules.contains(&finding.rule))
+            })
+    }
+}
+
+struct Linter<'a> {
+    spans: &'a [Span],
+    // the statement being checked
+    current: usize,
+    builtins: HashSet<String>,
+    defined: HashSet<String>,
+    // names assigned and not read since, with the statement that assigned them;
+    // the ones left at the end are what the module exports, so only values
+    // overwritten before being read are reported
+    unread: HashMap<String, usize>,
+    findings: Vec<Finding>,
+}
+
+impl<'a> Linter<'a> {
+    fn new(spans: &'a [Span]) -> Self {
+        Linter {
+            spans,
+            current: 0,
+            builtins: builtin::globals(&Rc::new(Streams::standard()))
+                .into_keys()
+                .collect(),
+            defined: HashSet::new(),
+            unread: HashMap::new(),
+            findings: Vec::new(),
+        }
+    }
+
+    fn report(&mut self, rule: Rule, statement: usize, message: String) {
+        self.findings.push(Finding {
+            rule,
+            span: self.spans[statement],
+            message,
+        });
+    }
+
+    fn assign(&mut self, target: &Expression) {
+        match target {
+            Expression::Identifier { name } => {
+                if self.builtins.contains(name) {
+                    let message = format!("'{}' esconde a função embutida de mesmo nome", name);
+                    self.report(Rule::ShadowedBuiltin, self.current, message);
+                }
+                if let Some(statement) = self.unread.insert(name.clone(), self.current) {
+                    self.unused(name, statement);
+                }
+                self.defined.insert(name.clone());
+            }
+            Expression::Tuple { elements } => {
+                elements.iter().for_each(|target| self.assign(target))
+            }
+            // the object of an attribute is read, not assigned
+            target => self.visit_expression(target),
+        }
+    }
+
+    fn unused(&mut self, name: &str, statement: usize) {
+        if !name.starts_with('_') {
+            let message = format!("o valor atribuído a '{}' nunca é usado", name);
+            self.report(Rule::UnusedVariable, statement, message);
+        }
+    }
+}
+
+/// Made only of literals, so its value never changes.
+fn is_constant(expression: &Expression) -> bool {
+    match expression {
+        Expression::True
+        | Expression::False
+        | Expression::None
+        | Expression::Num { .. }
+        | Expression::Str { .. } => true,
+        Expression::UnOp { a, .. } => is_constant(a),
+        Expression::BinOp { a, b, .. }
+        | Expression::Compare { a, b, .. }
+        | Expression::BoolOp { a, b, .. } => is_constant(a) && is_constant(b),
+        Expression::Tuple { elements } => elements.iter().all(is_constant),
+        _ => false,
+    }
+}
+
+impl<'a> Visitor for Linter<'a> {
+    type Output = ();
+
+    fn default_output(&mut self) {}
+
+    fn visit_program(&mut self, program: &Program) {
+        let mut exit = None;
+        for (i, statement) in program.statements.iter().enumerate() {
+            if let Some(keyword) = exit.take() {
+                let span = Span {
+                    start: self.spans[i].start,
+                    end: self.spans[self.spans.len() - 1].end,
+                };
+                self.findings.push(Finding {
+                    rule: Rule::UnreachableCode,
+                    span,
+                    message: format!("código depois de '{}' nunca é executado", keyword),
+                });
+            }
+            exit = match statement {
+                Statement::Return { .. } => Some("retorne"),
+                Statement::Break => Some("interrompa"),
+                Statement::Continue => Some("continue"),
+                _ => None,
+            }
+            .filter(|_| i + 1 < program.statements.len());
+
+            self.current = i;
+            self.visit_statement(statement);
+        }
+    }
+
+    fn visit_assign(&mut self, targets: &[Expression], value: &Expression) {
+        self.visit_expression(value);
+        for target in targets {
+            self.assign(target);
+        }
+    }
+
+    fn visit_import(&mut self, names: &[Alias]) {
+        for alias in names {
+            self.defined.insert(alias.bound_name().into());
+        }
+    }
+
+    fn visit_import_from(&mut self, _module: &str, names: &[Alias]) {
+        self.visit_import(names)
+    }
+
+    fn visit_with(&mut self, items: &[WithItem], body: &Statement) {
+        for item in items {
+            self.visit_expression(&item.context_expr);
+            if let Some(target) = &item.optional_vars {
+                self.assign(target);
+            }
+        }
+        self.visit_statement(body);
+    }
+
+    fn visit_aug_assign(&mut self, target: &Expression, _op: Operator, value: &Expression) {
+        self.visit_expression(target);
+        self.visit_expression(value);
+        self.assign(target);
+    }
+
+    fn visit_compare(&mut self, a: &Expression, op: Comparison, b: &Expression) {
+        let equality = op == Comparison::Equal || op == Comparison::NotEqual;
+        if equality && (*a == Expression::None || *b == Expression::None) {
+            let message = "compare com Vazio usando 'é'".to_string();
+            self.report(Rule::NoneComparison, self.current, message);
+        }
+        walk_compare(self, a, op, b)
+    }
+
+    fn visit_if_expression(&mut self, test: &Expression, body: &Expression, orelse: &Expression) {
+        if is_constant(test) {
+            let message = "a condição do 'se' é sempre a mesma".to_string();
+            self.report(Rule::ConstantCondition, self.current, message);
+        }
+        walk_if_expression(self, test, body, orelse)
+    }
+
+    fn visit_identifier(&mut self, name: &str) {
+        self.unread.remove(name);
+        if !self.defined.contains(name) && !self.builtins.contains(name) {
+            let message = format!("nome '{}' não está definido", name);
+            self.report(Rule::UndefinedName, self.current, message);
+            // reported once, not at every use
+            self.defined.insert(name.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint_source, Rule};
+
+    fn rules(source: &str) -> Vec<(Rule, usize)> {
+        lint_source(source)
+            .unwrap()
+            .into_iter()
+            .map(|finding| (finding.rule, finding.span.start.line))
+            .collect()
+    }
+
+    #[test]
+    fn clean_program() {
+        assert_eq!(
+            vec![] as Vec<(Rule, usize)>,
+            rules("x = 1\nx += 2\nimprima(x)")
+        );
+    }
+
+    #[test]
+    fn undefined_names() {
+        assert_eq!(
+            vec![(Rule::UndefinedName, 1), (Rule::UndefinedName, 2)],
+            rules("imprima(y)\nx = x + 1\nimprima(x, y)")
+        );
+        // the object of an attribute target is read
+        assert_eq!(vec![(Rule::UndefinedName, 1)], rules("t.x = 1"));
+        assert_eq!(
+            vec![(Rule::UndefinedName, 2)],
+            rules("importe a como b de c importe d\nimprima(a.x, b.x, d)")
+        );
+    }
+
+    #[test]
+    fn unused_variables() {
+        assert_eq!(
+            vec![(Rule::UnusedVariable, 1), (Rule::UnusedVariable, 3)],
+            rules("x = 1\nx = 2\na, b = x, 3\nimprima(a)\nb = 4\n_ignorado = 4\n_ignorado = 5")
+        );
+        // what is left at the end is exported, other modules may read it
+        assert_eq!(
+            vec![] as Vec<(Rule, usize)>,
+            rules("x = 1\ny, z = 2, 3\nimprima(y)")
+        );
+    }
+
+    #[test]
+    fn unreachable_code() {
+        let findings = lint_source("passe\nretorne 1\nx = 1\nimprima(x)").unwrap();
+        assert_eq!(1, findings.len());
+        assert_eq!(Rule::UnreachableCode, findings[0].rule);
+        assert_eq!(3, findings[0].span.start.line);
+        assert_eq!(4, findings[0].span.end.line);
+        assert_eq!(vec![] as Vec<(Rule, usize)>, rules("passe\nretorne 1"));
+    }
+
+    #[test]
+    fn comparison_with_none() {
+        assert_eq!(
+            vec![(Rule::NoneComparison, 2), (Rule::NoneComparison, 2)],
+            rules("x = 1\nimprima(x == Vazio) imprima(Vazio != x)\nimprima(x é Vazio)")
+        );
+    }
+
+    #[test]
+    fn shadowed_builtins() {
+        assert_eq!(
+            vec![(Rule::ShadowedBuiltin, 1)],
+            rules("imprima = 1\nimprima")
+        );
+    }
+
+    #[test]
+    fn constant_conditions() {
+        assert_eq!(
+            vec![(Rule::ConstantCondition, 2), (Rule::ConstantCondition, 2)],
+            rules(
+                "x = 1\nimprima(se Verdadeiro: x) imprima(se 1 < 2: x senao: 0)\nimprima(se x: 1)"
+            )
+        );
+    }
+
+    #[test]
+    fn rules_can_be_disabled() {
+        assert_eq!(
+            vec![(Rule::UndefinedName, 5)],
+            rules(
+                "# lint: desative-tudo variavel-nao-usada\n\
+                 x = y  # lint: desative nome-indefinido, sombreia-embutida\n\
+                 # lint: desative nome-indefinido\nimprima(z)\nimprima(w)"
+            )
+        );
+        assert_eq!(
+            vec![(Rule::UndefinedName, 4)],
+            rules(
+                "imprima = y  # lint: desative\n# lint: desative\nimprima(z == Vazio)\nimprima(w)"
+            )
+        );
+        assert_eq!(
+            vec![] as Vec<(Rule, usize)>,
+            rules("# lint: desative-tudo\nimprima = y\nimprima(z == Vazio)")
+        );
+    }
+
+    #[test]
+    fn findings_are_displayed_with_position_and_rule() {
+        let findings = lint_source("x = 1\nx = 2\n  imprima(y)").unwrap();
+        assert_eq!(
+            vec![
+                "1:1: aviso[variavel-nao-usada]: o valor atribuído a 'x' nunca é usado",
+                "3:3: erro[nome-indefinido]: nome 'y' não está definido",
+            ],
+            findings
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+        );
+    }
+}
@@ -0,0 +1,612 @@
+//! A Language Server Protocol server, run by the `apl-lsp` binary.
+//!
+//! Messages are JSON-RPC over stdio, each preceded by a `Content-Length`
+//! header. Documents are synchronised whole on every change; each time one
+//! changes it is scanned, parsed and linted again and the diagnostics are
+//! published. The language has no functions yet, so definitions and symbols
+//! are the top-level variables.
+
+use crate::ast::{Alias, Expression, Statement};
+use crate::builtin::{self, Streams};
+use crate::linter::{lint_source, Severity};
+use crate::native::NativeFunction;
+use crate::parse::{parse_source, ParseError, ParsedSource};
+use crate::token::Token;
+use crate::tokenizer::{scan_spanned, Position, Span, KEYWORDS};
+
+use serde_json::{json, Value};
+
+use std::collections::HashMap;
+use std::io::prelude::*;
+use std::io::{self, BufRead};
+use std::rc::Rc;
+
+const TOKEN_TYPES: [&str; 7] = [
+    "keyword", "function", "variable", "string", "number", "operator", "comment",
+];
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+// LSP enumerations
+const SYNC_FULL: i64 = 1;
+const DIAGNOSTIC_ERROR: i64 = 1;
+const DIAGNOSTIC_WARNING: i64 = 2;
+const COMPLETION_FUNCTION: i64 = 3;
+const COMPLETION_VARIABLE: i64 = 6;
+const COMPLETION_KEYWORD: i64 = 14;
+const SYMBOL_VARIABLE: i64 = 13;
+
+/// Serves requests until the client sends `exit` and returns the exit status:
+/// 0 when `shutdown` came first, 1 otherwise.
+pub fn run<R: BufRead, W: Write>(mut input: R, output: W) -> i32 {
+    let mut server = Server {
+        output,
+        documents: HashMap::new(),
+        shutdown: false,
+    };
+    loop {
+        let message = match read_message(&mut input) {
+            Ok(Some(message)) => message,
+            Ok(None) | Err(_) => return if server.shutdown { 0 } else { 1 },
+        };
+        if let Some(status) = server.handle(&message) {
+            return status;
+        }
+    }
+}
+
+/// The body of the next message, or `None` at the end of the input.
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let length = length.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "cabeçalho Content-Length ausente",
+        )
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+struct Server<W: Write> {
+    output: W,
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+}
+
+type Response = Result<Value, (i64, String)>;
+
+impl<W: Write> Server<W> {
+    /// Returns the exit status when the message is `exit`.
+    fn handle(&mut self, message: &str) -> Option<i32> {
+        let message: Value = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(error) => {
+                self.respond(&Value::Null, Err((PARSE_ERROR, error.to_string())));
+                return None;
+            }
+        };
+
+        // without a method it is a response, and we never make requests
+        let method = message["method"].as_str()?;
+        let params = &message["params"];
+        let id = message.get("id");
+
+        match method {
+            "exit" => return Some(if self.shutdown { 0 } else { 1 }),
+            "initialized" | "$/cancelRequest" | "$/setTrace" => {}
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                self.update(uri(params), document["text"].as_str().unwrap_or_default());
+            }
+            "textDocument/didChange" => {
+                // full sync, so the last change is the whole text
+                let changes = params["contentChanges"].as_array();
+                if let Some(change) = changes.and_then(|changes| changes.last()) {
+                    self.update(uri(params), change["text"].as_str().unwrap_or_default());
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri(params));
+                self.publish(uri(params), Vec::new());
+            }
+            _ => {
+                if let Some(id) = id {
+                    let response = if self.shutdown {
+                        Err((INVALID_REQUEST, "o servidor foi encerrado".into()))
+                    } else {
+                        self.request(method, params)
+                    };
+                    self.respond(id, response);
+                }
+            }
+        }
+        None
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Response {
+        if method == "initialize" {
+            return Ok(capabilities());
+        }
+        if method == "shutdown" {
+            self.shutdown = true;
+            return Ok(Value::Null);
+        }
+
+        let document = match self.documents.get(uri(params)) {
+            Some(document) => document,
+            None if method.starts_with("textDocument/") => return Ok(Value::Null),
+            None => return Err((METHOD_NOT_FOUND, format!("método desconhecido: {}", method))),
+        };
+        let position = document.position(&params["position"]);
+        match method {
+            "textDocument/hover" => Ok(document.hover(position)),
+            "textDocument/definition" => Ok(document.definition(uri(params), position)),
+            "textDocument/documentSymbol" => Ok(document.symbols()),
+            "textDocument/completion" => Ok(document.completion(position)),
+            "textDocument/semanticTokens/full" => Ok(document.semantic_tokens()),
+            _ => Err((METHOD_NOT_FOUND, format!("método desconhecido: {}", method))),
+        }
+    }
+
+    fn update(&mut self, uri: &str, text: &str) {
+        let document = Document::new(text);
+        let diagnostics = document.diagnostics();
+        self.documents.insert(uri.into(), document);
+        self.publish(uri, diagnostics);
+    }
+
+    fn publish(&mut self, uri: &str, diagnostics: Vec<Value>) {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }));
+    }
+
+    fn respond(&mut self, id: &Value, response: Response) {
+        let message = match response {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        self.send(message);
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        // the client is gone when stdout fails, and `exit` will never come
+        let _ = write!(
+            self.output,
+            "Content-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .and_then(|_| self.output.flush());
+    }
+}
+
+fn uri(params: &Value) -> &str {
+    params["textDocument"]["uri"].as_str().unwrap_or_default()
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": SYNC_FULL,
+            "hoverProvider": true,
+            "definitionProvider": true,
+            "documentSymbolProvider": true,
+            "completionProvider": {},
+            "semanticTokensProvider": {
+                "legend": { "tokenTypes": TOKEN_TYPES, "tokenModifiers": [] },
+                "full": true,
+            },
+        },
+        "serverInfo": { "name": "apl-lsp", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+/// An open document. The tokens are empty and the program is missing while
+/// the text does not scan or parse.
+struct Document {
+    text: String,
+    lines: Vec<String>,
+    tokens: Vec<(Token, Span)>,
+    parsed: Option<ParsedSource>,
+}
+
+impl Document {
+    fn new(text: &str) -> Self {
+        Document {
+            text: text.into(),
+            lines: text.split('\n').map(String::from).collect(),
+            tokens: scan_spanned(text).unwrap_or_default(),
+            parsed: parse_source(text).ok(),
+        }
+    }
+
+    fn diagnostics(&self) -> Vec<Value> {
+        let diagnostic = |range: Value, severity: i64, code: Option<&str>, message: String| {
+            json!({
+                "range": range,
+                "severity": severity,
+                "code": code,
+                "source": "apl",
+                "message": message,
+            })
+        };
+
+        match lint_source(&self.text) {
+            Ok(findings) => findings
+                .into_iter()
+                .map(|finding| {
+                    let severity = match finding.rule.severity() {
+                        Severity::Error => DIAGNOSTIC_ERROR,
+                        Severity::Warning => DIAGNOSTIC_WARNING,
+                    };
+                    let range = self.range(finding.span);
+                    diagnostic(range, severity, Some(finding.rule.id()), finding.message)
+                })
+                .collect(),
+            Err(ParseError::Lex(errors)) => errors
+                .into_iter()
+                .map(|error| {
+                    let end = Position {
+                        line: error.position.line,
+                        column: error.position.column + 1,
+                    };
+                    let range = self.range(Span {
+                        start: error.position,
+                        end,
+                    });
+                    diagnostic(range, DIAGNOSTIC_ERROR, None, error.kind.to_string())
+                })
+                .collect(),
+            Err(ParseError::Syntax { message, span }) => {
+                vec![diagnostic(
+                    self.range(span),
+                    DIAGNOSTIC_ERROR,
+                    None,
+                    message,
+                )]
+            }
+            Err(error) => {
+                let start = Position { line: 1, column: 1 };
+                let range = self.range(Span { start, end: start });
+                vec![diagnostic(range, DIAGNOSTIC_ERROR, None, error.to_string())]
+            }
+        }
+    }
+
+    fn hover(&self, position: Position) -> Value {
+        let function = self
+            .identifier_at(position)
+            .and_then(|(name, _)| builtin(&name));
+        match function {
+            Some(function) => json!({
+                "contents": {
+                    "kind": "markdown",
+                    "value": format!("```apl\n{}\n```\n\n{}", function.signature(), function.get_doc()),
+                },
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn definition(&self, uri: &str, position: Position) -> Value {
+        let name = match self.identifier_at(position) {
+            Some((name, _)) => name,
+            None => return Value::Null,
+        };
+        match self
+            .definitions()
+            .into_iter()
+            .find(|(defined, ..)| *defined == name)
+        {
+            Some((_, span, _)) => json!({ "uri": uri, "range": self.range(span) }),
+            None => Value::Null,
+        }
+    }
+
+    fn symbols(&self) -> Value {
+        let symbols: Vec<Value> = self
+            .definitions()
+            .into_iter()
+            .map(|(name, span, statement)| {
+                json!({
+                    "name": name,
+                    "kind": SYMBOL_VARIABLE,
+                    "range": self.range(statement),
+                    "selectionRange": self.range(span),
+                })
+            })
+            .collect();
+        json!(symbols)
+    }
+
+    /// Keywords, builtins and the variables defined before the cursor; the
+    /// client filters them by what was typed.
+    fn completion(&self, position: Position) -> Value {
+        let mut items: Vec<Value> = KEYWORDS
+            .iter()
+            .filter(|(_, token)| !is_function(token))
+            .map(|(keyword, _)| json!({ "label": keyword, "kind": COMPLETION_KEYWORD }))
+            .collect();
+
+        let mut builtins = builtins();
+        builtins.sort_by(|a, b| a.name().cmp(b.name()));
+        items.extend(builtins.iter().map(|function| {
+            json!({
+                "label": function.name(),
+                "kind": COMPLETION_FUNCTION,
+                "detail": function.signature(),
+                "documentation": function.get_doc(),
+            })
+        }));
+
+        items.extend(
+            self.definitions()
+                .into_iter()
+                .filter(|(_, span, _)| span.start < position)
+                .map(|(name, ..)| json!({ "label": name, "kind": COMPLETION_VARIABLE })),
+        );
+        json!(items)
+    }
+
+    /// Every token with its type, encoded relative to the previous one as
+    /// the protocol asks.
+    fn semantic_tokens(&self) -> Value {
+        let mut data = Vec::new();
+        let mut previous = (0, 0);
+        for (token, span) in &self.tokens {
+            let kind = match token_type(token) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let start = self.lsp_position(span.start);
+            let end = self.lsp_position(span.end);
+            let delta_line = start.0 - previous.0;
+            let delta_start = if delta_line == 0 {
+                start.1 - previous.1
+            } else {
+                start.1
+            };
+            data.extend_from_slice(&[delta_line, delta_start, end.1 - start.1, kind, 0]);
+            previous = start;
+        }
+        json!({ "data": data })
+    }
+
+    /// The first assignment or import of each name: the name, where it is
+    /// and where the whole statement is.
+    fn definitions(&self) -> Vec<(String, Span, Span)> {
+        let parsed = match &self.parsed {
+            Some(parsed) => parsed,
+            None => return Vec::new(),
+        };
+
+        let mut definitions: Vec<(String, Span, Span)> = Vec::new();
+        for (statement, span) in parsed.program.statements.iter().zip(&parsed.spans) {
+            let mut names = Vec::new();
+            defined(statement, &mut names);
+
+            for name in names {
+                if definitions.iter().any(|(defined, ..)| defined == name) {
+                    continue;
+                }
+                let token = self.tokens.iter().find(|(token, token_span)| {
+                    token_span.start >= span.start && *token == Token::Identifier(name.into())
+                });
+                if let Some((_, name_span)) = token {
+                    definitions.push((name.into(), *name_span, *span));
+                }
+            }
+        }
+        definitions
+    }
+
+    fn identifier_at(&self, position: Position) -> Option<(String, Span)> {
+        self.tokens
+            .iter()
+            .find(|(_, span)| span.start <= position && position < span.end)
+            .and_then(|(token, span)| match token {
+                Token::Identifier(name) => Some((name.clone(), *span)),
+                Token::Imprima => Some(("imprima".into(), *span)),
+                Token::Entrada => Some(("entrada".into(), *span)),
+                _ => None,
+            })
+    }
+
+    fn range(&self, span: Span) -> Value {
+        let start = self.lsp_position(span.start);
+        let end = self.lsp_position(span.end);
+        json!({
+            "start": { "line": start.0, "character": start.1 },
+            "end": { "line": end.0, "character": end.1 },
+        })
+    }
+
+    /// Line and UTF-16 offset, both from 0, as the protocol counts them.
+    fn lsp_position(&self, position: Position) -> (u64, u64) {
+        let line = position.line - 1;
+        let character = self.lines.get(line).map_or(0, |text| {
+            text.chars()
+                .take(position.column - 1)
+                .map(char::len_utf16)
+                .sum::<usize>()
+        });
+        (line as u64, character as u64)
+    }
+
+    /// The reverse of `lsp_position`.
+    fn position(&self, position: &Value) -> Position {
+        let line = position["line"].as_u64().unwrap_or_default() as usize;
+        let character = position["character"].as_u64().unwrap_or_default() as usize;
+        let mut units = 0;
+        let column = self.lines.get(line).map_or(0, |text| {
+            text.chars()
+                .take_while(|c| {
+                    units += c.len_utf16();
+                    units <= character
+                })
+                .count()
+        });
+        Position {
+            line: line + 1,
+            column: column + 1,
+        }
+    }
+}
+
+/// The names a statement defines, including those of the statement inside
+/// a `com`.
+fn defined<'a>(statement: &'a Statement, names: &mut Vec<&'a str>) {
+    match statement {
+        Statement::Assign { targets, .. } => {
+            targets.iter().for_each(|target| assigned(target, names))
+        }
+        Statement::AugAssign { target, .. } => assigned(target, names),
+        Statement::Import { names: aliases } | Statement::ImportFrom { names: aliases, .. } => {
+            names.extend(aliases.iter().map(Alias::bound_name))
+        }
+        Statement::With { items, body } => {
+            for target in items.iter().filter_map(|item| item.optional_vars.as_ref()) {
+                assigned(target, names);
+            }
+            defined(body, names);
+        }
+        _ => {}
+    }
+}
+
+/// The names a target assigns to.
+fn assigned<'a>(target: &'a Expression, names: &mut Vec<&'a str>) {
+    match target {
+        Expression::Identifier { name } => names.push(name),
+        Expression::Tuple { elements } => {
+            elements.iter().for_each(|target| assigned(target, names))
+        }
+        _ => {}
+    }
+}
+
+/// The builtins, for their names and documentation; they are never called.
+fn builtins() -> Vec<NativeFunction> {
+    builtin::builtins(&Rc::new(Streams::standard()))
+}
+
+fn builtin(name: &str) -> Option<NativeFunction> {
+    builtins()
+        .into_iter()
+        .find(|function| function.name() == name)
+}
+
+/// `imprima` and `entrada` are scanned as keywords, but are functions.
+fn is_function(token: &Token) -> bool {
+    *token == Token::Imprima || *token == Token::Entrada
+}
+
+/// The index in `TOKEN_TYPES`, or `None` for punctuation.
+fn token_type(token: &Token) -> Option<u64> {
+    let kind = match token {
+        token if is_function(token) => "function",
+        token if KEYWORDS.iter().any(|(_, keyword)| keyword == token) => "keyword",
+        Token::Identifier(name) if builtin(name).is_some() => "function",
+        Token::Identifier(_) => "variable",
+        Token::Texto(_) => "string",
+        Token::Inteiro(_) | Token::InteiroGrande(_) | Token::Real(_) => "number",
+        Token::Comment(_) => "comment",
+        Token::ParentOpen
+        | Token::ParentClose
+        | Token::BraceOpen
+        | Token::BraceClose
+        | Token::BracketOpen
+        | Token::BracketClose
+        | Token::Colon
+        | Token::Comma
+        | Token::Dot
+        | Token::WhiteSpace => return None,
+        _ => "operator",
+    };
+    TOKEN_TYPES
+        .iter()
+        .position(|name| *name == kind)
+        .map(|index| index as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Document;
+    use crate::tokenizer::Position;
+    use serde_json::json;
+
+    #[test]
+    fn positions_are_converted_to_utf16() {
+        let document = Document::new("x = \"é😀\" y = 1\n  z");
+        let y = Position {
+            line: 1,
+            column: 10,
+        };
+        assert_eq!((0, 10), document.lsp_position(y));
+        assert_eq!(y, document.position(&json!({ "line": 0, "character": 10 })));
+        let z = Position { line: 2, column: 3 };
+        assert_eq!((1, 2), document.lsp_position(z));
+        assert_eq!(z, document.position(&json!({ "line": 1, "character": 2 })));
+    }
+
+    #[test]
+    fn semantic_tokens_are_relative() {
+        let document = Document::new("x = se a: 1 # c\n  imprima(\"t\")");
+        assert_eq!(
+            json!({ "data": [
+                0, 0, 1, 2, 0,
+                0, 2, 1, 5, 0,
+                0, 2, 2, 0, 0,
+                0, 3, 1, 2, 0,
+                0, 3, 1, 4, 0,
+                0, 2, 3, 6, 0,
+                1, 2, 7, 1, 0,
+                0, 8, 3, 3, 0,
+            ]}),
+            document.semantic_tokens()
+        );
+    }
+
+    #[test]
+    fn definitions_point_at_the_first_assignment() {
+        let document = Document::new("a = 1\nb, a = 2, 3\nt.x = a\nb += 1");
+        let names: Vec<(String, usize, usize)> = document
+            .definitions()
+            .into_iter()
+            .map(|(name, span, _)| (name, span.start.line, span.start.column))
+            .collect();
+        assert_eq!(
+            vec![("a".to_string(), 1, 1), ("b".to_string(), 2, 1)],
+            names
+        );
+    }
+}
@@ -1,4 +1,7 @@
+use std::env;
+use std::process;
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = env::args().collect();
+    process::exit(apl::cli::run(&args));
 }
-
@@ -0,0 +1,305 @@
+use crate::error::Error;
+use crate::object::Object;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+/// How many arguments a native function accepts, counting keywords that name
+/// one of its parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arity {
+    Exact(usize),
+    Range(usize, usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    /// How many of the parameters must always be given.
+    fn required(self) -> usize {
+        match self {
+            Arity::Exact(n) | Arity::Range(n, _) | Arity::AtLeast(n) => n,
+        }
+    }
+
+    fn accepts(self, count: usize) -> bool {
+        match self {
+            Arity::Exact(n) => count == n,
+            Arity::Range(min, max) => min <= count && count <= max,
+            Arity::AtLeast(min) => min <= count,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arity::Exact(n) => write!(f, "{}", n),
+            Arity::Range(min, max) => write!(f, "de {} a {}", min, max),
+            Arity::AtLeast(min) => write!(f, "pelo menos {}", min),
+        }
+    }
+}
+
+/// The arguments of a call to a native function.
+#[derive(Debug)]
+pub struct Args {
+    positional: Vec<Object>,
+    keywords: Vec<(String, Object)>,
+    params: Rc<Vec<String>>,
+}
+
+impl Args {
+    /// The argument for the parameter at `index`, given either by position or
+    /// by its name.
+    pub fn get(&self, index: usize) -> Option<&Object> {
+        self.positional
+            .get(index)
+            .or_else(|| self.params.get(index).and_then(|name| self.keyword(name)))
+    }
+
+    /// The name of the parameter at `index`, if it has one.
+    pub fn param(&self, index: usize) -> Option<&str> {
+        self.params.get(index).map(String::as_str)
+    }
+
+    pub fn keyword(&self, name: &str) -> Option<&Object> {
+        self.keywords
+            .iter()
+            .find(|(keyword, _)| keyword == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn positional(&self) -> &[Object] {
+        &self.positional
+    }
+
+    pub fn into_positional(self) -> Vec<Object> {
+        self.positional
+    }
+
+    pub fn len(&self) -> usize {
+        self.positional.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positional.is_empty()
+    }
+}
+
+/// A Rust function or closure that can be called from APL code.
+///
+/// ```
+/// use apl::{Arity, Engine, NativeFunction, Object};
+/// use std::convert::TryFrom;
+///
+/// let dobro = NativeFunction::new("dobro", |args| {
+///     let n = i64::try_from(args.get(0).cloned().unwrap())?;
+///     Ok(Object::from(n * 2))
+/// })
+/// .params(&["n"]);
+///
+/// let engine = Engine::new();
+/// engine.register(dobro);
+/// assert_eq!(Object::from(42), engine.eval_expr("dobro(n=21)").unwrap());
+/// ```
+#[derive(Clone)]
+pub struct NativeFunction {
+    name: String,
+    params: Rc<Vec<String>>,
+    rest: Option<String>,
+    keywords: Rc<Vec<String>>,
+    arity: Arity,
+    doc: String,
+    function: Rc<dyn Fn(Args) -> Result<Object>>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, function: F) -> Self
+    where
+        F: Fn(Args) -> Result<Object> + 'static,
+    {
+        NativeFunction {
+            name: name.into(),
+            params: Rc::new(Vec::new()),
+            rest: None,
+            keywords: Rc::new(Vec::new()),
+            arity: Arity::AtLeast(0),
+            doc: String::new(),
+            function: Rc::new(function),
+        }
+    }
+
+    /// Names the parameters, which makes them accepted as keywords too, and
+    /// sets the arity to exactly that many arguments.
+    pub fn params(mut self, params: &[&str]) -> Self {
+        self.params = Rc::new(params.iter().map(|p| p.to_string()).collect());
+        self.arity = Arity::Exact(params.len());
+        self
+    }
+
+    /// Accepts any number of positional arguments after the named parameters;
+    /// `name` is how the signature shows them.
+    pub fn rest(mut self, name: &str) -> Self {
+        self.rest = Some(name.into());
+        self.arity = Arity::AtLeast(self.params.len());
+        self
+    }
+
+    /// Keyword only arguments, such as `sep` in `imprima(a, b, sep=", ")`.
+    pub fn keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = Rc::new(keywords.iter().map(|k| k.to_string()).collect());
+        self
+    }
+
+    pub fn arity(mut self, arity: Arity) -> Self {
+        self.arity = arity;
+        self
+    }
+
+    pub fn doc(mut self, doc: &str) -> Self {
+        self.doc = doc.into();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_doc(&self) -> &str {
+        &self.doc
+    }
+
+    /// How the function is called, e.g. `imprima(*valores, sep=…)`.
+    pub fn signature(&self) -> String {
+        let rest = self.rest.iter().map(|rest| format!("*{}", rest));
+        let keywords = self.keywords.iter().map(|keyword| format!("{}=…", keyword));
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .cloned()
+            .chain(rest)
+            .chain(keywords)
+            .collect();
+        format!("{}({})", self.name, params.join(", "))
+    }
+
+    pub fn call(&self, positional: Vec<Object>, keywords: Vec<(String, Object)>) -> Result<Object> {
+        let mut count = positional.len();
+        for (name, _) in &keywords {
+            match self.params.iter().position(|param| param == name) {
+                Some(index) if index < positional.len() => {
+                    return Err(Error::InvalidArgument(format!(
+                        "{}() recebeu múltiplos valores para o argumento '{}'",
+                        self.name, name
+                    )))
+                }
+                Some(_) => count += 1,
+                None if self.keywords.contains(name) => {}
+                None => {
+                    return Err(Error::InvalidArgument(format!(
+                        "{}() não aceita o argumento nomeado '{}'",
+                        self.name, name
+                    )))
+                }
+            }
+        }
+
+        if !self.arity.accepts(count) {
+            return Err(Error::InvalidArgument(format!(
+                "{}({}) recebe {} argumento(s), mas recebeu {}",
+                self.name,
+                self.params.join(", "),
+                self.arity,
+                count
+            )));
+        }
+        // a keyword filling a later parameter does not stand in for a
+        // required one left out, as in `soma(inicio=1)`
+        let given = |index: usize, name: &String| {
+            index < positional.len() || keywords.iter().any(|(keyword, _)| keyword == name)
+        };
+        let required = self.arity.required().min(self.params.len());
+        for (index, name) in self.params[..required].iter().enumerate() {
+            if !given(index, name) {
+                return Err(Error::InvalidArgument(format!(
+                    "{}() não recebeu o argumento obrigatório '{}'",
+                    self.name, name
+                )));
+            }
+        }
+
+        (self.function)(Args {
+            positional,
+            keywords,
+            params: Rc::clone(&self.params),
+        })
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<função nativa {}>", self.name)
+    }
+}
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.function, &other.function)
+    }
+}
+
+/// A namespace of values reachable through attributes, as in `modulo.nome`.
+/// Clones share the same attributes.
+#[derive(Clone)]
+pub struct Module {
+    name: String,
+    attributes: Rc<RefCell<HashMap<String, Object>>>,
+}
+
+impl Module {
+    pub fn new(name: &str) -> Self {
+        Module {
+            name: name.into(),
+            attributes: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get(&self, name: &str) -> Option<Object> {
+        self.attributes.borrow().get(name).cloned()
+    }
+
+    /// The names of the attributes, in alphabetical order.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.attributes.borrow().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn set(&self, name: &str, value: Object) {
+        self.attributes.borrow_mut().insert(name.into(), value);
+    }
+
+    pub fn register(&self, function: NativeFunction) {
+        self.set(&function.name.clone(), function.into());
+    }
+}
+
+impl fmt::Debug for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<módulo {}>", self.name)
+    }
+}
+
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.attributes, &other.attributes)
+    }
+}
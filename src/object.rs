@@ -1,20 +1,51 @@
-use crate::ast::Number;
+use crate::ast::{BooleanOperation, Comparison, Number, Operator, UnaryOperation};
+use crate::datetime::DateTime;
 use crate::error::{Error, OperatorError};
+use crate::file::File;
+use crate::native::{Module, NativeFunction};
 use crate::primitive::Primitive;
+use crate::regex::{Match, Pattern};
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::rc::Rc;
 
 type Result<T> = ::std::result::Result<T, Error>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Object {
     Primitive(Primitive),
-    Builtin(Builtin),
+    Native(Rc<NativeFunction>),
+    Module(Module),
+    File(File),
+    DateTime(DateTime),
+    Pattern(Pattern),
+    Match(Match),
+    Tuple(Vec<Object>),
     Unit,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum Builtin {
-    Print(fn(Object) -> Result<Object>),
-    Input(fn() -> Result<Object>),
+/// What `imprima` writes for the value.
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Object::Primitive(primitive) => write!(f, "{}", primitive),
+            Object::Native(function) => write!(f, "{:?}", function),
+            Object::Module(module) => write!(f, "{:?}", module),
+            Object::File(file) => write!(f, "{:?}", file),
+            Object::DateTime(datetime) => write!(f, "{}", datetime),
+            Object::Pattern(pattern) => write!(f, "{:?}", pattern),
+            Object::Match(found) => write!(f, "{:?}", found),
+            Object::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(Object::repr).collect();
+                match elements.as_slice() {
+                    [element] => write!(f, "({},)", element),
+                    elements => write!(f, "({})", elements.join(", ")),
+                }
+            }
+            Object::Unit => write!(f, "Vazio"),
+        }
+    }
 }
 
 impl From<i64> for Object {
@@ -35,6 +66,54 @@ impl From<String> for Object {
     }
 }
 
+impl From<&str> for Object {
+    fn from(n: &str) -> Self {
+        Object::Primitive(Primitive::Str(n.into()))
+    }
+}
+
+impl<T: Into<Object>> From<Vec<T>> for Object {
+    fn from(n: Vec<T>) -> Self {
+        Object::Tuple(n.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<NativeFunction> for Object {
+    fn from(n: NativeFunction) -> Self {
+        Object::Native(Rc::new(n))
+    }
+}
+
+impl From<Module> for Object {
+    fn from(n: Module) -> Self {
+        Object::Module(n)
+    }
+}
+
+impl From<File> for Object {
+    fn from(n: File) -> Self {
+        Object::File(n)
+    }
+}
+
+impl From<DateTime> for Object {
+    fn from(n: DateTime) -> Self {
+        Object::DateTime(n)
+    }
+}
+
+impl From<Pattern> for Object {
+    fn from(n: Pattern) -> Self {
+        Object::Pattern(n)
+    }
+}
+
+impl From<Match> for Object {
+    fn from(n: Match) -> Self {
+        Object::Match(n)
+    }
+}
+
 impl From<bool> for Object {
     fn from(n: bool) -> Self {
         Object::Primitive(Primitive::Boolean(n))
@@ -45,6 +124,7 @@ impl From<Number> for Object {
     fn from(s: Number) -> Self {
         match s {
             Number::Integer { value } => Object::Primitive(Primitive::Integer(value)),
+            Number::BigInteger { value } => Object::Primitive(Primitive::BigInteger(value)),
             Number::Float { value } => Object::Primitive(Primitive::Float(value)),
         }
     }
@@ -56,7 +136,94 @@ impl From<Primitive> for Object {
     }
 }
 
+impl TryFrom<Object> for i64 {
+    type Error = Error;
+
+    fn try_from(o: Object) -> Result<Self> {
+        match o {
+            Object::Primitive(Primitive::Integer(n)) => Ok(n),
+            other => Err(Error::InvalidConversion(other, "inteiro")),
+        }
+    }
+}
+
+impl TryFrom<Object> for f64 {
+    type Error = Error;
+
+    fn try_from(o: Object) -> Result<Self> {
+        match o {
+            Object::Primitive(Primitive::Float(n)) => Ok(n),
+            Object::Primitive(Primitive::Integer(n)) => Ok(n as f64),
+            other => Err(Error::InvalidConversion(other, "real")),
+        }
+    }
+}
+
+impl TryFrom<Object> for String {
+    type Error = Error;
+
+    fn try_from(o: Object) -> Result<Self> {
+        match o {
+            Object::Primitive(Primitive::Str(s)) => Ok(s),
+            other => Err(Error::InvalidConversion(other, "texto")),
+        }
+    }
+}
+
+impl TryFrom<Object> for bool {
+    type Error = Error;
+
+    fn try_from(o: Object) -> Result<Self> {
+        match o {
+            Object::Primitive(Primitive::Boolean(b)) => Ok(b),
+            other => Err(Error::InvalidConversion(other, "logico")),
+        }
+    }
+}
+
+impl<T: TryFrom<Object, Error = Error>> TryFrom<Object> for Vec<T> {
+    type Error = Error;
+
+    fn try_from(o: Object) -> Result<Self> {
+        match o {
+            Object::Tuple(elements) => elements.into_iter().map(T::try_from).collect(),
+            other => Err(Error::InvalidConversion(other, "tupla")),
+        }
+    }
+}
+
 impl Object {
+    /// The name of the type of the value, as error messages show it.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Primitive(primitive) => primitive.type_name(),
+            Object::Native(_) => "função nativa",
+            Object::Module(_) => "módulo",
+            Object::File(_) => "arquivo",
+            Object::DateTime(_) => "datahora",
+            Object::Pattern(_) => "padrão",
+            Object::Match(_) => "correspondência",
+            Object::Tuple(_) => "tupla",
+            Object::Unit => "Vazio",
+        }
+    }
+
+    /// Like `Display`, but texts are quoted, so `("1", 1)` shows both apart.
+    pub fn repr(&self) -> String {
+        match self {
+            Object::Primitive(primitive) => primitive.repr(),
+            other => other.to_string(),
+        }
+    }
+
+    /// The value as error messages show it, with texts in single quotes.
+    pub fn describe(&self) -> String {
+        match self {
+            Object::Primitive(primitive) => primitive.describe(),
+            other => other.repr(),
+        }
+    }
+
     pub fn negate(&self) -> Result<Self> {
         if let Object::Primitive(p) = self {
             return Ok(p.negate()?.into());
@@ -68,7 +235,7 @@ impl Object {
         if let Object::Primitive(p) = self {
             return Ok(p.plus()?.into());
         }
-        self.error(None, OperatorError::UnarySub)
+        self.error(None, OperatorError::UnaryPlus)
     }
 
     pub fn unary_minus(&self) -> Result<Self> {
@@ -81,6 +248,12 @@ impl Object {
     pub fn add(&self, other: &Self) -> Result<Self> {
         match (self, other) {
             (Object::Primitive(l), Object::Primitive(r)) => Ok(l.add(r)?.into()),
+            (Object::DateTime(d), Object::Primitive(seconds))
+            | (Object::Primitive(seconds), Object::DateTime(d))
+                if seconds.is_number() =>
+            {
+                Ok(d.add_seconds(seconds)?.into())
+            }
             (_, r) => self.error(Some(r), OperatorError::Add),
         }
     }
@@ -88,6 +261,10 @@ impl Object {
     pub fn subtract(&self, other: &Self) -> Result<Self> {
         match (self, other) {
             (Object::Primitive(l), Object::Primitive(r)) => Ok(l.sub(r)?.into()),
+            (Object::DateTime(d), Object::Primitive(seconds)) if seconds.is_number() => {
+                Ok(d.add_seconds(&seconds.minus()?)?.into())
+            }
+            (Object::DateTime(l), Object::DateTime(r)) => Ok(l.seconds_since(r).into()),
             (_, r) => self.error(Some(r), OperatorError::Sub),
         }
     }
@@ -113,6 +290,13 @@ impl Object {
         }
     }
 
+    pub fn power(&self, other: &Self) -> Result<Self> {
+        match (self, other) {
+            (Object::Primitive(l), Object::Primitive(r)) => Ok(l.pow(r)?.into()),
+            (_, r) => self.error(Some(r), OperatorError::Pow),
+        }
+    }
+
     pub fn module(&self, other: &Self) -> Result<Self> {
         match (self, other) {
             (Object::Primitive(l), Object::Primitive(r)) => Ok(l.module(r)?.into()),
@@ -144,6 +328,7 @@ impl Object {
     pub fn less_than(&self, other: &Self) -> Result<Self> {
         match (self, other) {
             (Object::Primitive(l), Object::Primitive(r)) => Ok(l.less_than(r)?.into()),
+            (Object::DateTime(l), Object::DateTime(r)) => Ok((l < r).into()),
             (_, r) => self.error(Some(r), OperatorError::LessThan),
         }
     }
@@ -151,6 +336,7 @@ impl Object {
     pub fn greater_than(&self, other: &Self) -> Result<Self> {
         match (self, other) {
             (Object::Primitive(l), Object::Primitive(r)) => Ok(l.greater_than(r)?.into()),
+            (Object::DateTime(l), Object::DateTime(r)) => Ok((l > r).into()),
             (_, r) => self.error(Some(r), OperatorError::GreaterThan),
         }
     }
@@ -158,6 +344,7 @@ impl Object {
     pub fn less_than_equal(&self, other: &Self) -> Result<Self> {
         match (self, other) {
             (Object::Primitive(l), Object::Primitive(r)) => Ok(l.less_than_equal(r)?.into()),
+            (Object::DateTime(l), Object::DateTime(r)) => Ok((l <= r).into()),
             (_, r) => self.error(Some(r), OperatorError::LessThanEqual),
         }
     }
@@ -165,6 +352,7 @@ impl Object {
     pub fn greater_than_equal(&self, other: &Self) -> Result<Self> {
         match (self, other) {
             (Object::Primitive(l), Object::Primitive(r)) => Ok(l.greater_than_equal(r)?.into()),
+            (Object::DateTime(l), Object::DateTime(r)) => Ok((l >= r).into()),
             (_, r) => self.error(Some(r), OperatorError::GreaterThanEqual),
         }
     }
@@ -172,6 +360,8 @@ impl Object {
     pub fn equal(&self, other: &Self) -> Result<Self> {
         match (self, other) {
             (Object::Primitive(l), Object::Primitive(r)) => Ok(l.eq(r)?.into()),
+            (Object::DateTime(l), Object::DateTime(r)) => Ok((l == r).into()),
+            (Object::Pattern(l), Object::Pattern(r)) => Ok((l == r).into()),
             (_, r) => self.error(Some(r), OperatorError::Equal),
         }
     }
@@ -179,18 +369,214 @@ impl Object {
     pub fn not_equal(&self, other: &Self) -> Result<Self> {
         match (self, other) {
             (Object::Primitive(l), Object::Primitive(r)) => Ok(l.not_eq(r)?.into()),
+            (Object::DateTime(l), Object::DateTime(r)) => Ok((l != r).into()),
+            (Object::Pattern(l), Object::Pattern(r)) => Ok((l != r).into()),
             (_, r) => self.error(Some(r), OperatorError::NotEqual),
         }
     }
 
+    pub fn subscript(&self, index: &Self) -> Result<Self> {
+        match (self, index) {
+            (Object::Tuple(elements), Object::Primitive(Primitive::Integer(i))) => {
+                let position = Self::position(*i, elements.len())?;
+                Ok(elements[position].clone())
+            }
+            (Object::Primitive(Primitive::Str(s)), Object::Primitive(Primitive::Integer(i))) => {
+                let position = Self::position(*i, s.chars().count())?;
+                Ok(s.chars().nth(position).unwrap().to_string().into())
+            }
+            (_, r) => self.error(Some(r), OperatorError::Subscript),
+        }
+    }
+
+    pub fn binary(&self, op: Operator, other: &Self) -> Result<Self> {
+        match op {
+            Operator::Add => self.add(other),
+            Operator::Sub => self.subtract(other),
+            Operator::Mul => self.multiply(other),
+            Operator::Div => self.real_divide(other),
+            Operator::FloorDiv => self.int_divide(other),
+            Operator::Mod => self.module(other),
+            Operator::Pow => self.power(other),
+        }
+    }
+
+    pub fn compare(&self, op: Comparison, other: &Self) -> Result<Self> {
+        match op {
+            Comparison::Equal => self.equal(other),
+            Comparison::NotEqual => self.not_equal(other),
+            Comparison::Greater => self.greater_than(other),
+            Comparison::Less => self.less_than(other),
+            Comparison::GreaterThan => self.greater_than_equal(other),
+            Comparison::LessThan => self.less_than_equal(other),
+            Comparison::Is => self.is(other),
+            _ => Err(Error::OtherError("comparison not implemented yet".into())),
+        }
+    }
+
+    pub fn boolean(&self, op: BooleanOperation, other: &Self) -> Result<Self> {
+        match op {
+            BooleanOperation::And => self.and(other),
+            BooleanOperation::Or => self.or(other),
+        }
+    }
+
+    pub fn unary(&self, op: UnaryOperation) -> Result<Self> {
+        match op {
+            UnaryOperation::Not => self.negate(),
+            UnaryOperation::Minus => self.unary_minus(),
+            UnaryOperation::Plus => self.unary_plus(),
+        }
+    }
+
+    /// The condition of a `se` expression must be a boolean.
+    pub fn test(&self) -> Result<bool> {
+        match self {
+            Object::Primitive(Primitive::Boolean(b)) => Ok(*b),
+            other => Err(Error::InvalidArgument(format!(
+                "a condição deve ser Verdadeiro ou Falso, não {}",
+                other.describe()
+            ))),
+        }
+    }
+
+    /// What `logico(valor)` is: zeros, empty texts and tuples and `Vazio`
+    /// are false, everything else is true.
+    pub fn truthy(&self) -> bool {
+        match self {
+            Object::Primitive(Primitive::Integer(n)) => *n != 0,
+            Object::Primitive(Primitive::BigInteger(_)) => true,
+            Object::Primitive(Primitive::Float(n)) => *n != 0.0,
+            Object::Primitive(Primitive::Str(s)) => !s.is_empty(),
+            Object::Primitive(Primitive::Boolean(b)) => *b,
+            Object::Tuple(elements) => !elements.is_empty(),
+            Object::Native(_)
+            | Object::Module(_)
+            | Object::File(_)
+            | Object::DateTime(_)
+            | Object::Pattern(_)
+            | Object::Match(_) => true,
+            Object::Unit => false,
+        }
+    }
+
+    /// The elements of a tuple, the characters of a text, or the remaining
+    /// lines of a file.
+    pub fn items(&self) -> Result<Vec<Self>> {
+        match self {
+            Object::Tuple(elements) => Ok(elements.clone()),
+            Object::File(file) => Ok(file.lines()?.into_iter().map(Into::into).collect()),
+            Object::Primitive(Primitive::Str(s)) => {
+                Ok(s.chars().map(|c| c.to_string().into()).collect())
+            }
+            other => Err(Error::InvalidArgument(format!(
+                "{} não é iterável",
+                other.type_name()
+            ))),
+        }
+    }
+
+    pub fn call(&self, args: Vec<Self>, keywords: Vec<(String, Self)>) -> Result<Self> {
+        match self {
+            Object::Native(function) => function.call(args, keywords),
+            other => Err(Error::OtherError(format!("{} não é chamável", other.describe()))),
+        }
+    }
+
+    /// Splits the value for `a, b = valor`.
+    pub fn unpack(self, count: usize) -> Result<Vec<Self>> {
+        match self {
+            Object::Tuple(values) if values.len() == count => Ok(values),
+            Object::Tuple(values) => Err(Error::OtherError(format!(
+                "esperava {} valores para desempacotar, recebeu {}",
+                count,
+                values.len()
+            ))),
+            other => Err(Error::OtherError(format!(
+                "não é possível desempacotar {}",
+                other.describe()
+            ))),
+        }
+    }
+
+    pub fn get_attribute(&self, name: &str) -> Result<Self> {
+        match self {
+            Object::Module(module) => module
+                .get(name)
+                .ok_or_else(|| Error::InvalidAttribute(self.clone(), name.into())),
+            Object::File(file) => file
+                .attribute(name)
+                .ok_or_else(|| Error::InvalidAttribute(self.clone(), name.into())),
+            Object::DateTime(datetime) => datetime
+                .attribute(name)
+                .ok_or_else(|| Error::InvalidAttribute(self.clone(), name.into())),
+            Object::Pattern(pattern) => pattern
+                .attribute(name)
+                .ok_or_else(|| Error::InvalidAttribute(self.clone(), name.into())),
+            Object::Match(found) => found
+                .attribute(name)
+                .ok_or_else(|| Error::InvalidAttribute(self.clone(), name.into())),
+            _ => Err(Error::InvalidAttribute(self.clone(), name.into())),
+        }
+    }
+
+    pub fn set_attribute(&self, name: &str, value: Self) -> Result<()> {
+        match self {
+            Object::Module(module) => {
+                module.set(name, value);
+                Ok(())
+            }
+            _ => Err(Error::InvalidAttribute(self.clone(), name.into())),
+        }
+    }
+
+    /// What `com valor como alvo` binds to the target. Anything that can be
+    /// closed with `feche()`, such as a file, is its own context.
+    pub fn enter(&self) -> Result<Self> {
+        match self.get_attribute("feche") {
+            Ok(_) => Ok(self.clone()),
+            Err(_) => Err(Error::InvalidArgument(format!(
+                "{} não pode ser usado com 'com'",
+                self.type_name()
+            ))),
+        }
+    }
+
+    /// Closes a context at the end of a `com` statement.
+    pub fn exit(&self) -> Result<()> {
+        self.get_attribute("feche")?.call(vec![], vec![])?;
+        Ok(())
+    }
+
+    /// Negative indexes count from the end, as in `t[-1]`.
+    fn position(index: i64, len: usize) -> Result<usize> {
+        let position = if index < 0 { index + len as i64 } else { index };
+        if position < 0 || position >= len as i64 {
+            return Err(Error::IndexOutOfRange(index));
+        }
+        Ok(position as usize)
+    }
+
     fn error<T>(&self, right: Option<&Self>, op: OperatorError) -> Result<T> {
         Err(Error::InvalidType(op, self.clone(), right.cloned()))
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn display() {
+        let tuple = Object::from(vec![Object::from("a"), Object::from(1.0), Object::Unit]);
+        assert_eq!("(\"a\", 1.0, Vazio)", tuple.to_string());
+        assert_eq!("(1,)", Object::from(vec![1]).to_string());
+        assert_eq!("()", Object::Tuple(vec![]).to_string());
+        assert_eq!("Vazio", Object::Unit.to_string());
+        assert_eq!("a", Object::from("a").to_string());
+        assert_eq!("<módulo m>", Object::from(Module::new("m")).to_string());
+    }
+
     #[test]
     fn negate() {
         let truth = Object::Primitive(Primitive::Boolean(true));
@@ -683,7 +1069,7 @@ mod tests {
     fn unary_plus() {
         let a = Object::Primitive(Primitive::Integer(-10));
         assert_eq!(
-            Object::Primitive(Primitive::Integer(10)),
+            Object::Primitive(Primitive::Integer(-10)),
             a.unary_plus().unwrap()
         )
     }
@@ -697,4 +1083,140 @@ mod tests {
             a.module(&b).unwrap()
         )
     }
+
+    #[test]
+    fn div_real_by_zero_int() {
+        let a = Object::Primitive(Primitive::Integer(1));
+        let b = Object::Primitive(Primitive::Integer(0));
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::RealDiv,
+                Primitive::Integer(1),
+                Primitive::Integer(0)
+            )),
+            a.real_divide(&b)
+        )
+    }
+
+    #[test]
+    fn module_by_zero_int() {
+        let a = Object::Primitive(Primitive::Integer(1));
+        let b = Object::Primitive(Primitive::Integer(0));
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::Module,
+                Primitive::Integer(1),
+                Primitive::Integer(0)
+            )),
+            a.module(&b)
+        )
+    }
+
+    #[test]
+    fn div_real_by_zero_float() {
+        let a = Object::Primitive(Primitive::Float(1.0));
+        let b = Object::Primitive(Primitive::Float(0.0));
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::RealDiv,
+                Primitive::Float(1.0),
+                Primitive::Float(0.0)
+            )),
+            a.real_divide(&b)
+        )
+    }
+
+    #[test]
+    fn module_by_zero_float() {
+        let a = Object::Primitive(Primitive::Float(1.0));
+        let b = Object::Primitive(Primitive::Float(0.0));
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::Module,
+                Primitive::Float(1.0),
+                Primitive::Float(0.0)
+            )),
+            a.module(&b)
+        )
+    }
+
+    #[test]
+    fn div_real_by_zero_int_float() {
+        let a = Object::Primitive(Primitive::Integer(1));
+        let b = Object::Primitive(Primitive::Float(0.0));
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::RealDiv,
+                Primitive::Integer(1),
+                Primitive::Float(0.0)
+            )),
+            a.real_divide(&b)
+        )
+    }
+
+    #[test]
+    fn module_by_zero_int_float() {
+        let a = Object::Primitive(Primitive::Integer(1));
+        let b = Object::Primitive(Primitive::Float(0.0));
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::Module,
+                Primitive::Integer(1),
+                Primitive::Float(0.0)
+            )),
+            a.module(&b)
+        )
+    }
+
+    #[test]
+    fn div_real_by_zero_float_int() {
+        let a = Object::Primitive(Primitive::Float(1.0));
+        let b = Object::Primitive(Primitive::Integer(0));
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::RealDiv,
+                Primitive::Float(1.0),
+                Primitive::Integer(0)
+            )),
+            a.real_divide(&b)
+        )
+    }
+
+    #[test]
+    fn module_by_zero_float_int() {
+        let a = Object::Primitive(Primitive::Float(1.0));
+        let b = Object::Primitive(Primitive::Integer(0));
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::Module,
+                Primitive::Float(1.0),
+                Primitive::Integer(0)
+            )),
+            a.module(&b)
+        )
+    }
+
+    #[test]
+    fn div_int_by_zero() {
+        let a = Object::Primitive(Primitive::Integer(10));
+        let b = Object::Primitive(Primitive::Integer(0));
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::IntDiv,
+                Primitive::Integer(10),
+                Primitive::Integer(0)
+            )),
+            a.int_divide(&b)
+        )
+    }
+
+    #[test]
+    fn power() {
+        let a = Object::Primitive(Primitive::Integer(3));
+        let b = Object::Primitive(Primitive::Integer(3));
+        assert_eq!(
+            Object::Primitive(Primitive::Integer(27)),
+            a.power(&b).unwrap()
+        )
+    }
 }
@@ -0,0 +1,116 @@
+//! Optimisations of the syntax tree, run before evaluation when the engine
+//! is asked to.
+//!
+//! Operations on literals are computed once, with the same `Object` methods
+//! the backends use, and a `se` whose condition is a literal is replaced by
+//! the branch it takes. An operation that fails, such as `1 / 0`, is left as
+//! it is so the error still happens when the program runs.
+
+use crate::ast::{Expression, Number, Program};
+use crate::folder::{fold_expression, Folder};
+use crate::object::Object;
+use crate::primitive::Primitive;
+
+pub(crate) fn optimize(program: Program) -> Program {
+    ConstantFolder.fold_program(program)
+}
+
+struct ConstantFolder;
+
+impl Folder for ConstantFolder {
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        // the children first, so `60 * 60 * 24` folds from the inside out
+        match fold_expression(self, expression) {
+            Expression::IfExpression { test, body, orelse } => {
+                match constant(&test).and_then(|test| test.test().ok()) {
+                    Some(true) => *body,
+                    Some(false) => *orelse,
+                    None => Expression::IfExpression { test, body, orelse },
+                }
+            }
+            expression => evaluate(&expression).unwrap_or(expression),
+        }
+    }
+}
+
+/// The literal an operation on literals results in, when it succeeds.
+fn evaluate(expression: &Expression) -> Option<Expression> {
+    let value = match expression {
+        Expression::BinOp { a, op, b } => constant(a)?.binary(*op, &constant(b)?),
+        Expression::Compare { a, op, b } => constant(a)?.compare(*op, &constant(b)?),
+        Expression::BoolOp { a, op, b } => constant(a)?.boolean(*op, &constant(b)?),
+        Expression::UnOp { op, a } => constant(a)?.unary(*op),
+        _ => return None,
+    };
+    literal(value.ok()?)
+}
+
+/// The value of a literal.
+fn constant(expression: &Expression) -> Option<Object> {
+    match expression {
+        Expression::Num { value } => Some(value.clone().into()),
+        Expression::Str { value } => Some(value.as_str().into()),
+        Expression::True => Some(true.into()),
+        Expression::False => Some(false.into()),
+        Expression::None => Some(Object::Unit),
+        _ => None,
+    }
+}
+
+/// The literal of a value, if it has one.
+fn literal(object: Object) -> Option<Expression> {
+    let value = match object {
+        Object::Primitive(Primitive::Integer(value)) => Number::Integer { value },
+        Object::Primitive(Primitive::BigInteger(value)) => Number::BigInteger { value },
+        Object::Primitive(Primitive::Float(value)) => Number::Float { value },
+        Object::Primitive(Primitive::Str(value)) => return Some(Expression::Str { value }),
+        Object::Primitive(Primitive::Boolean(true)) => return Some(Expression::True),
+        Object::Primitive(Primitive::Boolean(false)) => return Some(Expression::False),
+        Object::Unit => return Some(Expression::None),
+        _ => return None,
+    };
+    Some(Expression::Num { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::optimize;
+    use crate::parse::parse_program;
+
+    fn assert_optimized(expected: &str, source: &str) {
+        assert_eq!(
+            parse_program(expected).unwrap(),
+            optimize(parse_program(source).unwrap()),
+            "{}",
+            source
+        );
+    }
+
+    #[test]
+    fn arithmetic_is_folded() {
+        assert_optimized("segundos = 86400", "segundos = 60 * 60 * 24");
+        assert_optimized("x = 7", "x = 1 + 2 * 3");
+        assert_optimized("x = 2.5", "x = 5 / 2");
+        assert_optimized("x = a * 6", "x = a * (2 + 4)");
+        assert_optimized("x = 1 + a + 3", "x = (2 - 1) + a + 3");
+        assert_optimized("f(Verdadeiro, k=\"a\")", "f(1 < 2 e nao Falso, k=\"a\")");
+    }
+
+    #[test]
+    fn errors_are_left_for_the_runtime() {
+        assert_optimized("x = 1 / 0", "x = 1 / (3 - 3)");
+        assert_optimized("x = 1 + \"a\"", "x = 1 + \"a\"");
+        assert_optimized("x = se 1: a senao: b", "x = se 1: a senao: b");
+    }
+
+    #[test]
+    fn dead_branches_are_removed() {
+        assert_optimized("x = a", "x = se Verdadeiro: a senao: b");
+        assert_optimized("x = b", "x = se 2 > 3: a senao: b");
+        assert_optimized("x = Vazio", "x = se Falso: a");
+        assert_optimized(
+            "x = se c: 3 senao: 1",
+            "x = se c: 1 + 2 senao: se 1 < 0: 0 senao: 1",
+        );
+    }
+}
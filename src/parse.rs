@@ -1,52 +1,97 @@
 use crate::ast;
 use crate::ast::{BooleanOperation, Comparison, Number, Operator, Statement, UnaryOperation};
 use crate::token::Token;
-use crate::tokenizer::scan;
+use crate::tokenizer::{scan_spanned, LexError, Position, Span};
 use crate::tokens::Tokens;
 
-#[derive(Debug)]
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
 pub enum ParseError {
+    Lex(Vec<LexError>),
+    /// Tokens that do not make a program, and where the first wrong one is.
+    Syntax {
+        message: String,
+        span: Span,
+    },
     Message(String),
 }
 
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Lex(errors) => {
+                let lines: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}", lines.join("\n"))
+            }
+            ParseError::Syntax { message, span } => write!(f, "{}: {}", span.start, message),
+            ParseError::Message(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// A program with what the parser usually throws away, so it can be printed back.
+pub(crate) struct ParsedSource {
+    pub(crate) program: ast::Program,
+    /// Where each top-level statement is, in the same order.
+    pub(crate) spans: Vec<Span>,
+    /// The text of every comment and where it is.
+    pub(crate) comments: Vec<(String, Span)>,
+    /// Every number and text as it was written, in order.
+    pub(crate) literals: Vec<String>,
+}
+
 /// It takes the list of tokens as input and create an AST as output.
-pub struct Parser {
+pub struct Parser<'a> {
+    source: &'a str,
     tokens: Tokens,
+    // where each token is, so errors can point at it
+    spans: Vec<Span>,
+    // first and one past the last token of each top-level statement
+    ranges: Vec<(usize, usize)>,
 }
 
-impl Parser {
-    pub fn init(tokens: Vec<Token>) -> Self {
+impl<'a> Parser<'a> {
+    pub fn init(source: &'a str, tokens: Vec<Token>, spans: Vec<Span>) -> Self {
         Parser {
+            source,
             tokens: Tokens::init(tokens),
+            spans,
+            ranges: Vec::new(),
         }
     }
 
-    fn parser(&mut self) -> Option<ast::Program> {
+    fn parser(&mut self) -> Result<ast::Program, ParseError> {
         let mut statements: Vec<Statement> = Vec::new();
-        let mut errors: Vec<ParseError> = Vec::new();
+        let mut start = self.tokens.pos();
         while let Some(result) = self.statements() {
-            match result {
-                Ok(statement) => {
-                    statements.push(statement);
-                }
-                Err(error) => {
-                    errors.push(error);
-                    break;
-                }
-            }
+            statements.push(result?);
+            self.ranges.push((start, self.tokens.pos()));
+            start = self.tokens.pos();
         }
-        Some(ast::Program {
-            statements: statements,
-        })
+        Ok(ast::Program { statements })
     }
 
+    /// The next statement, or `None` at the end of the tokens.
     fn statements(&mut self) -> Option<Result<Statement, ParseError>> {
         match self.current() {
-            Some(Token::Interrompa) => Some(Ok(ast::Statement::Break)),
-            Some(Token::Passe) => Some(Ok(ast::Statement::Pass)),
+            None => None,
+            Some(Token::Interrompa) => {
+                self.advance();
+                Some(Ok(ast::Statement::Break))
+            }
+            Some(Token::Continue) => {
+                self.advance();
+                Some(Ok(ast::Statement::Continue))
+            }
+            Some(Token::Passe) => {
+                self.advance();
+                Some(Ok(ast::Statement::Pass))
+            }
             Some(Token::Retorne) => {
-                if let Some(expr) = self.expression() {
-                    Some(Ok(ast::Statement::Return {
+                self.advance();
+                if self.starts_expression() {
+                    Some(self.expression().map(|expr| ast::Statement::Return {
                         value: Some(vec![expr]),
                     }))
                 } else {
@@ -56,62 +101,297 @@ impl Parser {
                 }
             }
             Some(Token::Verifique) => {
-                if let Some(expr) = self.expression() {
-                    Some(Ok(ast::Statement::Assert {
-                        test: expr,
-                        msg: None,
-                    }))
-                } else {
-                    None
-                }
+                self.advance();
+                Some(
+                    self.required("'verifique'")
+                        .map(|expr| ast::Statement::Assert {
+                            test: expr,
+                            msg: None,
+                        }),
+                )
             }
-            Some(Token::Identifier(_)) => match self.peek() {
-                Some(Token::Equal) => {
-                    if let Some(assign) = self.parse_assignment() {
-                        Some(Ok(assign))
-                    } else {
-                        Some(Err(ParseError::Message("could not get assignment".into())))
-                    }
-                }
-                _ => None,
-            },
-            Some(Token::Imprima) => {
-                if let Some(expression) = self.parse_print() {
-                    Some(Ok(ast::Statement::Expr { expression }))
-                } else {
-                    Some(Err(ParseError::Message("could not get statements".into())))
-                }
+            Some(Token::Importe) => {
+                self.advance();
+                Some(self.aliases().map(|names| ast::Statement::Import { names }))
             }
-            _ => {
-                if let Some(expression) = self.expression() {
-                    Some(Ok(ast::Statement::Expr { expression }))
-                } else {
-                    None
-                }
+            Some(Token::De) => {
+                self.advance();
+                Some(self.import_from())
             }
+            Some(Token::Com) => {
+                self.advance();
+                Some(self.with_statement())
+            }
+            Some(_) => Some(self.expression_statement()),
         }
     }
 
-    fn parse_assignment(&mut self) -> Option<ast::Statement> {
-        let identifier = match self.identifier() {
-            Ok(identifier) => identifier,
-            Err(o) => {
-                return None;
+    /// `de modulo importe a, b como c`
+    fn import_from(&mut self) -> Result<Statement, ParseError> {
+        let module = self.name("'de'")?;
+        if !self.consume(&Token::Importe) {
+            return Err(self.error(format!("'importe' esperado depois de 'de {}'", module)));
+        }
+        let names = self.aliases()?;
+        Ok(ast::Statement::ImportFrom { module, names })
+    }
+
+    /// `com abra(caminho) como f, outro: corpo`; with no blocks in the
+    /// language, the body is a single statement.
+    fn with_statement(&mut self) -> Result<Statement, ParseError> {
+        let mut items = Vec::new();
+        loop {
+            let context_expr = self.required("'com'")?;
+            let optional_vars = if self.consume(&Token::Como) {
+                let start = self.tokens.pos();
+                if !self.starts_expression() {
+                    return Err(self.error("alvo esperado depois de 'como'"));
+                }
+                let target = self.expression()?;
+                self.check_target(&target, start)?;
+                Some(target)
+            } else {
+                None
+            };
+            items.push(ast::WithItem {
+                context_expr,
+                optional_vars,
+            });
+            if !self.consume(&Token::Comma) {
+                break;
             }
+        }
+        if !self.consume(&Token::Colon) {
+            return Err(self.error("':' esperado depois de 'com'"));
+        }
+        let body = match self.statements() {
+            Some(body) => body?,
+            None => return Err(self.error("instrução esperada depois de ':'")),
         };
+        Ok(ast::Statement::With {
+            items,
+            body: Box::new(body),
+        })
+    }
+
+    /// The names after `importe`, separated by commas, each one optionally
+    /// followed by `como apelido`.
+    fn aliases(&mut self) -> Result<Vec<ast::Alias>, ParseError> {
+        let mut aliases = Vec::new();
+        loop {
+            let name = self.name("'importe'")?;
+            let asname = if self.consume(&Token::Como) {
+                Some(self.name("'como'")?)
+            } else {
+                None
+            };
+            aliases.push(ast::Alias { name, asname });
+            if !self.consume(&Token::Comma) {
+                return Ok(aliases);
+            }
+        }
+    }
+
+    fn name(&mut self, after: &str) -> Result<String, ParseError> {
+        match self.current() {
+            Some(Token::Identifier(name)) => {
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.error(format!("nome esperado depois de {}", after))),
+        }
+    }
+
+    fn expression_statement(&mut self) -> Result<Statement, ParseError> {
+        let start = self.tokens.pos();
+        let expression = self.expression_list()?;
+        match self.current() {
+            Some(Token::Equal) => self.parse_assignment(expression, start),
+            Some(token) if Self::is_augmented_assignment(&token) => {
+                self.parse_augmented_assignment(expression, start, token)
+            }
+            _ => Ok(ast::Statement::Expr { expression }),
+        }
+    }
 
-        if !self.consume(&Token::Equal) {
-            return None;
+    /// `a = b = 0` assigns the same value to every target, from left to right.
+    fn parse_assignment(
+        &mut self,
+        first: ast::Expression,
+        start: usize,
+    ) -> Result<Statement, ParseError> {
+        let mut targets = Vec::new();
+        let mut value = first;
+        let mut start = start;
+        while self.current() == Some(Token::Equal) {
+            self.check_target(&value, start)?;
+            self.advance();
+            targets.push(value);
+            start = self.tokens.pos();
+            if !self.starts_expression() {
+                return Err(self.error("expressão esperada depois de '='"));
+            }
+            value = self.expression_list()?;
         }
 
-        Some(ast::Statement::Assign {
-            targets: vec![identifier],
-            value: self.expression().unwrap(),
+        Ok(ast::Statement::Assign { targets, value })
+    }
+
+    fn parse_augmented_assignment(
+        &mut self,
+        target: ast::Expression,
+        start: usize,
+        token: Token,
+    ) -> Result<Statement, ParseError> {
+        if let ast::Expression::Tuple { .. } = target {
+            return Err(self.error_at(
+                self.span_from(start),
+                "atribuição aumentada não aceita múltiplos alvos",
+            ));
+        }
+        self.check_target(&target, start)?;
+        let operator = self.text(self.span());
+        self.advance();
+        if !self.starts_expression() {
+            return Err(self.error(format!("expressão esperada depois de '{}'", operator)));
+        }
+        let value = self.expression_list()?;
+        Ok(ast::Statement::AugAssign {
+            target,
+            op: Operator::from(token),
+            value,
         })
     }
 
-    fn expression(&mut self) -> Option<ast::Expression> {
-        let mut a = self.factor();
+    fn is_augmented_assignment(token: &Token) -> bool {
+        matches!(
+            token,
+            Token::PlusEqual
+                | Token::MinusEqual
+                | Token::StarEqual
+                | Token::SlashEqual
+                | Token::PercentEqual
+                | Token::SlashSlashEqual
+                | Token::StarStarEqual
+        )
+    }
+
+    /// Checks the target that starts at the token `start` and ends right
+    /// before the current one.
+    fn check_target(&self, target: &ast::Expression, start: usize) -> Result<(), ParseError> {
+        let reason = match Self::invalid_target(target) {
+            Some(reason) => reason,
+            None => return Ok(()),
+        };
+        let span = self.span_from(start);
+        Err(self.error_at(
+            span,
+            format!("não é possível atribuir a '{}'{}", self.text(span), reason),
+        ))
+    }
+
+    /// What is wrong with the target, if anything, to add to the error.
+    fn invalid_target(target: &ast::Expression) -> Option<&'static str> {
+        match target {
+            ast::Expression::Identifier { .. } | ast::Expression::Attribute { .. } => None,
+            // tuples and texts are the only values with indexes, and neither
+            // can be changed in place
+            ast::Expression::Subscript { .. } => {
+                Some(": nenhum valor pode ser alterado pelo índice")
+            }
+            ast::Expression::Tuple { elements } => elements.iter().find_map(Self::invalid_target),
+            _ => Some(""),
+        }
+    }
+
+    /// One or more comma separated expressions; more than one makes a tuple.
+    fn expression_list(&mut self) -> Result<ast::Expression, ParseError> {
+        let first = self.expression()?;
+        if self.current() != Some(Token::Comma) {
+            return Ok(first);
+        }
+
+        let mut elements = vec![first];
+        while self.consume(&Token::Comma) {
+            elements.push(self.expression()?);
+        }
+        Ok(ast::Expression::Tuple { elements })
+    }
+
+    /// An expression that must come after `after`.
+    fn required(&mut self, after: &str) -> Result<ast::Expression, ParseError> {
+        if !self.starts_expression() {
+            return Err(self.error(format!("expressão esperada depois de {}", after)));
+        }
+        self.expression()
+    }
+
+    /// Whether an expression can start at the current token.
+    fn starts_expression(&self) -> bool {
+        matches!(
+            self.current(),
+            Some(Token::Identifier(_))
+                | Some(Token::Imprima)
+                | Some(Token::Entrada)
+                | Some(Token::Vazio)
+                | Some(Token::Inteiro(_))
+                | Some(Token::InteiroGrande(_))
+                | Some(Token::Real(_))
+                | Some(Token::Texto(_))
+                | Some(Token::Logico(_))
+                | Some(Token::ParentOpen)
+                | Some(Token::Se)
+                | Some(Token::Nao)
+                | Some(Token::Plus)
+                | Some(Token::Minus)
+        )
+    }
+
+    fn expression(&mut self) -> Result<ast::Expression, ParseError> {
+        if self.consume(&Token::Se) {
+            return self.if_expression();
+        }
+        self.disjunction()
+    }
+
+    fn disjunction(&mut self) -> Result<ast::Expression, ParseError> {
+        let mut a = self.conjunction()?;
+        while let Some(Token::Ou) = self.current() {
+            self.advance();
+            a = ast::Expression::BoolOp {
+                a: Box::new(a),
+                op: BooleanOperation::Or,
+                b: Box::new(self.conjunction()?),
+            };
+        }
+        Ok(a)
+    }
+
+    fn conjunction(&mut self) -> Result<ast::Expression, ParseError> {
+        let mut a = self.inversion()?;
+        while let Some(Token::E) = self.current() {
+            self.advance();
+            a = ast::Expression::BoolOp {
+                a: Box::new(a),
+                op: BooleanOperation::And,
+                b: Box::new(self.inversion()?),
+            };
+        }
+        Ok(a)
+    }
+
+    fn inversion(&mut self) -> Result<ast::Expression, ParseError> {
+        if self.consume(&Token::Nao) {
+            return Ok(ast::Expression::UnOp {
+                op: UnaryOperation::Not,
+                a: Box::new(self.inversion()?),
+            });
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<ast::Expression, ParseError> {
+        let mut a = self.sum()?;
         while let Some(token) = self.current() {
             match token {
                 Token::Less
@@ -122,120 +402,220 @@ impl Parser {
                 | Token::EqualEqual
                 | Token::Is => {
                     self.advance();
-                    a = Some(ast::Expression::Compare {
-                        a: Box::new(a.unwrap()),
+                    a = ast::Expression::Compare {
+                        a: Box::new(a),
                         op: Comparison::from(token),
-                        b: Box::new(self.factor().unwrap()),
-                    });
+                        b: Box::new(self.sum()?),
+                    };
                 }
-                Token::Plus | Token::Minus | Token::Slash | Token::Star | Token::Percent => {
+                _ => break,
+            }
+        }
+        Ok(a)
+    }
+
+    fn sum(&mut self) -> Result<ast::Expression, ParseError> {
+        let mut a = self.term()?;
+        while let Some(token) = self.current() {
+            match token {
+                Token::Plus | Token::Minus => {
                     self.advance();
-                    if let Some(possible_factor) = a {
-                        a = Some(ast::Expression::BinOp {
-                            a: Box::new(possible_factor),
-                            op: Operator::from(token),
-                            b: Box::new(self.factor().unwrap()),
-                        });
-                    } else {
-                        a = Some(ast::Expression::UnOp {
-                            op: UnaryOperation::from(token),
-                            a: Box::new(self.factor().unwrap()),
-                        })
-                    }
+                    a = ast::Expression::BinOp {
+                        a: Box::new(a),
+                        op: Operator::from(token),
+                        b: Box::new(self.term()?),
+                    };
                 }
-                Token::E | Token::Ou => {
+                _ => break,
+            }
+        }
+        Ok(a)
+    }
+
+    fn term(&mut self) -> Result<ast::Expression, ParseError> {
+        let mut a = self.unary()?;
+        while let Some(token) = self.current() {
+            match token {
+                Token::Star | Token::Slash | Token::SlashSlash | Token::Percent => {
                     self.advance();
-                    a = Some(ast::Expression::BoolOp {
-                        a: Box::new(a.unwrap()),
-                        op: BooleanOperation::from(token),
-                        b: Box::new(self.factor().unwrap()),
-                    })
+                    a = ast::Expression::BinOp {
+                        a: Box::new(a),
+                        op: Operator::from(token),
+                        b: Box::new(self.unary()?),
+                    };
                 }
-                Token::Se => {
+                _ => break,
+            }
+        }
+        Ok(a)
+    }
+
+    fn unary(&mut self) -> Result<ast::Expression, ParseError> {
+        match self.current() {
+            Some(token @ Token::Plus) | Some(token @ Token::Minus) => {
+                self.advance();
+                Ok(ast::Expression::UnOp {
+                    op: UnaryOperation::from(token),
+                    a: Box::new(self.unary()?),
+                })
+            }
+            _ => self.power(),
+        }
+    }
+
+    /// `**` binds tighter than a unary minus on its left (`-2 ** 2` is `-(2 ** 2)`)
+    /// and is right associative (`2 ** 3 ** 2` is `2 ** (3 ** 2)`).
+    fn power(&mut self) -> Result<ast::Expression, ParseError> {
+        let a = self.primary()?;
+        if self.consume(&Token::StarStar) {
+            return Ok(ast::Expression::BinOp {
+                a: Box::new(a),
+                op: Operator::Pow,
+                b: Box::new(self.unary()?),
+            });
+        }
+        Ok(a)
+    }
+
+    /// An atom followed by any number of calls, subscripts and attribute accesses.
+    fn primary(&mut self) -> Result<ast::Expression, ParseError> {
+        let mut a = self.factor()?;
+        loop {
+            if self.consume(&Token::ParentOpen) {
+                a = self.call(a)?;
+            } else if self.consume(&Token::BraceOpen) {
+                let index = self.expression_list()?;
+                self.expect(&Token::BraceClose, "']'")?;
+                a = ast::Expression::Subscript {
+                    value: Box::new(a),
+                    index: Box::new(index),
+                };
+            } else if self.consume(&Token::Dot) {
+                // nothing after a dot can be `e` the operator, so it is the
+                // name, as in `m.e`
+                let name = if self.consume(&Token::E) {
+                    "e".to_string()
+                } else {
+                    match self.identifier() {
+                        Ok(ast::Expression::Identifier { name }) => name,
+                        _ => return Err(self.error("nome esperado depois de '.'")),
+                    }
+                };
+                a = ast::Expression::Attribute {
+                    value: Box::new(a),
+                    name,
+                };
+            } else {
+                return Ok(a);
+            }
+        }
+    }
+
+    fn call(&mut self, function: ast::Expression) -> Result<ast::Expression, ParseError> {
+        let mut args = Vec::new();
+        let mut keywords = Vec::new();
+        while !self.consume(&Token::ParentClose) {
+            match (self.current(), self.peek()) {
+                (Some(Token::Identifier(name)), Some(Token::Equal)) => {
                     self.advance();
-                    a = self.if_expression()
-                }
-                Token::Nao => {
                     self.advance();
-                    a = Some(ast::Expression::UnOp {
-                        op: UnaryOperation::from(token),
-                        a: Box::new(self.factor().unwrap()),
-                    })
+                    let value = self.expression()?;
+                    keywords.push(ast::Keyword { name, value });
                 }
-                _ => {
-                    break;
+                (Some(_), _) if !keywords.is_empty() => {
+                    return Err(self.error("argumento posicional depois de um argumento nomeado"))
                 }
+                _ => args.push(self.expression()?),
+            }
+
+            if !self.consume(&Token::Comma) {
+                self.expect(&Token::ParentClose, "')'")?;
+                break;
             }
         }
-        return a;
+
+        Ok(ast::Expression::Call {
+            function: Box::new(function),
+            args,
+            keywords,
+        })
     }
 
-    fn factor(&mut self) -> Option<ast::Expression> {
+    fn factor(&mut self) -> Result<ast::Expression, ParseError> {
         match self.current() {
+            Some(Token::Identifier(_)) | Some(Token::Imprima) | Some(Token::Entrada) => {
+                self.identifier()
+            }
+            Some(Token::Vazio) => {
+                let _ = self.advance();
+                Ok(ast::Expression::None)
+            }
             Some(Token::Inteiro(value)) => {
                 let _ = self.advance();
-                Some(ast::Expression::Num {
+                Ok(ast::Expression::Num {
                     value: Number::Integer { value },
                 })
             }
+            Some(Token::InteiroGrande(value)) => {
+                let _ = self.advance();
+                Ok(ast::Expression::Num {
+                    value: Number::BigInteger { value },
+                })
+            }
             Some(Token::Real(value)) => {
                 let _ = self.advance();
-                Some(ast::Expression::Num {
+                Ok(ast::Expression::Num {
                     value: Number::Float { value },
                 })
             }
             Some(Token::Texto(value)) => {
                 let _ = self.advance();
-                Some(ast::Expression::Str { value })
+                Ok(ast::Expression::Str { value })
             }
             Some(Token::Logico(value)) => {
                 let _ = self.advance();
-                Some(if value {
+                Ok(if value {
                     ast::Expression::True
                 } else {
                     ast::Expression::False
                 })
             }
-            _ => None,
+            Some(Token::ParentOpen) => {
+                let _ = self.advance();
+                if self.consume(&Token::ParentClose) {
+                    return Ok(ast::Expression::Tuple { elements: vec![] });
+                }
+                let first = self.expression()?;
+                if !self.consume(&Token::Comma) {
+                    self.expect(&Token::ParentClose, "')'")?;
+                    return Ok(first);
+                }
+                // a comma makes a tuple, and it may end one, as in `(1,)`
+                let mut elements = vec![first];
+                while !self.consume(&Token::ParentClose) {
+                    elements.push(self.expression()?);
+                    if !self.consume(&Token::Comma) {
+                        self.expect(&Token::ParentClose, "')'")?;
+                        break;
+                    }
+                }
+                Ok(ast::Expression::Tuple { elements })
+            }
+            _ => Err(self.unexpected()),
         }
     }
 
-    fn parse_print(&mut self) -> Option<ast::Expression> {
-        let print_identifier = match self.identifier() {
-            Ok(print_identifier) => print_identifier,
-            Err(_) => return None,
-        };
-
-        if !self.consume(&Token::ParentOpen) {
-            return None;
-        }
-
-        let args = vec![self.expression().unwrap()];
-
-        if !self.consume(&Token::ParentClose) {
-            return None;
-        }
-
-        Some(ast::Expression::Call {
-            function: Box::new(print_identifier),
-            args,
-            keywords: vec![],
-        })
-    }
-
-    fn if_expression(&mut self) -> Option<ast::Expression> {
-        self.expected(&Token::Se);
-        let test = self.expression().unwrap();
-        self.expected(&Token::Colon);
-        self.advance();
-        let body = self.expression().unwrap();
+    fn if_expression(&mut self) -> Result<ast::Expression, ParseError> {
+        let test = self.required("'se'")?;
+        self.expect(&Token::Colon, "':'")?;
+        let body = self.required("':'")?;
         let mut orelse = ast::Expression::None;
         if self.consume(&Token::SeNao) {
-            self.advance();
-            orelse = self.expression().unwrap();
+            self.expect(&Token::Colon, "':'")?;
+            orelse = self.required("'senao:'")?;
         }
 
-        Some(ast::Expression::IfExpression {
+        Ok(ast::Expression::IfExpression {
             test: Box::new(test),
             body: Box::new(body),
             orelse: Box::new(orelse),
@@ -250,13 +630,17 @@ impl Parser {
                     name: String::from("imprima"),
                 })
             }
-            Some(Token::Identifier(name)) => {
+            Some(Token::Entrada) => {
                 let _ = self.advance();
                 Ok(ast::Expression::Identifier {
-                    name: String::from(name),
+                    name: String::from("entrada"),
                 })
             }
-            _ => Err(ParseError::Message("identifier not found".into())),
+            Some(Token::Identifier(name)) => {
+                let _ = self.advance();
+                Ok(ast::Expression::Identifier { name })
+            }
+            _ => Err(self.unexpected()),
         }
     }
 
@@ -270,6 +654,69 @@ impl Parser {
         }
     }
 
+    /// Consumes the token, which has to be there; `expected` is how the
+    /// error names it.
+    fn expect(&mut self, tok: &Token, expected: &str) -> Result<(), ParseError> {
+        if self.consume(tok) {
+            return Ok(());
+        }
+        Err(match self.current() {
+            Some(_) => self.error(format!(
+                "{} esperado, encontrado '{}'",
+                expected,
+                self.text(self.span())
+            )),
+            None => self.error(format!("{} esperado, mas a entrada terminou", expected)),
+        })
+    }
+
+    /// The error for a token that nothing can start with.
+    fn unexpected(&self) -> ParseError {
+        match self.current() {
+            Some(_) => self.error(format!("token inesperado: '{}'", self.text(self.span()))),
+            None => self.error("fim inesperado da entrada"),
+        }
+    }
+
+    fn error<S: Into<String>>(&self, message: S) -> ParseError {
+        self.error_at(self.span(), message)
+    }
+
+    fn error_at<S: Into<String>>(&self, span: Span, message: S) -> ParseError {
+        ParseError::Syntax {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Where the current token is; past the last one, the empty span at
+    /// the end of the code.
+    fn span(&self) -> Span {
+        match self.spans.get(self.tokens.pos()) {
+            Some(span) => *span,
+            None => {
+                let end = self
+                    .spans
+                    .last()
+                    .map_or(Position { line: 1, column: 1 }, |span| span.end);
+                Span { start: end, end }
+            }
+        }
+    }
+
+    /// From the token `start` to the one before the current.
+    fn span_from(&self, start: usize) -> Span {
+        Span {
+            start: self.spans[start].start,
+            end: self.spans[self.tokens.pos() - 1].end,
+        }
+    }
+
+    /// The code in the span, as it was written.
+    fn text(&self, span: Span) -> String {
+        source_text(self.source, span)
+    }
+
     fn current(&self) -> Option<Token> {
         self.tokens.current()
     }
@@ -282,20 +729,73 @@ impl Parser {
     fn peek(&mut self) -> Option<Token> {
         self.tokens.peek()
     }
+}
 
-    fn expected(&self, tok: &Token) -> Option<Token> {
-        let current = self.current().unwrap();
-        if &current == tok {
-            return Some(current);
+/// The code in the span, as it was written.
+fn source_text(source: &str, span: Span) -> String {
+    let lines: Vec<String> = source
+        .split('\n')
+        .enumerate()
+        .skip(span.start.line - 1)
+        .take(span.end.line + 1 - span.start.line)
+        .map(|(index, line)| {
+            let first = if index + 1 == span.start.line {
+                span.start.column - 1
+            } else {
+                0
+            };
+            let last = if index + 1 == span.end.line {
+                span.end.column - 1
+            } else {
+                usize::MAX
+            };
+            line.chars().skip(first).take(last - first).collect()
+        })
+        .collect();
+    lines.join("\n")
+}
+
+pub fn parse_program(source: &str) -> Result<ast::Program, ParseError> {
+    parse_source(source).map(|parsed| parsed.program)
+}
+
+pub(crate) fn parse_source(source: &str) -> Result<ParsedSource, ParseError> {
+    let mut tokens = Vec::new();
+    let mut token_spans = Vec::new();
+    let mut comments = Vec::new();
+    let mut literals = Vec::new();
+    for (token, span) in scan_spanned(source).map_err(ParseError::Lex)? {
+        if let Token::Inteiro(_) | Token::InteiroGrande(_) | Token::Real(_) | Token::Texto(_) =
+            token
+        {
+            literals.push(source_text(source, span));
+        }
+        match token {
+            Token::Comment(text) => comments.push((text, span)),
+            token => {
+                tokens.push(token);
+                token_spans.push(span);
+            }
         }
-        None
     }
-}
 
-pub fn parse_program(source: &str) -> Option<ast::Program> {
-    let tokens = scan(source);
-    let mut parser = Parser::init(tokens);
-    parser.parser()
+    let mut parser = Parser::init(source, tokens, token_spans);
+    let program = parser.parser()?;
+    let spans = parser
+        .ranges
+        .iter()
+        .map(|&(start, end)| Span {
+            start: parser.spans[start].start,
+            end: parser.spans[end - 1].end,
+        })
+        .collect();
+
+    Ok(ParsedSource {
+        program,
+        spans,
+        comments,
+        literals,
+    })
 }
 
 #[cfg(test)]
@@ -303,13 +803,19 @@ mod tests {
     use crate::ast::{BooleanOperation, Comparison, Number, Operator, UnaryOperation};
 
     use super::ast;
-    use super::parse_program;
+    use super::{parse_program, ParseError};
+    use crate::tokenizer::{LexError, LexErrorKind, Position, Span};
+
+    /// The error for the source, with where it is.
+    fn error(source: &str) -> String {
+        parse_program(source).unwrap_err().to_string()
+    }
 
     #[test]
     fn test_parse_empty() {
         let parse_ast = parse_program("\n");
 
-        assert_eq!(parse_ast, Some(ast::Program { statements: vec![] }))
+        assert_eq!(parse_ast, Ok(ast::Program { statements: vec![] }))
     }
 
     #[test]
@@ -318,7 +824,7 @@ mod tests {
 
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::Call {
                         function: Box::new(ast::Expression::Identifier {
@@ -345,7 +851,7 @@ mod tests {
 
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![
                     ast::Statement::Expr {
                         expression: ast::Expression::Call {
@@ -380,7 +886,7 @@ mod tests {
 
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::Call {
                         function: Box::new(ast::Expression::Identifier {
@@ -413,7 +919,7 @@ mod tests {
         let parse_ast = parse_program(r#"numero = 10"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Assign {
                     targets: vec![ast::Expression::Identifier {
                         name: "numero".into()
@@ -431,7 +937,7 @@ mod tests {
         let parse_ast = parse_program(r#"real = 10.0"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Assign {
                     targets: vec![ast::Expression::Identifier {
                         name: "real".into()
@@ -449,7 +955,7 @@ mod tests {
         let parse_ast = parse_program(r#"nome = "coral""#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Assign {
                     targets: vec![ast::Expression::Identifier {
                         name: "nome".into()
@@ -467,7 +973,7 @@ mod tests {
         let parse_ast = parse_program(r#"1 < 5"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::Compare {
                         a: Box::new(ast::Expression::Num {
@@ -488,7 +994,7 @@ mod tests {
         let parse_ast = parse_program(r#"1 é 5"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::Compare {
                         a: Box::new(ast::Expression::Num {
@@ -509,7 +1015,7 @@ mod tests {
         let parse_ast = parse_program(r#"1 <= 5"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::Compare {
                         a: Box::new(ast::Expression::Num {
@@ -530,7 +1036,7 @@ mod tests {
         let parse_ast = parse_program(r#"1 == 1"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::Compare {
                         a: Box::new(ast::Expression::Num {
@@ -551,7 +1057,7 @@ mod tests {
         let parse_ast = parse_program(r#"Verdadeiro ou Falso"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::BoolOp {
                         a: Box::new(ast::Expression::True),
@@ -568,7 +1074,7 @@ mod tests {
         let parse_ast = parse_program(r#"1 + 5"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::BinOp {
                         a: Box::new(ast::Expression::Num {
@@ -589,7 +1095,7 @@ mod tests {
         let parse_ast = parse_program(r#"1 % 5"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::BinOp {
                         a: Box::new(ast::Expression::Num {
@@ -610,7 +1116,7 @@ mod tests {
         let parse_ast = parse_program(r#"nao Falso"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::UnOp {
                         op: UnaryOperation::Not,
@@ -626,7 +1132,7 @@ mod tests {
         let parse_ast = parse_program(r#"-1"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::UnOp {
                         op: UnaryOperation::Minus,
@@ -644,7 +1150,7 @@ mod tests {
         let parse_ast = parse_program(r#"+1"#);
         assert_eq!(
             parse_ast,
-            Some(ast::Program {
+            Ok(ast::Program {
                 statements: vec![ast::Statement::Expr {
                     expression: ast::Expression::UnOp {
                         op: UnaryOperation::Plus,
@@ -656,4 +1162,428 @@ mod tests {
             })
         )
     }
+
+    #[test]
+    fn test_floor_div_operation() {
+        let parse_ast = parse_program(r#"7 // 2"#);
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![ast::Statement::Expr {
+                    expression: ast::Expression::BinOp {
+                        a: Box::new(ast::Expression::Num {
+                            value: Number::Integer { value: 7 }
+                        }),
+                        op: Operator::FloorDiv,
+                        b: Box::new(ast::Expression::Num {
+                            value: Number::Integer { value: 2 }
+                        }),
+                    }
+                }]
+            })
+        )
+    }
+
+    #[test]
+    fn test_mul_has_precedence_over_sum() {
+        let parse_ast = parse_program(r#"1 + 2 * 3"#);
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![ast::Statement::Expr {
+                    expression: ast::Expression::BinOp {
+                        a: Box::new(ast::Expression::Num {
+                            value: Number::Integer { value: 1 }
+                        }),
+                        op: Operator::Add,
+                        b: Box::new(ast::Expression::BinOp {
+                            a: Box::new(ast::Expression::Num {
+                                value: Number::Integer { value: 2 }
+                            }),
+                            op: Operator::Mul,
+                            b: Box::new(ast::Expression::Num {
+                                value: Number::Integer { value: 3 }
+                            }),
+                        }),
+                    }
+                }]
+            })
+        )
+    }
+
+    #[test]
+    fn test_parenthesis_change_precedence() {
+        let parse_ast = parse_program(r#"(1 + 2) * 3"#);
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![ast::Statement::Expr {
+                    expression: ast::Expression::BinOp {
+                        a: Box::new(ast::Expression::BinOp {
+                            a: Box::new(ast::Expression::Num {
+                                value: Number::Integer { value: 1 }
+                            }),
+                            op: Operator::Add,
+                            b: Box::new(ast::Expression::Num {
+                                value: Number::Integer { value: 2 }
+                            }),
+                        }),
+                        op: Operator::Mul,
+                        b: Box::new(ast::Expression::Num {
+                            value: Number::Integer { value: 3 }
+                        }),
+                    }
+                }]
+            })
+        )
+    }
+
+    #[test]
+    fn test_power_is_right_associative() {
+        let parse_ast = parse_program(r#"2 ** 3 ** 2"#);
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![ast::Statement::Expr {
+                    expression: ast::Expression::BinOp {
+                        a: Box::new(ast::Expression::Num {
+                            value: Number::Integer { value: 2 }
+                        }),
+                        op: Operator::Pow,
+                        b: Box::new(ast::Expression::BinOp {
+                            a: Box::new(ast::Expression::Num {
+                                value: Number::Integer { value: 3 }
+                            }),
+                            op: Operator::Pow,
+                            b: Box::new(ast::Expression::Num {
+                                value: Number::Integer { value: 2 }
+                            }),
+                        }),
+                    }
+                }]
+            })
+        )
+    }
+
+    #[test]
+    fn test_power_binds_tighter_than_unary_minus() {
+        let parse_ast = parse_program(r#"-2 ** -1"#);
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![ast::Statement::Expr {
+                    expression: ast::Expression::UnOp {
+                        op: UnaryOperation::Minus,
+                        a: Box::new(ast::Expression::BinOp {
+                            a: Box::new(ast::Expression::Num {
+                                value: Number::Integer { value: 2 }
+                            }),
+                            op: Operator::Pow,
+                            b: Box::new(ast::Expression::UnOp {
+                                op: UnaryOperation::Minus,
+                                a: Box::new(ast::Expression::Num {
+                                    value: Number::Integer { value: 1 }
+                                }),
+                            }),
+                        }),
+                    }
+                }]
+            })
+        )
+    }
+
+    #[test]
+    fn test_lex_errors_are_reported() {
+        let parse_ast = parse_program("1 @ 2\n\"oi");
+        assert_eq!(
+            parse_ast,
+            Err(ParseError::Lex(vec![
+                LexError {
+                    kind: LexErrorKind::UnexpectedCharacter('@'),
+                    position: Position { line: 1, column: 3 },
+                },
+                LexError {
+                    kind: LexErrorKind::UnterminatedString,
+                    position: Position { line: 2, column: 1 },
+                },
+            ]))
+        )
+    }
+
+    #[test]
+    fn test_unexpected_token_is_reported() {
+        let parse_ast = parse_program("1 + 2 )");
+        assert_eq!(
+            parse_ast,
+            Err(ParseError::Syntax {
+                message: "token inesperado: ')'".into(),
+                span: Span {
+                    start: Position { line: 1, column: 7 },
+                    end: Position { line: 1, column: 8 },
+                },
+            })
+        )
+    }
+
+    #[test]
+    fn test_truncated_input_is_reported() {
+        assert_eq!(
+            "1:10: ')' esperado, mas a entrada terminou",
+            error("imprima(1")
+        );
+        assert_eq!("1:12: fim inesperado da entrada", error("imprima(2 +"));
+        assert_eq!(
+            "2:3: ')' esperado, mas a entrada terminou",
+            error("imprima(1)\n(1")
+        );
+        assert_eq!("1:15: fim inesperado da entrada", error("imprima(1) 2 +"));
+        assert_eq!("1:4: ']' esperado, mas a entrada terminou", error("x[1"));
+        assert_eq!("1:3: nome esperado depois de '.'", error("a."));
+        assert_eq!("1:5: ')' esperado, encontrado ']'", error("f(1 ]"));
+        assert_eq!(
+            "1:8: argumento posicional depois de um argumento nomeado",
+            error("f(a=1, 2)")
+        );
+    }
+
+    #[test]
+    fn test_unfinished_if_expression_is_reported() {
+        assert_eq!("1:7: expressão esperada depois de 'se'", error("x = se"));
+        assert_eq!("1:3: expressão esperada depois de 'se'", error("se"));
+        assert_eq!(
+            "1:24: expressão esperada depois de 'senao:'",
+            error("se Verdadeiro: 1 senao:")
+        );
+        assert_eq!(
+            "1:13: ':' esperado, mas a entrada terminou",
+            error("imprima(se 1")
+        );
+        assert_eq!("1:6: ':' esperado, encontrado '2'", error("se 1 2"));
+        assert_eq!("1:6: expressão esperada depois de ':'", error("se 1:"));
+    }
+
+    #[test]
+    fn test_break_continue_and_pass() {
+        let parse_ast = parse_program("interrompa continue passe");
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![
+                    ast::Statement::Break,
+                    ast::Statement::Continue,
+                    ast::Statement::Pass,
+                ]
+            })
+        )
+    }
+
+    #[test]
+    fn test_imports() {
+        let alias = |name: &str, asname: Option<&str>| ast::Alias {
+            name: name.into(),
+            asname: asname.map(Into::into),
+        };
+        let parse_ast = parse_program("importe a, b como c de d importe h como f, g");
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![
+                    ast::Statement::Import {
+                        names: vec![alias("a", None), alias("b", Some("c"))]
+                    },
+                    ast::Statement::ImportFrom {
+                        module: "d".into(),
+                        names: vec![alias("h", Some("f")), alias("g", None)]
+                    },
+                ]
+            })
+        );
+
+        assert_eq!("1:6: 'importe' esperado depois de 'de a'", error("de a b"));
+        assert_eq!(
+            "1:16: nome esperado depois de 'como'",
+            error("importe a como 1")
+        );
+    }
+
+    #[test]
+    fn test_with() {
+        let identifier = |name: &str| ast::Expression::Identifier { name: name.into() };
+        assert_eq!(
+            parse_program("com a como b, c: d"),
+            Ok(ast::Program {
+                statements: vec![ast::Statement::With {
+                    items: vec![
+                        ast::WithItem {
+                            context_expr: identifier("a"),
+                            optional_vars: Some(identifier("b")),
+                        },
+                        ast::WithItem {
+                            context_expr: identifier("c"),
+                            optional_vars: None,
+                        },
+                    ],
+                    body: Box::new(ast::Statement::Expr {
+                        expression: identifier("d")
+                    }),
+                }]
+            })
+        );
+
+        assert_eq!(
+            "1:14: ':' esperado depois de 'com'",
+            error("com a como b d")
+        );
+        assert_eq!(
+            "1:12: não é possível atribuir a '1'",
+            error("com a como 1: b")
+        );
+        assert_eq!("1:7: instrução esperada depois de ':'", error("com a:"));
+    }
+
+    #[test]
+    fn test_augmented_assignment() {
+        let parse_ast = parse_program("total //= 2");
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![ast::Statement::AugAssign {
+                    target: ast::Expression::Identifier {
+                        name: "total".into()
+                    },
+                    op: Operator::FloorDiv,
+                    value: ast::Expression::Num {
+                        value: Number::Integer { value: 2 }
+                    },
+                }]
+            })
+        )
+    }
+
+    #[test]
+    fn test_chained_assignment() {
+        let parse_ast = parse_program("a = b = 0");
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![ast::Statement::Assign {
+                    targets: vec![
+                        ast::Expression::Identifier { name: "a".into() },
+                        ast::Expression::Identifier { name: "b".into() },
+                    ],
+                    value: ast::Expression::Num {
+                        value: Number::Integer { value: 0 }
+                    },
+                }]
+            })
+        )
+    }
+
+    #[test]
+    fn test_tuple_assignment() {
+        let parse_ast = parse_program("a, b = b, a");
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![ast::Statement::Assign {
+                    targets: vec![ast::Expression::Tuple {
+                        elements: vec![
+                            ast::Expression::Identifier { name: "a".into() },
+                            ast::Expression::Identifier { name: "b".into() },
+                        ]
+                    }],
+                    value: ast::Expression::Tuple {
+                        elements: vec![
+                            ast::Expression::Identifier { name: "b".into() },
+                            ast::Expression::Identifier { name: "a".into() },
+                        ]
+                    },
+                }]
+            })
+        )
+    }
+
+    #[test]
+    fn test_subscript_and_attribute_assignment() {
+        let parse_ast = parse_program("obj.x = 2");
+        assert_eq!(
+            parse_ast,
+            Ok(ast::Program {
+                statements: vec![ast::Statement::Assign {
+                    targets: vec![ast::Expression::Attribute {
+                        value: Box::new(ast::Expression::Identifier { name: "obj".into() }),
+                        name: "x".into(),
+                    }],
+                    value: ast::Expression::Num {
+                        value: Number::Integer { value: 2 }
+                    },
+                }]
+            })
+        );
+
+        let message =
+            "não é possível atribuir a 'lst[0]': nenhum valor pode ser alterado pelo índice";
+        assert_eq!(format!("1:1: {}", message), error("lst[0] = 1"));
+        assert_eq!(format!("1:1: {}", message), error("lst[0] += 1"));
+        assert_eq!(
+            "1:1: não é possível atribuir a 'a, lst[0]': nenhum valor pode ser alterado pelo índice",
+            error("a, lst[0] = 1, 2")
+        );
+    }
+
+    #[test]
+    fn test_tuples_may_end_with_a_comma() {
+        let tuple = |values: &[i64]| {
+            Ok(ast::Program {
+                statements: vec![ast::Statement::Expr {
+                    expression: ast::Expression::Tuple {
+                        elements: values
+                            .iter()
+                            .map(|&value| ast::Expression::Num {
+                                value: Number::Integer { value },
+                            })
+                            .collect(),
+                    },
+                }],
+            })
+        };
+        assert_eq!(tuple(&[1]), parse_program("(1,)"));
+        assert_eq!(tuple(&[1, 2]), parse_program("(1, 2,)"));
+        assert_eq!(tuple(&[1, 2]), parse_program("(1, 2)"));
+        assert_eq!("1:4: fim inesperado da entrada", error("(1,"));
+        assert_eq!("1:4: token inesperado: ','", error("(1,,)"));
+    }
+
+    #[test]
+    fn test_e_after_a_dot_is_a_name() {
+        let attribute = |name: &str| ast::Expression::Attribute {
+            value: Box::new(ast::Expression::Identifier { name: "m".into() }),
+            name: name.into(),
+        };
+        assert_eq!(
+            parse_program("m.e e m.x"),
+            Ok(ast::Program {
+                statements: vec![ast::Statement::Expr {
+                    expression: ast::Expression::BoolOp {
+                        a: Box::new(attribute("e")),
+                        op: BooleanOperation::And,
+                        b: Box::new(attribute("x")),
+                    }
+                }]
+            })
+        )
+    }
+
+    #[test]
+    fn test_invalid_assignment_targets() {
+        assert_eq!("1:1: não é possível atribuir a '1'", error("1 = a"));
+        assert_eq!(
+            "1:5: não é possível atribuir a '(1 + 2)'",
+            error("a = (1 + 2) = b")
+        );
+        assert_eq!(
+            "1:1: atribuição aumentada não aceita múltiplos alvos",
+            error("a, b += 1")
+        );
+    }
 }
@@ -2,22 +2,45 @@ use self::Primitive::*;
 use crate::error::Error;
 use crate::error::OperatorError;
 
+use num_bigint::BigInt;
+use num_integer::Integer as _;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use std::convert::TryFrom;
+use std::fmt;
+
 type Result<T> = ::std::result::Result<T, Error>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Primitive {
     Integer(i64),
+    BigInteger(BigInt),
     Float(f64),
     Str(String),
     Boolean(bool),
 }
 
+/// Operands of a mixed operation where at least one side is a `BigInteger`.
+enum Promoted {
+    Big(BigInt, BigInt),
+    Float(f64, f64),
+}
+
 impl From<i64> for Primitive {
     fn from(n: i64) -> Self {
         Primitive::Integer(n)
     }
 }
 
+impl From<BigInt> for Primitive {
+    fn from(n: BigInt) -> Self {
+        match n.to_i64() {
+            Some(small) => Primitive::Integer(small),
+            None => Primitive::BigInteger(n),
+        }
+    }
+}
+
 impl From<f64> for Primitive {
     fn from(n: f64) -> Self {
         Primitive::Float(n)
@@ -36,7 +59,66 @@ impl From<bool> for Primitive {
     }
 }
 
+/// How `imprima` shows the value: texts without quotes, logicals as the
+/// literals of the language.
+impl fmt::Display for Primitive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Integer(n) => write!(f, "{}", n),
+            BigInteger(n) => write!(f, "{}", n),
+            Float(n) if n.is_nan() => write!(f, "nan"),
+            Float(n) if n.is_infinite() => {
+                write!(f, "{}infinito", if *n < 0.0 { "-" } else { "" })
+            }
+            // very large and very small reals are written with an exponent,
+            // as Python does: `1e+20`, `1.5e-07`
+            Float(n) if *n != 0.0 && (n.abs() >= 1e16 || n.abs() < 1e-4) => {
+                let scientific = format!("{:e}", n);
+                let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+                let exponent: i32 = exponent.parse().unwrap_or(0);
+                let sign = if exponent < 0 { '-' } else { '+' };
+                write!(f, "{}e{}{:02}", mantissa, sign, exponent.abs())
+            }
+            // a real always looks like one, even when it has no fraction
+            Float(n) if n.fract() == 0.0 => write!(f, "{:.1}", n),
+            Float(n) => write!(f, "{}", n),
+            Str(s) => write!(f, "{}", s),
+            Boolean(true) => write!(f, "Verdadeiro"),
+            Boolean(false) => write!(f, "Falso"),
+        }
+    }
+}
+
 impl Primitive {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Integer(_) | BigInteger(_) => "inteiro",
+            Float(_) => "real",
+            Str(_) => "texto",
+            Boolean(_) => "logico",
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Integer(_) | BigInteger(_) | Float(_))
+    }
+
+    /// Like `Display`, but a text is quoted, as it is inside a tuple.
+    pub fn repr(&self) -> String {
+        match self {
+            Str(s) => format!("{:?}", s),
+            other => other.to_string(),
+        }
+    }
+
+    /// The value as error messages show it, with texts in single quotes.
+    pub fn describe(&self) -> String {
+        match self {
+            Str(s) => format!("'{}'", s),
+            other => other.to_string(),
+        }
+    }
+
     pub fn negate(&self) -> Result<Self> {
         match self {
             Boolean(i) => Ok(Boolean(!*i)),
@@ -46,7 +128,11 @@ impl Primitive {
 
     pub fn minus(&self) -> Result<Self> {
         match self {
-            Integer(v) => Ok(Integer(-*v)),
+            Integer(v) => Ok(v
+                .checked_neg()
+                .map(Integer)
+                .unwrap_or_else(|| (-BigInt::from(*v)).into())),
+            BigInteger(v) => Ok((-v).into()),
             Float(v) => Ok(Float(-*v)),
             l => Self::error(l, None, OperatorError::UnarySub),
         }
@@ -54,8 +140,7 @@ impl Primitive {
 
     pub fn plus(&self) -> Result<Self> {
         match self {
-            Integer(v) => Ok(Integer((-1 * *v))),
-            Float(v) => Ok(Float((-1.0 * *v))),
+            Integer(_) | BigInteger(_) | Float(_) => Ok(self.clone()),
             l => Self::error(l, None, OperatorError::UnaryPlus),
         }
     }
@@ -83,7 +168,11 @@ impl Primitive {
             (Float(left), Float(right)) => (*left == *right).into(),
             (Float(left), Integer(right)) => (*left == (*right as f64)).into(),
             (Integer(left), Float(right)) => ((*left as f64) == *right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::Equal)?,
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => (l == r).into(),
+                Some(Promoted::Float(l, r)) => (l == r).into(),
+                None => Self::error(left, Some(right), OperatorError::Equal)?,
+            },
         };
         Ok(res)
     }
@@ -95,7 +184,11 @@ impl Primitive {
             (Float(left), Float(right)) => (*left != *right).into(),
             (Float(left), Integer(right)) => (*left != (*right as f64)).into(),
             (Integer(left), Float(right)) => ((*left as f64) != *right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::NotEqual)?,
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => (l != r).into(),
+                Some(Promoted::Float(l, r)) => (l != r).into(),
+                None => Self::error(left, Some(right), OperatorError::NotEqual)?,
+            },
         };
         Ok(res)
     }
@@ -106,7 +199,12 @@ impl Primitive {
             (Float(left), Float(right)) => (*left < *right).into(),
             (Float(left), Integer(right)) => (*left < (*right as f64)).into(),
             (Integer(left), Float(right)) => ((*left as f64) < *right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::LessThan)?,
+            (Str(left), Str(right)) => (left < right).into(),
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => (l < r).into(),
+                Some(Promoted::Float(l, r)) => (l < r).into(),
+                None => Self::error(left, Some(right), OperatorError::LessThan)?,
+            },
         };
         Ok(res)
     }
@@ -117,7 +215,12 @@ impl Primitive {
             (Float(left), Float(right)) => (*left <= *right).into(),
             (Float(left), Integer(right)) => (*left <= (*right as f64)).into(),
             (Integer(left), Float(right)) => ((*left as f64) <= *right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::LessThanEqual)?,
+            (Str(left), Str(right)) => (left <= right).into(),
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => (l <= r).into(),
+                Some(Promoted::Float(l, r)) => (l <= r).into(),
+                None => Self::error(left, Some(right), OperatorError::LessThanEqual)?,
+            },
         };
         Ok(res)
     }
@@ -128,7 +231,12 @@ impl Primitive {
             (Float(left), Float(right)) => (*left > *right).into(),
             (Float(left), Integer(right)) => (*left > (*right as f64)).into(),
             (Integer(left), Float(right)) => ((*left as f64) > *right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::GreaterThan)?,
+            (Str(left), Str(right)) => (left > right).into(),
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => (l > r).into(),
+                Some(Promoted::Float(l, r)) => (l > r).into(),
+                None => Self::error(left, Some(right), OperatorError::GreaterThan)?,
+            },
         };
         Ok(res)
     }
@@ -139,93 +247,302 @@ impl Primitive {
             (Float(left), Float(right)) => (*left >= *right).into(),
             (Float(left), Integer(right)) => (*left >= (*right as f64)).into(),
             (Integer(left), Float(right)) => ((*left as f64) >= *right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::GreaterThanEqual)?,
+            (Str(left), Str(right)) => (left >= right).into(),
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => (l >= r).into(),
+                Some(Promoted::Float(l, r)) => (l >= r).into(),
+                None => Self::error(left, Some(right), OperatorError::GreaterThanEqual)?,
+            },
         };
         Ok(res)
     }
 
     pub fn is(&self, other: &Self) -> Result<Self> {
         let res = match (self, other) {
-            (Integer(_), Integer(_)) => (true).into(),
+            (Integer(_), Integer(_))
+            | (Integer(_), BigInteger(_))
+            | (BigInteger(_), Integer(_))
+            | (BigInteger(_), BigInteger(_)) => (true).into(),
             (Float(_), Float(_)) => (true).into(),
             (Boolean(_), Boolean(_)) => (true).into(),
             (Str(_), Str(_)) => (true).into(),
-            (left, right) => (false).into(),
+            _ => (false).into(),
         };
         Ok(res)
     }
 
     pub fn add(&self, other: &Self) -> Result<Self> {
         let res = match (self, other) {
-            (Integer(left), Integer(right)) => (left + right).into(),
+            (Integer(left), Integer(right)) => match left.checked_add(*right) {
+                Some(n) => n.into(),
+                None => (BigInt::from(*left) + right).into(),
+            },
             (Float(left), Float(right)) => (left + right).into(),
             (Float(left), Integer(right)) => (left + (*right as f64)).into(),
             (Integer(left), Float(right)) => ((*left as f64) + right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::Add)?,
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => (l + r).into(),
+                Some(Promoted::Float(l, r)) => (l + r).into(),
+                None => Self::error(left, Some(right), OperatorError::Add)?,
+            },
         };
         Ok(res)
     }
 
     pub fn sub(&self, other: &Self) -> Result<Self> {
         let res = match (self, other) {
-            (Integer(left), Integer(right)) => (left - right).into(),
+            (Integer(left), Integer(right)) => match left.checked_sub(*right) {
+                Some(n) => n.into(),
+                None => (BigInt::from(*left) - right).into(),
+            },
             (Float(left), Float(right)) => (left - right).into(),
             (Float(left), Integer(right)) => (left - (*right as f64)).into(),
             (Integer(left), Float(right)) => ((*left as f64) - right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::Sub)?,
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => (l - r).into(),
+                Some(Promoted::Float(l, r)) => (l - r).into(),
+                None => Self::error(left, Some(right), OperatorError::Sub)?,
+            },
         };
         Ok(res)
     }
 
     pub fn mul(&self, other: &Self) -> Result<Self> {
         let res = match (self, other) {
-            (Integer(left), Integer(right)) => (left * right).into(),
+            (Integer(left), Integer(right)) => match left.checked_mul(*right) {
+                Some(n) => n.into(),
+                None => (BigInt::from(*left) * right).into(),
+            },
             (Float(left), Float(right)) => (left * right).into(),
             (Float(left), Integer(right)) => (left * (*right as f64)).into(),
             (Integer(left), Float(right)) => ((*left as f64) * right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::Mul)?,
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => (l * r).into(),
+                Some(Promoted::Float(l, r)) => (l * r).into(),
+                None => Self::error(left, Some(right), OperatorError::Mul)?,
+            },
         };
         Ok(res)
     }
 
     pub fn real_div(&self, other: &Self) -> Result<Self> {
         let res = match (self, other) {
+            (Integer(_), _) | (BigInteger(_), _) | (Float(_), _) if other.is_zero() => {
+                Self::zero_division(self, other, OperatorError::RealDiv)?
+            }
             (Integer(left), Integer(right)) => ((*left as f64) / (*right as f64)).into(),
             (Float(left), Float(right)) => (left / right).into(),
             (Float(left), Integer(right)) => (left / (*right as f64)).into(),
             (Integer(left), Float(right)) => ((*left as f64) / right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::RealDiv)?,
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => (big_to_f64(&l) / big_to_f64(&r)).into(),
+                Some(Promoted::Float(l, r)) => (l / r).into(),
+                None => Self::error(left, Some(right), OperatorError::RealDiv)?,
+            },
         };
         Ok(res)
     }
 
+    /// Floor division, rounding towards negative infinity like `math.floor(a / b)`.
     pub fn int_div(&self, other: &Self) -> Result<Self> {
         let res = match (self, other) {
-            (Integer(left), Integer(right)) => (left / right).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::IntDiv)?,
+            (Integer(_), _) | (BigInteger(_), _) | (Float(_), _) if other.is_zero() => {
+                Self::zero_division(self, other, OperatorError::IntDiv)?
+            }
+            (Integer(left), Integer(right)) => match left.checked_div(*right) {
+                Some(_) => left.div_floor(right).into(),
+                None => BigInt::from(*left).div_floor(&BigInt::from(*right)).into(),
+            },
+            (Float(left), Float(right)) => floor_div_f64(*left, *right).into(),
+            (Float(left), Integer(right)) => floor_div_f64(*left, *right as f64).into(),
+            (Integer(left), Float(right)) => floor_div_f64(*left as f64, *right).into(),
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => l.div_floor(&r).into(),
+                Some(Promoted::Float(l, r)) => floor_div_f64(l, r).into(),
+                None => Self::error(left, Some(right), OperatorError::IntDiv)?,
+            },
         };
         Ok(res)
     }
 
+    /// The remainder takes the sign of the divisor, so `a == (a // b) * b + a % b`.
     pub fn module(&self, other: &Self) -> Result<Self> {
         let res = match (self, other) {
-            (Integer(left), Integer(right)) => (left % right).into(),
-            (Float(left), Float(right)) => (left % right).into(),
-            (Integer(left), Float(right)) => ((*left as f64) % right).into(),
-            (Float(left), Float(right)) => (left % (*right as f64)).into(),
-            (left, right) => Self::error(left, Some(right), OperatorError::Module)?,
+            (Integer(_), _) | (BigInteger(_), _) | (Float(_), _) if other.is_zero() => {
+                Self::zero_division(self, other, OperatorError::Module)?
+            }
+            (Integer(left), Integer(right)) => floor_mod_i64(*left, *right).into(),
+            (Float(left), Float(right)) => floor_mod_f64(*left, *right).into(),
+            (Integer(left), Float(right)) => floor_mod_f64(*left as f64, *right).into(),
+            (Float(left), Integer(right)) => floor_mod_f64(*left, *right as f64).into(),
+            (left, right) => match Self::promote(left, right) {
+                Some(Promoted::Big(l, r)) => l.mod_floor(&r).into(),
+                Some(Promoted::Float(l, r)) => floor_mod_f64(l, r).into(),
+                None => Self::error(left, Some(right), OperatorError::Module)?,
+            },
+        };
+        Ok(res)
+    }
+
+    /// Integers raised to non-negative integers stay exact (growing into a
+    /// `BigInteger` when needed); everything else is computed as a float.
+    pub fn pow(&self, other: &Self) -> Result<Self> {
+        let res = match (self, other) {
+            (Integer(_), _) | (BigInteger(_), _) | (Float(_), _)
+                if self.is_zero() && other.is_negative() =>
+            {
+                Self::zero_division(self, other, OperatorError::Pow)?
+            }
+            (Integer(left), Integer(right)) if *right >= 0 => {
+                let exponent = Self::exponent(self, other, *right)?;
+                match left.checked_pow(exponent) {
+                    Some(n) => n.into(),
+                    None => num_traits::pow(BigInt::from(*left), exponent as usize).into(),
+                }
+            }
+            (BigInteger(left), Integer(right)) if *right >= 0 => {
+                let exponent = Self::exponent(self, other, *right)?;
+                num_traits::pow(left.clone(), exponent as usize).into()
+            }
+            (Integer(_), BigInteger(_)) | (BigInteger(_), BigInteger(_)) => {
+                Self::error(self, Some(other), OperatorError::Pow)?
+            }
+            (left, right) => match (left.to_f64(), right.to_f64()) {
+                (Some(l), Some(r)) if l < 0.0 && r.fract() != 0.0 => {
+                    Self::error(left, Some(right), OperatorError::Pow)?
+                }
+                (Some(l), Some(r)) => l.powf(r).into(),
+                _ => Self::error(left, Some(right), OperatorError::Pow)?,
+            },
         };
         Ok(res)
     }
 
+    fn exponent(left: &Self, right: &Self, exponent: i64) -> Result<u32> {
+        u32::try_from(exponent)
+            .or_else(|_| Self::error(left, Some(right), OperatorError::Pow))
+    }
+
+    fn promote(left: &Self, right: &Self) -> Option<Promoted> {
+        match (left, right) {
+            (BigInteger(l), BigInteger(r)) => Some(Promoted::Big(l.clone(), r.clone())),
+            (BigInteger(l), Integer(r)) => Some(Promoted::Big(l.clone(), BigInt::from(*r))),
+            (Integer(l), BigInteger(r)) => Some(Promoted::Big(BigInt::from(*l), r.clone())),
+            (BigInteger(l), Float(r)) => Some(Promoted::Float(big_to_f64(l), *r)),
+            (Float(l), BigInteger(r)) => Some(Promoted::Float(*l, big_to_f64(r))),
+            _ => None,
+        }
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        match self {
+            Integer(n) => Some(*n as f64),
+            BigInteger(n) => Some(big_to_f64(n)),
+            Float(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        match self {
+            Integer(n) => *n == 0,
+            BigInteger(n) => n.is_zero(),
+            Float(n) => *n == 0.0,
+            _ => false,
+        }
+    }
+
+    fn is_negative(&self) -> bool {
+        match self {
+            Integer(n) => *n < 0,
+            BigInteger(n) => n.is_negative(),
+            Float(n) => *n < 0.0,
+            _ => false,
+        }
+    }
+
+    fn zero_division<T>(left: &Self, right: &Self, op: OperatorError) -> Result<T> {
+        Err(Error::ZeroDivision(op, left.clone(), right.clone()))
+    }
+
     fn error<T>(left: &Self, right: Option<&Self>, op: OperatorError) -> Result<T> {
         Err(Error::InvalidOperation(op, left.clone(), right.cloned()))
     }
 }
 
+pub(crate) fn big_to_f64(n: &BigInt) -> f64 {
+    n.to_f64().unwrap_or_else(|| {
+        if n.is_negative() {
+            f64::NEG_INFINITY
+        } else {
+            f64::INFINITY
+        }
+    })
+}
+
+fn floor_mod_i64(left: i64, right: i64) -> i64 {
+    let rem = left.wrapping_rem(right);
+    if rem != 0 && (rem < 0) != (right < 0) {
+        rem + right
+    } else {
+        rem
+    }
+}
+
+/// Divides as CPython does, from the remainder rather than by flooring the
+/// quotient, so `1 // 0.1` is `9.0` and agrees with `floor_mod_f64`.
+fn floor_div_f64(left: f64, right: f64) -> f64 {
+    let rem = left % right;
+    let mut div = (left - rem) / right;
+    if rem != 0.0 && (rem < 0.0) != (right < 0.0) {
+        div -= 1.0;
+    }
+    if div == 0.0 {
+        return 0.0f64.copysign(left / right);
+    }
+    // the division may land just off the integer it should be
+    let floor = div.floor();
+    if div - floor > 0.5 {
+        floor + 1.0
+    } else {
+        floor
+    }
+}
+
+fn floor_mod_f64(left: f64, right: f64) -> f64 {
+    let rem = left % right;
+    if rem != 0.0 && (rem < 0.0) != (right < 0.0) {
+        rem + right
+    } else {
+        rem
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn display() {
+        assert_eq!("42", Integer(42).to_string());
+        assert_eq!("2.5", Float(2.5).to_string());
+        assert_eq!("3.0", Float(3.0).to_string());
+        assert_eq!("1e+20", Float(1e20).to_string());
+        assert_eq!("-1.5e+16", Float(-1.5e16).to_string());
+        assert_eq!("1234567890123456.0", Float(1234567890123456.0).to_string());
+        assert_eq!("1.5e-07", Float(1.5e-7).to_string());
+        assert_eq!("0.0001", Float(1e-4).to_string());
+        assert_eq!("1.7976931348623157e+308", Float(f64::MAX).to_string());
+        assert_eq!("5e-324", Float(5e-324).to_string());
+        assert_eq!("-0.0", Float(-0.0).to_string());
+        assert_eq!("-infinito", Float(f64::NEG_INFINITY).to_string());
+        assert_eq!("nan", Float(f64::NAN).to_string());
+        assert_eq!("olá", Str("olá".into()).to_string());
+        assert_eq!("\"olá\"", Str("olá".into()).repr());
+        assert_eq!("Verdadeiro", Boolean(true).to_string());
+        assert_eq!("Falso", Boolean(false).repr());
+    }
+
     #[test]
     fn negate() {
         let truth = Primitive::Boolean(true);
@@ -356,6 +673,14 @@ mod tests {
         assert_eq!(Primitive::Boolean(false), a.less_than(&b).unwrap())
     }
 
+    #[test]
+    fn less_str() {
+        let a = Primitive::Str("abacate".into());
+        let b = Primitive::Str("banana".into());
+        assert_eq!(Primitive::Boolean(true), a.less_than(&b).unwrap());
+        assert_eq!(Primitive::Boolean(false), b.less_than_equal(&a).unwrap())
+    }
+
     #[test]
     fn less_eq_int() {
         let a = Primitive::Integer(1);
@@ -582,7 +907,7 @@ mod tests {
     #[test]
     fn unary_plus() {
         let a = Primitive::Integer(-10);
-        assert_eq!(Primitive::Integer(10), a.plus().unwrap())
+        assert_eq!(Primitive::Integer(-10), a.plus().unwrap())
     }
 
     #[test]
@@ -591,4 +916,330 @@ mod tests {
         let b = Primitive::Integer(2);
         assert_eq!(Primitive::Integer(0), a.module(&b).unwrap())
     }
+
+    #[test]
+    fn div_real_by_zero_int() {
+        let a = Primitive::Integer(1);
+        let b = Primitive::Integer(0);
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::RealDiv,
+                a.clone(),
+                b.clone()
+            )),
+            a.real_div(&b)
+        )
+    }
+
+    #[test]
+    fn div_real_by_zero_float() {
+        let a = Primitive::Float(1.0);
+        let b = Primitive::Float(0.0);
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::RealDiv,
+                a.clone(),
+                b.clone()
+            )),
+            a.real_div(&b)
+        )
+    }
+
+    #[test]
+    fn div_real_by_zero_int_float() {
+        let a = Primitive::Integer(1);
+        let b = Primitive::Float(-0.0);
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::RealDiv,
+                a.clone(),
+                b.clone()
+            )),
+            a.real_div(&b)
+        )
+    }
+
+    #[test]
+    fn div_real_by_zero_float_int() {
+        let a = Primitive::Float(1.0);
+        let b = Primitive::Integer(0);
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::RealDiv,
+                a.clone(),
+                b.clone()
+            )),
+            a.real_div(&b)
+        )
+    }
+
+    #[test]
+    fn div_int_by_zero() {
+        let a = Primitive::Integer(10);
+        let b = Primitive::Integer(0);
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::IntDiv,
+                a.clone(),
+                b.clone()
+            )),
+            a.int_div(&b)
+        )
+    }
+
+    #[test]
+    fn module_by_zero_int() {
+        let a = Primitive::Integer(10);
+        let b = Primitive::Integer(0);
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::Module,
+                a.clone(),
+                b.clone()
+            )),
+            a.module(&b)
+        )
+    }
+
+    #[test]
+    fn module_by_zero_float() {
+        let a = Primitive::Float(10.0);
+        let b = Primitive::Float(0.0);
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::Module,
+                a.clone(),
+                b.clone()
+            )),
+            a.module(&b)
+        )
+    }
+
+    #[test]
+    fn module_by_zero_int_float() {
+        let a = Primitive::Integer(10);
+        let b = Primitive::Float(0.0);
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::Module,
+                a.clone(),
+                b.clone()
+            )),
+            a.module(&b)
+        )
+    }
+
+    #[test]
+    fn module_by_zero_float_int() {
+        let a = Primitive::Float(10.0);
+        let b = Primitive::Integer(0);
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::Module,
+                a.clone(),
+                b.clone()
+            )),
+            a.module(&b)
+        )
+    }
+
+    #[test]
+    fn module_float_int() {
+        let a = Primitive::Float(10.5);
+        let b = Primitive::Integer(2);
+        assert_eq!(Primitive::Float(0.5), a.module(&b).unwrap())
+    }
+
+    #[test]
+    fn module_int_min_by_minus_one() {
+        let a = Primitive::Integer(i64::MIN);
+        let b = Primitive::Integer(-1);
+        assert_eq!(Primitive::Integer(0), a.module(&b).unwrap())
+    }
+
+    #[test]
+    fn div_by_zero_non_number() {
+        let a = Primitive::Str("texto".into());
+        let b = Primitive::Integer(0);
+        assert_eq!(
+            Err(Error::InvalidOperation(
+                OperatorError::RealDiv,
+                a.clone(),
+                Some(b.clone())
+            )),
+            a.real_div(&b)
+        )
+    }
+
+    #[test]
+    fn floor_div_int_negative() {
+        let a = Primitive::Integer(-7);
+        let b = Primitive::Integer(2);
+        assert_eq!(Primitive::Integer(-4), a.int_div(&b).unwrap())
+    }
+
+    #[test]
+    fn floor_div_float() {
+        let a = Primitive::Float(-7.5);
+        let b = Primitive::Float(2.0);
+        assert_eq!(Primitive::Float(-4.0), a.int_div(&b).unwrap())
+    }
+
+    #[test]
+    fn floor_div_int_float() {
+        let a = Primitive::Integer(7);
+        let b = Primitive::Float(2.0);
+        assert_eq!(Primitive::Float(3.0), a.int_div(&b).unwrap())
+    }
+
+    #[test]
+    fn floor_div_float_int() {
+        let a = Primitive::Float(7.0);
+        let b = Primitive::Integer(-2);
+        assert_eq!(Primitive::Float(-4.0), a.int_div(&b).unwrap())
+    }
+
+    #[test]
+    fn floor_div_float_agrees_with_module() {
+        let tenth = Primitive::Float(0.1);
+        assert_eq!(
+            Primitive::Float(9.0),
+            Primitive::Integer(1).int_div(&tenth).unwrap()
+        );
+        assert_eq!(
+            Primitive::Float(-10.0),
+            Primitive::Integer(-1).int_div(&tenth).unwrap()
+        );
+        assert_eq!(
+            Primitive::Float(-1.0),
+            Primitive::Integer(-1)
+                .int_div(&Primitive::Float(f64::INFINITY))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn floor_div_overflow_grows_into_big_integer() {
+        let a = Primitive::Integer(i64::MIN);
+        let b = Primitive::Integer(-1);
+        assert_eq!(
+            Primitive::BigInteger(BigInt::from(i64::MAX) + 1),
+            a.int_div(&b).unwrap()
+        )
+    }
+
+    #[test]
+    fn module_takes_sign_of_divisor() {
+        let a = Primitive::Integer(-7);
+        let b = Primitive::Integer(2);
+        assert_eq!(Primitive::Integer(1), a.module(&b).unwrap());
+
+        let a = Primitive::Integer(7);
+        let b = Primitive::Integer(-2);
+        assert_eq!(Primitive::Integer(-1), a.module(&b).unwrap());
+
+        let a = Primitive::Float(-7.5);
+        let b = Primitive::Integer(2);
+        assert_eq!(Primitive::Float(0.5), a.module(&b).unwrap())
+    }
+
+    #[test]
+    fn pow_int() {
+        let a = Primitive::Integer(2);
+        let b = Primitive::Integer(10);
+        assert_eq!(Primitive::Integer(1024), a.pow(&b).unwrap())
+    }
+
+    #[test]
+    fn pow_int_negative_exponent() {
+        let a = Primitive::Integer(2);
+        let b = Primitive::Integer(-1);
+        assert_eq!(Primitive::Float(0.5), a.pow(&b).unwrap())
+    }
+
+    #[test]
+    fn pow_float() {
+        let a = Primitive::Float(4.0);
+        let b = Primitive::Float(0.5);
+        assert_eq!(Primitive::Float(2.0), a.pow(&b).unwrap())
+    }
+
+    #[test]
+    fn pow_int_float() {
+        let a = Primitive::Integer(9);
+        let b = Primitive::Float(0.5);
+        assert_eq!(Primitive::Float(3.0), a.pow(&b).unwrap())
+    }
+
+    #[test]
+    fn pow_float_int() {
+        let a = Primitive::Float(1.5);
+        let b = Primitive::Integer(2);
+        assert_eq!(Primitive::Float(2.25), a.pow(&b).unwrap())
+    }
+
+    #[test]
+    fn pow_big_integer() {
+        let a = Primitive::Integer(2);
+        let b = Primitive::Integer(100);
+        let expected: BigInt = "1267650600228229401496703205376".parse().unwrap();
+        assert_eq!(Primitive::BigInteger(expected), a.pow(&b).unwrap())
+    }
+
+    #[test]
+    fn pow_zero_to_negative_exponent() {
+        let a = Primitive::Integer(0);
+        let b = Primitive::Integer(-1);
+        assert_eq!(
+            Err(Error::ZeroDivision(
+                OperatorError::Pow,
+                a.clone(),
+                b.clone()
+            )),
+            a.pow(&b)
+        )
+    }
+
+    #[test]
+    fn pow_negative_base_fractional_exponent() {
+        let a = Primitive::Float(-8.0);
+        let b = Primitive::Float(0.5);
+        assert_eq!(
+            Err(Error::InvalidOperation(
+                OperatorError::Pow,
+                a.clone(),
+                Some(b.clone())
+            )),
+            a.pow(&b)
+        )
+    }
+
+    #[test]
+    fn big_integer_arithmetic_shrinks_back() {
+        let big = Primitive::Integer(i64::MAX)
+            .add(&Primitive::Integer(1))
+            .unwrap();
+        assert_eq!(Primitive::BigInteger(BigInt::from(i64::MAX) + 1), big);
+        assert_eq!(
+            Primitive::Integer(i64::MAX),
+            big.sub(&Primitive::Integer(1)).unwrap()
+        )
+    }
+
+    #[test]
+    fn big_integer_comparison() {
+        let big = Primitive::Integer(2).pow(&Primitive::Integer(70)).unwrap();
+        assert_eq!(
+            Primitive::Boolean(true),
+            big.greater_than(&Primitive::Integer(i64::MAX)).unwrap()
+        );
+        assert_eq!(
+            Primitive::Boolean(true),
+            big.less_than(&Primitive::Float(1e30)).unwrap()
+        );
+        assert_eq!(
+            Primitive::Boolean(true),
+            big.is(&Primitive::Integer(1)).unwrap()
+        )
+    }
 }
@@ -0,0 +1,1563 @@
+//! The regular expressions of the `regex` module.
+//!
+//! The syntax is Python's `re`, and so is the matching: a backtracking
+//! matcher that takes the first alternative that matches, which is what lazy
+//! quantifiers, backreferences and lookarounds need. Positions count
+//! characters, as indexes into texts do.
+
+use crate::builtin::{argument, type_error};
+use crate::error::Error;
+use crate::native::{Arity, NativeFunction};
+use crate::object::Object;
+use crate::primitive::Primitive;
+
+use std::cell::Cell;
+use std::fmt;
+use std::rc::Rc;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+/// The most instructions a pattern compiles to. Counted repetitions are
+/// written out, so `(abc){1000}` takes a few thousand.
+const MAX_PROGRAM: usize = 100_000;
+
+/// The fewest steps a search may take before it gives up. A pattern like
+/// `(a*)*b` backtracks through exponentially many ways to match, so a search
+/// stops after this many steps or 100 per instruction and character.
+const MIN_STEPS: usize = 1_000_000;
+
+/// How a pattern matches, set by the options of `compile` or by `(?ims)` at
+/// the start of the pattern.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub(crate) struct Flags {
+    /// `ignore_caixa`, `(?i)`: letters match in either case.
+    pub ignore_case: bool,
+    /// `multilinha`, `(?m)`: `^` and `$` match at every line.
+    pub multiline: bool,
+    /// `ponto_tudo`, `(?s)`: `.` matches `\n` too.
+    pub dot_all: bool,
+}
+
+/// A compiled regular expression. Clones share the compiled program.
+#[derive(Clone)]
+pub struct Pattern {
+    compiled: Rc<Compiled>,
+}
+
+struct Compiled {
+    source: String,
+    flags: Flags,
+    program: Program,
+    groups: usize,
+    names: Vec<(String, usize)>,
+}
+
+/// What a pattern found in a text: where it and each of its groups are.
+#[derive(Clone)]
+pub struct Match {
+    pattern: Pattern,
+    text: Rc<Vec<char>>,
+    // the start and the end of each group, the whole match first
+    spans: Rc<Vec<Option<usize>>>,
+}
+
+impl Pattern {
+    pub(crate) fn new(source: &str, flags: Flags) -> Result<Self> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            position: 0,
+            flags,
+            flags_end: 0,
+            closed: Vec::new(),
+            names: Vec::new(),
+        };
+        let node = parser.alternation()?;
+        if parser.position < parser.chars.len() {
+            return Err(parser.error("')' sem '(' correspondente", parser.position));
+        }
+        let groups = parser.closed.len();
+        let program = Compiler::compile(&node, groups, parser.flags)?;
+        Ok(Pattern {
+            compiled: Rc::new(Compiled {
+                source: source.into(),
+                flags,
+                program,
+                groups,
+                names: parser.names,
+            }),
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.compiled.source
+    }
+
+    pub fn groups(&self) -> usize {
+        self.compiled.groups
+    }
+
+    pub(crate) fn flags(&self) -> Flags {
+        self.compiled.flags
+    }
+
+    /// The first match in the text or, when `anchored`, the match at its
+    /// start.
+    pub(crate) fn search(&self, text: &str, anchored: bool) -> Result<Option<Match>> {
+        let text = Rc::new(text.chars().collect::<Vec<_>>());
+        let spans = self.find(&text, 0, anchored, None)?;
+        Ok(spans.map(|spans| self.found(&text, spans)))
+    }
+
+    /// The matches that do not overlap, from the left, at most `limit` of
+    /// them unless it is 0. As in Python 3.7, an empty match may follow a
+    /// match that is not, but never another empty match at the same place.
+    fn find_iter(&self, text: &Rc<Vec<char>>, limit: usize) -> Result<Vec<Match>> {
+        let mut matches = Vec::new();
+        let mut position = 0;
+        let mut forbidden = None;
+        while position <= text.len() && (limit == 0 || matches.len() < limit) {
+            let spans = match self.find(text, position, false, forbidden)? {
+                Some(spans) => spans,
+                None => break,
+            };
+            let (start, end) = (spans[0].unwrap(), spans[1].unwrap());
+            forbidden = if start == end { Some(end) } else { None };
+            position = end;
+            matches.push(self.found(text, spans));
+        }
+        Ok(matches)
+    }
+
+    fn find(
+        &self,
+        text: &[char],
+        from: usize,
+        anchored: bool,
+        forbidden: Option<usize>,
+    ) -> Result<Option<Vec<Option<usize>>>> {
+        let program = &self.compiled.program;
+        let steps = program
+            .insts
+            .len()
+            .saturating_mul(text.len() + 1)
+            .saturating_mul(100)
+            .max(MIN_STEPS);
+        let matcher = Matcher {
+            program,
+            text,
+            forbidden,
+            steps: Cell::new(steps),
+        };
+        let last = if anchored { from } else { text.len() };
+        for start in from..=last {
+            let mut slots = vec![None; program.slots];
+            let end = match matcher.run(0, start, start, &mut slots) {
+                Some(end) => end,
+                None if matcher.steps.get() == 0 => {
+                    return Err(Error::InvalidValue(format!(
+                        "a busca por '{}' tentou caminhos demais",
+                        self.source()
+                    )))
+                }
+                None => continue,
+            };
+            slots[0] = Some(start);
+            slots[1] = Some(end);
+            slots.truncate(2 * (self.compiled.groups + 1));
+            return Ok(Some(slots));
+        }
+        Ok(None)
+    }
+
+    fn found(&self, text: &Rc<Vec<char>>, spans: Vec<Option<usize>>) -> Match {
+        Match {
+            pattern: self.clone(),
+            text: Rc::clone(text),
+            spans: Rc::new(spans),
+        }
+    }
+
+    /// Python's `findall`: the texts of the matches when there are no
+    /// groups, of the group when there is one, and tuples of the groups
+    /// otherwise, with `""` for those that did not take part.
+    pub(crate) fn find_all(&self, text: &str) -> Result<Vec<Object>> {
+        let text = Rc::new(text.chars().collect::<Vec<_>>());
+        let groups = self.compiled.groups;
+        let matches = self.find_iter(&text, 0)?;
+        let all = matches.iter().map(|found| {
+            let group = |index| found.group(index).unwrap_or_default().into();
+            match groups {
+                0 => group(0),
+                1 => group(1),
+                _ => Object::Tuple((1..=groups).map(group).collect()),
+            }
+        });
+        Ok(all.collect())
+    }
+
+    /// Replaces the first `count` matches, or all when it is 0, by the
+    /// template, where `\1` and `\g<nome>` stand for groups, or by what the
+    /// function returns for each match.
+    pub(crate) fn substitute(
+        &self,
+        replacement: &Object,
+        text: &str,
+        count: usize,
+    ) -> Result<String> {
+        let template = match replacement {
+            Object::Primitive(Primitive::Str(template)) => Some(self.template(template)?),
+            Object::Native(_) => None,
+            other => return Err(type_error("substitua", "um texto ou uma função", other)),
+        };
+        let chars = Rc::new(text.chars().collect::<Vec<_>>());
+        let mut result = String::new();
+        let mut position = 0;
+        for found in self.find_iter(&chars, count)? {
+            let (start, end) = found.span(0).expect("the match has a span");
+            result.extend(&chars[position..start]);
+            match &template {
+                Some(pieces) => {
+                    for piece in pieces {
+                        match piece {
+                            Piece::Text(text) => result.push_str(text),
+                            Piece::Group(index) => {
+                                result.push_str(&found.group(*index).unwrap_or_default())
+                            }
+                        }
+                    }
+                }
+                None => match replacement.call(vec![found.into()], vec![])? {
+                    Object::Primitive(Primitive::Str(text)) => result.push_str(&text),
+                    other => {
+                        return Err(Error::InvalidArgument(format!(
+                            "a função de substitua() deve retornar um texto, não {}",
+                            other.type_name()
+                        )))
+                    }
+                },
+            }
+            position = end;
+        }
+        result.extend(&chars[position..]);
+        Ok(result)
+    }
+
+    /// The parts of the text between the first `limit` matches, or all
+    /// when it is 0, with the groups of each match between them.
+    pub(crate) fn split(&self, text: &str, limit: usize) -> Result<Vec<Object>> {
+        let chars = Rc::new(text.chars().collect::<Vec<_>>());
+        let piece = |start, end| chars[start..end].iter().collect::<String>().into();
+        let mut parts = Vec::new();
+        let mut position = 0;
+        for found in self.find_iter(&chars, limit)? {
+            let (start, end) = found.span(0).expect("the match has a span");
+            parts.push(piece(position, start));
+            for index in 1..=self.compiled.groups {
+                parts.push(found.group(index).map_or(Object::Unit, Object::from));
+            }
+            position = end;
+        }
+        parts.push(piece(position, chars.len()));
+        Ok(parts)
+    }
+
+    fn template(&self, template: &str) -> Result<Vec<Piece>> {
+        let invalid =
+            |message: String| Error::InvalidValue(format!("substituto inválido: {}", message));
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                text.push(c);
+                continue;
+            }
+            let group = match chars.next() {
+                None => return Err(invalid("'\\' no fim".into())),
+                Some('g') => {
+                    if chars.next() != Some('<') {
+                        return Err(invalid("esperava '<' depois de \\g".into()));
+                    }
+                    let name: String = chars.by_ref().take_while(|&c| c != '>').collect();
+                    let group = match name.parse::<i64>() {
+                        Ok(n) => Object::from(n),
+                        Err(_) => Object::from(name),
+                    };
+                    self.group_index("substitua", &group)?
+                }
+                Some(c @ '1'..='9') => {
+                    let mut number = c.to_digit(10).unwrap() as i64;
+                    if let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        number = number * 10 + i64::from(digit);
+                        chars.next();
+                    }
+                    self.group_index("substitua", &Object::from(number))?
+                }
+                Some(c) => {
+                    match simple_escape(c) {
+                        Some(c) => text.push(c),
+                        None if c.is_ascii_alphanumeric() => {
+                            return Err(invalid(format!("escape inválido \\{}", c)))
+                        }
+                        None => {
+                            text.push('\\');
+                            text.push(c);
+                        }
+                    }
+                    continue;
+                }
+            };
+            pieces.push(Piece::Text(std::mem::take(&mut text)));
+            pieces.push(Piece::Group(group));
+        }
+        pieces.push(Piece::Text(text));
+        Ok(pieces)
+    }
+
+    /// The number of a group, given by number or by name.
+    fn group_index(&self, function: &str, group: &Object) -> Result<usize> {
+        let index = match group {
+            Object::Primitive(Primitive::Integer(n)) => {
+                Some(*n).filter(|&n| n >= 0 && n as usize <= self.compiled.groups)
+            }
+            Object::Primitive(Primitive::Str(name)) => self
+                .compiled
+                .names
+                .iter()
+                .find(|(known, _)| known == name)
+                .map(|&(_, index)| index as i64),
+            other => return Err(type_error(function, "um inteiro ou um texto", other)),
+        };
+        match index {
+            Some(index) => Ok(index as usize),
+            None => Err(Error::InvalidValue(format!(
+                "grupo inexistente: {}",
+                group.repr()
+            ))),
+        }
+    }
+
+    /// The attributes and methods of the pattern, as `p.busque` finds them.
+    pub(crate) fn attribute(&self, name: &str) -> Option<Object> {
+        let pattern = self.clone();
+        let method = match name {
+            "padrao" => return Some(self.source().into()),
+            "grupos" => return Some((self.groups() as i64).into()),
+            "nomes" => {
+                let names = self.compiled.names.iter().map(|(name, index)| {
+                    Object::Tuple(vec![name.as_str().into(), (*index as i64).into()])
+                });
+                return Some(Object::Tuple(names.collect()));
+            }
+            "busque" => NativeFunction::new(name, move |args| {
+                let text = text("busque", argument(&args, 0)?)?;
+                Ok(pattern
+                    .search(text, false)?
+                    .map_or(Object::Unit, Object::from))
+            })
+            .params(&["texto"])
+            .doc("A primeira correspondência no texto, ou Vazio quando não há."),
+            "corresponde" => NativeFunction::new(name, move |args| {
+                let text = text("corresponde", argument(&args, 0)?)?;
+                Ok(pattern
+                    .search(text, true)?
+                    .map_or(Object::Unit, Object::from))
+            })
+            .params(&["texto"])
+            .doc("A correspondência no início do texto, ou Vazio quando não há."),
+            "encontre_todos" => NativeFunction::new(name, move |args| {
+                let text = text("encontre_todos", argument(&args, 0)?)?;
+                Ok(Object::Tuple(pattern.find_all(text)?))
+            })
+            .params(&["texto"])
+            .doc(
+                "Uma tupla com os textos de todas as correspondências; com grupos, \
+                 os textos dos grupos.",
+            ),
+            "substitua" => NativeFunction::new(name, move |args| {
+                let text = text("substitua", argument(&args, 1)?)?;
+                let count = count("substitua", args.get(2))?;
+                Ok(pattern.substitute(argument(&args, 0)?, text, count)?.into())
+            })
+            .params(&["substituto", "texto", "vezes"])
+            .arity(Arity::Range(2, 3))
+            .doc(
+                "O texto com as correspondências trocadas pelo substituto, onde \
+                 \\1 e \\g<nome> são os grupos; com vezes, só as primeiras.",
+            ),
+            "divida" => NativeFunction::new(name, move |args| {
+                let text = text("divida", argument(&args, 0)?)?;
+                let count = count("divida", args.get(1))?;
+                Ok(Object::Tuple(pattern.split(text, count)?))
+            })
+            .params(&["texto", "vezes"])
+            .arity(Arity::Range(1, 2))
+            .doc(
+                "Uma tupla com as partes do texto entre as correspondências e os \
+                 grupos de cada uma; com vezes, divide só nas primeiras.",
+            ),
+            _ => return None,
+        };
+        Some(method.into())
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<padrão '{}'>", self.source())
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source() == other.source() && self.flags() == other.flags()
+    }
+}
+
+impl Match {
+    pub fn span(&self, group: usize) -> Option<(usize, usize)> {
+        match (self.spans.get(2 * group)?, self.spans.get(2 * group + 1)?) {
+            (Some(start), Some(end)) => Some((*start, *end)),
+            _ => None,
+        }
+    }
+
+    /// The text of the group, or `None` when it did not take part.
+    pub fn group(&self, group: usize) -> Option<String> {
+        let (start, end) = self.span(group)?;
+        Some(self.text[start..end].iter().collect())
+    }
+
+    /// The attributes and methods of the match, as `m.grupo` finds them.
+    pub(crate) fn attribute(&self, name: &str) -> Option<Object> {
+        let found = self.clone();
+        let method = match name {
+            "texto" => return Some(self.text.iter().collect::<String>().into()),
+            "padrao" => return Some(self.pattern.clone().into()),
+            "grupo" => NativeFunction::new(name, move |args| {
+                let groups = match args.positional() {
+                    [] => return Ok(found.group_object(0)),
+                    groups => groups
+                        .iter()
+                        .map(|group| Ok(found.group_object(found.index("grupo", group)?)))
+                        .collect::<Result<Vec<_>>>()?,
+                };
+                match groups.as_slice() {
+                    [group] => Ok(group.clone()),
+                    _ => Ok(Object::Tuple(groups)),
+                }
+            })
+            .rest("grupos")
+            .doc(
+                "O texto do grupo, pelo número ou pelo nome, ou Vazio quando ele não \
+                 participou; sem grupo, toda a correspondência, e com vários, uma tupla.",
+            ),
+            "grupos" => NativeFunction::new(name, move |args| {
+                let absent = args.get(0).cloned().unwrap_or(Object::Unit);
+                let groups = (1..=found.pattern.groups()).map(|index| {
+                    found
+                        .group(index)
+                        .map_or_else(|| absent.clone(), Object::from)
+                });
+                Ok(Object::Tuple(groups.collect()))
+            })
+            .params(&["ausente"])
+            .arity(Arity::Range(0, 1))
+            .doc(
+                "Uma tupla com os textos dos grupos, com ausente, ou Vazio, para os \
+                 que não participaram.",
+            ),
+            "nomeados" => NativeFunction::new(name, move |args| {
+                let absent = args.get(0).cloned().unwrap_or(Object::Unit);
+                let pairs = found.pattern.compiled.names.iter().map(|(name, index)| {
+                    let value = found
+                        .group(*index)
+                        .map_or_else(|| absent.clone(), Object::from);
+                    Object::Tuple(vec![name.as_str().into(), value])
+                });
+                Ok(Object::Tuple(pairs.collect()))
+            })
+            .params(&["ausente"])
+            .arity(Arity::Range(0, 1))
+            .doc("Uma tupla de pares (nome, texto) com os grupos nomeados."),
+            "inicio" => self.position_method(name, |(start, _)| start.into()),
+            "fim" => self.position_method(name, |(_, end)| end.into()),
+            "intervalo" => self.position_method(name, |(start, end)| {
+                Object::Tuple(vec![start.into(), end.into()])
+            }),
+            _ => return None,
+        };
+        Some(method.into())
+    }
+
+    /// `inicio`, `fim` and `intervalo`, which are -1 for a group that did
+    /// not take part.
+    fn position_method(&self, name: &str, result: fn((i64, i64)) -> Object) -> NativeFunction {
+        let found = self.clone();
+        let function = name.to_string();
+        NativeFunction::new(name, move |args| {
+            let group = match args.get(0) {
+                Some(group) => found.index(&function, group)?,
+                None => 0,
+            };
+            let span = found
+                .span(group)
+                .map_or((-1, -1), |(start, end)| (start as i64, end as i64));
+            Ok(result(span))
+        })
+        .params(&["grupo"])
+        .arity(Arity::Range(0, 1))
+        .doc(match name {
+            "inicio" => "Onde o grupo, ou toda a correspondência, começa no texto.",
+            "fim" => "Onde o grupo, ou toda a correspondência, termina no texto.",
+            _ => "O par (inicio, fim) do grupo, ou de toda a correspondência.",
+        })
+    }
+
+    fn index(&self, function: &str, group: &Object) -> Result<usize> {
+        self.pattern.group_index(function, group)
+    }
+
+    fn group_object(&self, group: usize) -> Object {
+        self.group(group).map_or(Object::Unit, Object::from)
+    }
+}
+
+impl fmt::Debug for Match {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (start, end) = self.span(0).expect("the match has a span");
+        write!(
+            f,
+            "<correspondência ({}, {}) '{}'>",
+            start,
+            end,
+            self.group(0).unwrap_or_default()
+        )
+    }
+}
+
+impl PartialEq for Match {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.spans, &other.spans)
+    }
+}
+
+fn text<'a>(function: &str, value: &'a Object) -> Result<&'a str> {
+    match value {
+        Object::Primitive(Primitive::Str(s)) => Ok(s),
+        other => Err(type_error(function, "um texto", other)),
+    }
+}
+
+/// `vezes`, where 0 or none means all.
+fn count(function: &str, value: Option<&Object>) -> Result<usize> {
+    match value {
+        None => Ok(0),
+        Some(Object::Primitive(Primitive::Integer(n))) if *n >= 0 => Ok(*n as usize),
+        Some(Object::Primitive(Primitive::Integer(_))) => {
+            Err(Error::InvalidValue("vezes não pode ser negativo".into()))
+        }
+        Some(other) => Err(type_error(function, "um inteiro", other)),
+    }
+}
+
+enum Piece {
+    Text(String),
+    Group(usize),
+}
+
+/// `\n` and the like, the escapes of characters that patterns and
+/// substitutes share.
+fn simple_escape(c: char) -> Option<char> {
+    match c {
+        'n' => Some('\n'),
+        't' => Some('\t'),
+        'r' => Some('\r'),
+        'f' => Some('\x0c'),
+        'v' => Some('\x0b'),
+        'a' => Some('\x07'),
+        '\\' => Some('\\'),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Set(Set),
+    Assert(Assertion),
+    Group(Option<usize>, Box<Node>),
+    Look {
+        behind: bool,
+        negate: bool,
+        node: Box<Node>,
+    },
+    Backref(usize),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Assertion {
+    Start,
+    End,
+    TextStart,
+    TextEnd,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+/// A `[...]` or one of `\d`, `\w` and `\s`.
+#[derive(Debug, Clone)]
+struct Set {
+    negated: bool,
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Item {
+    Range(char, char),
+    // each negated when true, as \D
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl Item {
+    fn contains(self, c: char) -> bool {
+        match self {
+            Item::Range(low, high) => low <= c && c <= high,
+            // only 0 to 9, so what it matches can always be read by `inteiro`
+            Item::Digit(negated) => c.is_ascii_digit() != negated,
+            Item::Word(negated) => is_word(c) != negated,
+            Item::Space(negated) => c.is_whitespace() != negated,
+        }
+    }
+}
+
+impl Set {
+    fn contains(&self, c: char, ignore_case: bool) -> bool {
+        let found = |c| self.items.iter().any(|item| item.contains(c));
+        let found = found(c) || (ignore_case && (found(lower(c)) || found(upper(c))));
+        found != self.negated
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn lower(c: char) -> char {
+    single(c.to_lowercase()).unwrap_or(c)
+}
+
+fn upper(c: char) -> char {
+    single(c.to_uppercase()).unwrap_or(c)
+}
+
+/// The character when the case mapping gives just one, as it does for all
+/// but a few, such as 'ß'.
+fn single(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    let c = chars.next()?;
+    match chars.next() {
+        None => Some(c),
+        Some(_) => None,
+    }
+}
+
+fn same(a: char, b: char, ignore_case: bool) -> bool {
+    a == b || (ignore_case && lower(a) == lower(b))
+}
+
+/// How many characters the node always matches, or `None` when it varies,
+/// which a lookbehind does not allow.
+fn width(node: &Node) -> Option<usize> {
+    match node {
+        Node::Empty | Node::Assert(_) | Node::Look { .. } => Some(0),
+        Node::Char(_) | Node::Any | Node::Set(_) => Some(1),
+        Node::Group(_, node) => width(node),
+        Node::Backref(_) => None,
+        Node::Repeat { node, min, max, .. } if *max == Some(*min) => width(node)?.checked_mul(*min),
+        Node::Repeat { .. } => None,
+        Node::Concat(nodes) => nodes
+            .iter()
+            .try_fold(0, |sum: usize, node| sum.checked_add(width(node)?)),
+        Node::Alternate(nodes) => {
+            let first = width(&nodes[0])?;
+            if nodes[1..].iter().all(|node| width(node) == Some(first)) {
+                Some(first)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// A recursive descent parser over the characters, so positions in errors
+/// are counted as a reader would.
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    flags: Flags,
+    // where the `(?ims)` at the start of the pattern end
+    flags_end: usize,
+    // whether each group has been closed, for backreferences
+    closed: Vec<bool>,
+    names: Vec<(String, usize)>,
+}
+
+impl Parser {
+    fn error(&self, message: &str, position: usize) -> Error {
+        Error::InvalidValue(format!(
+            "expressão regular inválida: {} na posição {}",
+            message, position
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn looking_at(&self, text: &str) -> bool {
+        let end = self.position + text.chars().count();
+        self.chars
+            .get(self.position..end)
+            .is_some_and(|chars| chars.iter().copied().eq(text.chars()))
+    }
+
+    fn alternation(&mut self) -> Result<Node> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        match branches.len() {
+            1 => Ok(branches.pop().unwrap()),
+            _ => Ok(Node::Alternate(branches)),
+        }
+    }
+
+    fn concat(&mut self) -> Result<Node> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        match nodes.len() {
+            0 => Ok(Node::Empty),
+            1 => Ok(nodes.pop().unwrap()),
+            _ => Ok(Node::Concat(nodes)),
+        }
+    }
+
+    fn quantified(&mut self, node: Node) -> Result<Node> {
+        let position = self.position;
+        let (min, max) = match self.quantifier()? {
+            Some(quantifier) => quantifier,
+            None => return Ok(node),
+        };
+        if let Node::Empty | Node::Assert(_) = node {
+            return Err(self.error("nada para repetir", position));
+        }
+        if max.is_some_and(|max| min > max) {
+            return Err(self.error("repetição com mínimo maior que o máximo", position));
+        }
+        if min.max(max.unwrap_or(0)) > MAX_PROGRAM {
+            return Err(self.error("repetição grande demais", position));
+        }
+        let greedy = !self.eat('?');
+        let next = self.position;
+        if self.quantifier()?.is_some() {
+            return Err(self.error("repetição múltipla", next));
+        }
+        Ok(Node::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    fn quantifier(&mut self) -> Result<Option<(usize, Option<usize>)>> {
+        let quantifier = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => return self.braces(),
+            _ => return Ok(None),
+        };
+        self.position += 1;
+        Ok(Some(quantifier))
+    }
+
+    /// `{n}`, `{n,}`, `{,m}` or `{n,m}`; anything else is a literal `{`.
+    fn braces(&mut self) -> Result<Option<(usize, Option<usize>)>> {
+        let start = self.position;
+        let end = match self.chars[start..].iter().position(|&c| c == '}') {
+            Some(length) => start + length,
+            None => return Ok(None),
+        };
+        let inside: String = self.chars[start + 1..end].iter().collect();
+        let (low, high) = match inside.find(',') {
+            Some(comma) => (&inside[..comma], Some(&inside[comma + 1..])),
+            None => (&inside[..], None),
+        };
+        let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        if !digits(low) || !high.is_none_or(digits) || (low.is_empty() && high.is_none()) {
+            return Ok(None);
+        }
+        let number = |s: &str| {
+            s.parse::<usize>()
+                .map_err(|_| self.error("repetição grande demais", start))
+        };
+        let min = if low.is_empty() { 0 } else { number(low)? };
+        let max = match high {
+            None => Some(min),
+            Some("") => None,
+            Some(high) => Some(number(high)?),
+        };
+        self.position = end + 1;
+        Ok(Some((min, max)))
+    }
+
+    fn atom(&mut self) -> Result<Node> {
+        let position = self.position;
+        let c = self.peek().expect("an atom starts at a character");
+        self.position += 1;
+        let node = match c {
+            '(' => return self.group(position),
+            '[' => Node::Set(self.set(position)?),
+            '.' => Node::Any,
+            '^' => Node::Assert(Assertion::Start),
+            '$' => Node::Assert(Assertion::End),
+            '\\' => return self.escape(position),
+            '*' | '+' | '?' => return Err(self.error("nada para repetir", position)),
+            '{' => {
+                self.position = position;
+                if self.braces()?.is_some() {
+                    return Err(self.error("nada para repetir", position));
+                }
+                self.position = position + 1;
+                Node::Char('{')
+            }
+            c => Node::Char(c),
+        };
+        Ok(node)
+    }
+
+    fn group(&mut self, start: usize) -> Result<Node> {
+        if !self.eat('?') {
+            return self.capture(start, None);
+        }
+        if self.eat(':') {
+            return Ok(Node::Group(None, Box::new(self.group_body(start)?)));
+        }
+        if self.looking_at("P<")
+            || (self.looking_at("<") && !self.looking_at("<=") && !self.looking_at("<!"))
+        {
+            self.eat('P');
+            self.eat('<');
+            let position = self.position;
+            let name = self.name('>')?;
+            if self.names.iter().any(|(known, _)| *known == name) {
+                let message = format!("nome de grupo repetido '{}'", name);
+                return Err(self.error(&message, position));
+            }
+            return self.capture(start, Some(name));
+        }
+        if self.looking_at("P=") {
+            self.position += 2;
+            let position = self.position;
+            let name = self.name(')')?;
+            return match self.names.iter().find(|(known, _)| *known == name) {
+                Some(&(_, index)) => self.reference(index, position),
+                None => {
+                    let message = format!("grupo desconhecido '{}'", name);
+                    Err(self.error(&message, position))
+                }
+            };
+        }
+        let lookarounds = [
+            ("=", false, false),
+            ("!", false, true),
+            ("<=", true, false),
+            ("<!", true, true),
+        ];
+        for &(prefix, behind, negate) in &lookarounds {
+            if self.looking_at(prefix) {
+                self.position += prefix.len();
+                let node = self.group_body(start)?;
+                if behind && width(&node).is_none() {
+                    return Err(self.error("lookbehind requer um padrão de tamanho fixo", start));
+                }
+                return Ok(Node::Look {
+                    behind,
+                    negate,
+                    node: Box::new(node),
+                });
+            }
+        }
+        if self.eat('#') {
+            return match self.chars[self.position..].iter().position(|&c| c == ')') {
+                Some(length) => {
+                    self.position += length + 1;
+                    Ok(Node::Empty)
+                }
+                None => Err(self.error("comentário não terminado", start)),
+            };
+        }
+        self.inline_flags(start)
+    }
+
+    fn capture(&mut self, start: usize, name: Option<String>) -> Result<Node> {
+        self.closed.push(false);
+        let index = self.closed.len();
+        if let Some(name) = name {
+            self.names.push((name, index));
+        }
+        let node = self.group_body(start)?;
+        self.closed[index - 1] = true;
+        Ok(Node::Group(Some(index), Box::new(node)))
+    }
+
+    fn group_body(&mut self, start: usize) -> Result<Node> {
+        let node = self.alternation()?;
+        if !self.eat(')') {
+            return Err(self.error("parêntese não fechado", start));
+        }
+        Ok(node)
+    }
+
+    /// `(?i)`, `(?m)`, `(?s)` or several of them, which apply to the whole
+    /// pattern and so must come first.
+    fn inline_flags(&mut self, start: usize) -> Result<Node> {
+        let mut flags = self.flags;
+        loop {
+            match self.peek() {
+                Some('i') => flags.ignore_case = true,
+                Some('m') => flags.multiline = true,
+                Some('s') => flags.dot_all = true,
+                Some(')') if self.position > start + 2 => break,
+                Some(c) if c.is_alphabetic() => {
+                    let message = format!("opção desconhecida '{}'", c);
+                    return Err(self.error(&message, self.position));
+                }
+                _ => return Err(self.error("sintaxe de grupo desconhecida", start)),
+            }
+            self.position += 1;
+        }
+        if start != self.flags_end {
+            return Err(self.error("as opções devem ficar no início do padrão", start));
+        }
+        self.position += 1;
+        self.flags = flags;
+        self.flags_end = self.position;
+        Ok(Node::Empty)
+    }
+
+    fn name(&mut self, end: char) -> Result<String> {
+        let start = self.position;
+        let length = match self.chars[start..].iter().position(|&c| c == end) {
+            Some(length) => length,
+            None => return Err(self.error("nome de grupo não terminado", start)),
+        };
+        let name: String = self.chars[start..start + length].iter().collect();
+        let mut chars = name.chars();
+        let valid =
+            chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(is_word);
+        if !valid {
+            let message = format!("nome de grupo inválido '{}'", name);
+            return Err(self.error(&message, start));
+        }
+        self.position = start + length + 1;
+        Ok(name)
+    }
+
+    fn reference(&self, index: usize, position: usize) -> Result<Node> {
+        match self.closed.get(index.wrapping_sub(1)) {
+            Some(true) => Ok(Node::Backref(index)),
+            Some(false) => Err(self.error("referência a um grupo aberto", position)),
+            None => Err(self.error("referência a um grupo inexistente", position)),
+        }
+    }
+
+    fn escape(&mut self, start: usize) -> Result<Node> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Err(self.error("'\\' no fim do padrão", start)),
+        };
+        if let Some(item) = shorthand(c) {
+            self.position += 1;
+            return Ok(Node::Set(Set {
+                negated: false,
+                items: vec![item],
+            }));
+        }
+        let assertion = match c {
+            'b' => Assertion::WordBoundary,
+            'B' => Assertion::NotWordBoundary,
+            'A' => Assertion::TextStart,
+            'Z' => Assertion::TextEnd,
+            '1'..='9' => {
+                let mut index = 0;
+                for _ in 0..2 {
+                    match self.peek().and_then(|c| c.to_digit(10)) {
+                        Some(digit) => index = index * 10 + digit as usize,
+                        None => break,
+                    }
+                    self.position += 1;
+                }
+                return self.reference(index, start);
+            }
+            _ => return Ok(Node::Char(self.escaped_char(start)?)),
+        };
+        self.position += 1;
+        Ok(Node::Assert(assertion))
+    }
+
+    /// The character an escape stands for, reading from after the `\`.
+    fn escaped_char(&mut self, start: usize) -> Result<char> {
+        let c = self.chars[self.position];
+        self.position += 1;
+        if let Some(c) = simple_escape(c) {
+            return Ok(c);
+        }
+        match c {
+            '0' => {
+                let mut code = 0;
+                for _ in 0..2 {
+                    match self.peek().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => code = code * 8 + digit,
+                        None => break,
+                    }
+                    self.position += 1;
+                }
+                Ok(std::char::from_u32(code).expect("an octal escape is below 0o100"))
+            }
+            'x' => self.hex(2, start),
+            'u' => self.hex(4, start),
+            'U' => self.hex(8, start),
+            c if c.is_ascii_alphanumeric() => {
+                let message = format!("escape inválido \\{}", c);
+                Err(self.error(&message, start))
+            }
+            c => Ok(c),
+        }
+    }
+
+    fn hex(&mut self, digits: usize, start: usize) -> Result<char> {
+        let end = self.position + digits;
+        let code = self
+            .chars
+            .get(self.position..end)
+            .filter(|chars| chars.iter().all(char::is_ascii_hexdigit))
+            .and_then(|chars| u32::from_str_radix(&chars.iter().collect::<String>(), 16).ok())
+            .and_then(std::char::from_u32);
+        match code {
+            Some(c) => {
+                self.position = end;
+                Ok(c)
+            }
+            None => Err(self.error("escape hexadecimal inválido", start)),
+        }
+    }
+
+    fn set(&mut self, start: usize) -> Result<Set> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        loop {
+            let position = self.position;
+            let c = match self.peek() {
+                Some(']') if !items.is_empty() => {
+                    self.position += 1;
+                    return Ok(Set { negated, items });
+                }
+                Some(c) => c,
+                None => return Err(self.error("conjunto não terminado", start)),
+            };
+            self.position += 1;
+            let low = self.set_item(c, position)?;
+            let range = self.peek() == Some('-')
+                && self.chars.get(self.position + 1).is_some_and(|&c| c != ']');
+            if !range {
+                items.push(low);
+                continue;
+            }
+            self.position += 1;
+            let c = self.chars[self.position];
+            self.position += 1;
+            match (low, self.set_item(c, position)?) {
+                (Item::Range(low, _), Item::Range(high, _)) if low <= high => {
+                    items.push(Item::Range(low, high))
+                }
+                _ => return Err(self.error("intervalo inválido", position)),
+            }
+        }
+    }
+
+    /// A character of a set, as a range of one, or a `\d`, `\w` or `\s`.
+    fn set_item(&mut self, c: char, position: usize) -> Result<Item> {
+        if c != '\\' {
+            return Ok(Item::Range(c, c));
+        }
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Err(self.error("'\\' no fim do padrão", position)),
+        };
+        if let Some(item) = shorthand(c) {
+            self.position += 1;
+            return Ok(item);
+        }
+        let c = match c {
+            'b' => {
+                self.position += 1;
+                '\x08'
+            }
+            _ => self.escaped_char(position)?,
+        };
+        Ok(Item::Range(c, c))
+    }
+}
+
+fn shorthand(c: char) -> Option<Item> {
+    match c {
+        'd' => Some(Item::Digit(false)),
+        'D' => Some(Item::Digit(true)),
+        'w' => Some(Item::Word(false)),
+        'W' => Some(Item::Word(true)),
+        's' => Some(Item::Space(false)),
+        'S' => Some(Item::Space(true)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Set(Set),
+    Assert(Assertion),
+    Backref(usize),
+    /// Goes on at the first, and at the second when that fails.
+    Split(usize, usize),
+    Jump(usize),
+    /// Keeps the position in a slot: where a group starts or ends, or where
+    /// an iteration of a loop starts.
+    Save(usize),
+    /// Ends an iteration of a loop, which only starts another when this one
+    /// matched something, so an empty one cannot repeat forever.
+    Loop {
+        slot: usize,
+        start: usize,
+        greedy: bool,
+    },
+    /// Runs the lookaround that follows, up to its `Succeed`, from
+    /// `behind` characters back, and then goes on at `end`.
+    Look {
+        behind: usize,
+        negate: bool,
+        end: usize,
+    },
+    Succeed,
+    Match,
+}
+
+struct Program {
+    insts: Vec<Inst>,
+    flags: Flags,
+    // two for each group and the whole match, then one for each loop
+    slots: usize,
+}
+
+struct Compiler {
+    insts: Vec<Inst>,
+    slots: usize,
+}
+
+impl Compiler {
+    fn compile(node: &Node, groups: usize, flags: Flags) -> Result<Program> {
+        let mut compiler = Compiler {
+            insts: Vec::new(),
+            slots: 2 * (groups + 1),
+        };
+        compiler.node(node)?;
+        compiler.emit(Inst::Match)?;
+        Ok(Program {
+            insts: compiler.insts,
+            flags,
+            slots: compiler.slots,
+        })
+    }
+
+    fn emit(&mut self, inst: Inst) -> Result<usize> {
+        if self.insts.len() >= MAX_PROGRAM {
+            return Err(Error::InvalidValue(
+                "expressão regular grande demais".into(),
+            ));
+        }
+        self.insts.push(inst);
+        Ok(self.insts.len() - 1)
+    }
+
+    fn split(first: usize, second: usize, greedy: bool) -> Inst {
+        if greedy {
+            Inst::Split(first, second)
+        } else {
+            Inst::Split(second, first)
+        }
+    }
+
+    fn node(&mut self, node: &Node) -> Result<()> {
+        match node {
+            Node::Empty => {}
+            Node::Char(c) => {
+                self.emit(Inst::Char(*c))?;
+            }
+            Node::Any => {
+                self.emit(Inst::Any)?;
+            }
+            Node::Set(set) => {
+                self.emit(Inst::Set(set.clone()))?;
+            }
+            Node::Assert(assertion) => {
+                self.emit(Inst::Assert(*assertion))?;
+            }
+            Node::Backref(index) => {
+                self.emit(Inst::Backref(*index))?;
+            }
+            Node::Group(None, node) => self.node(node)?,
+            Node::Group(Some(index), node) => {
+                self.emit(Inst::Save(2 * index))?;
+                self.node(node)?;
+                self.emit(Inst::Save(2 * index + 1))?;
+            }
+            Node::Look {
+                behind,
+                negate,
+                node,
+            } => {
+                let look = self.emit(Inst::Succeed)?;
+                self.node(node)?;
+                self.emit(Inst::Succeed)?;
+                self.insts[look] = Inst::Look {
+                    behind: if *behind { width(node).unwrap_or(0) } else { 0 },
+                    negate: *negate,
+                    end: self.insts.len(),
+                };
+            }
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.node(node)?;
+                }
+            }
+            Node::Alternate(nodes) => {
+                let (last, rest) = nodes.split_last().expect("an alternation has branches");
+                let mut jumps = Vec::new();
+                for node in rest {
+                    let split = self.emit(Inst::Jump(0))?;
+                    self.node(node)?;
+                    jumps.push(self.emit(Inst::Jump(0))?);
+                    self.insts[split] = Inst::Split(split + 1, self.insts.len());
+                }
+                self.node(last)?;
+                for jump in jumps {
+                    self.insts[jump] = Inst::Jump(self.insts.len());
+                }
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => {
+                for _ in 0..*min {
+                    self.node(node)?;
+                }
+                match max {
+                    None => self.star(node, *greedy)?,
+                    Some(max) => {
+                        // x{2,4} is xx(?:x(?:x)?)?
+                        let mut splits = Vec::new();
+                        for _ in *min..*max {
+                            splits.push(self.emit(Inst::Jump(0))?);
+                            self.node(node)?;
+                        }
+                        for split in splits {
+                            self.insts[split] = Self::split(split + 1, self.insts.len(), *greedy);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn star(&mut self, node: &Node, greedy: bool) -> Result<()> {
+        let split = self.emit(Inst::Jump(0))?;
+        let slot = self.slots;
+        self.slots += 1;
+        let start = self.emit(Inst::Save(slot))?;
+        self.node(node)?;
+        self.emit(Inst::Loop {
+            slot,
+            start,
+            greedy,
+        })?;
+        self.insts[split] = Self::split(start, self.insts.len(), greedy);
+        Ok(())
+    }
+}
+
+enum Backtrack {
+    Branch(usize, usize),
+    Restore(usize, Option<usize>),
+}
+
+struct Matcher<'a> {
+    program: &'a Program,
+    text: &'a [char],
+    // where an empty match is not accepted, after another one there
+    forbidden: Option<usize>,
+    // how many more steps the search may take; at 0 every run fails
+    steps: Cell<usize>,
+}
+
+impl Matcher<'_> {
+    /// Runs the program from `pc` at `position` and returns where the match
+    /// ends, leaving the groups in `slots`.
+    fn run(
+        &self,
+        pc: usize,
+        position: usize,
+        start: usize,
+        slots: &mut [Option<usize>],
+    ) -> Option<usize> {
+        let flags = self.program.flags;
+        let text = self.text;
+        let mut stack = Vec::new();
+        let (mut pc, mut position) = (pc, position);
+        loop {
+            match self.steps.get() {
+                0 => return None,
+                steps => self.steps.set(steps - 1),
+            }
+            let matched = match &self.program.insts[pc] {
+                Inst::Char(c) => {
+                    let matched =
+                        position < text.len() && same(text[position], *c, flags.ignore_case);
+                    position += 1;
+                    matched
+                }
+                Inst::Any => {
+                    let matched =
+                        position < text.len() && (flags.dot_all || text[position] != '\n');
+                    position += 1;
+                    matched
+                }
+                Inst::Set(set) => {
+                    let matched =
+                        position < text.len() && set.contains(text[position], flags.ignore_case);
+                    position += 1;
+                    matched
+                }
+                Inst::Assert(assertion) => self.assert(*assertion, position),
+                Inst::Backref(index) => match (slots[2 * index], slots[2 * index + 1]) {
+                    (Some(group_start), Some(group_end)) => {
+                        let group = &text[group_start..group_end];
+                        let end = position + group.len();
+                        let matched = end <= text.len()
+                            && group
+                                .iter()
+                                .zip(&text[position..end])
+                                .all(|(&a, &b)| same(a, b, flags.ignore_case));
+                        position = end;
+                        matched
+                    }
+                    _ => false,
+                },
+                Inst::Split(first, second) => {
+                    stack.push(Backtrack::Branch(*second, position));
+                    pc = *first;
+                    continue;
+                }
+                Inst::Jump(target) => {
+                    pc = *target;
+                    continue;
+                }
+                Inst::Save(slot) => {
+                    stack.push(Backtrack::Restore(*slot, slots[*slot]));
+                    slots[*slot] = Some(position);
+                    true
+                }
+                Inst::Loop {
+                    slot,
+                    start,
+                    greedy,
+                } => {
+                    if slots[*slot] != Some(position) {
+                        let (next, later) = if *greedy {
+                            (*start, pc + 1)
+                        } else {
+                            (pc + 1, *start)
+                        };
+                        stack.push(Backtrack::Branch(later, position));
+                        pc = next;
+                        continue;
+                    }
+                    true
+                }
+                Inst::Look {
+                    behind,
+                    negate,
+                    end,
+                } => {
+                    let mut inner = slots.to_vec();
+                    let found = position
+                        .checked_sub(*behind)
+                        .and_then(|from| self.run(pc + 1, from, start, &mut inner))
+                        .is_some();
+                    if found && !negate {
+                        // the groups of a lookahead that matched are kept
+                        for (slot, value) in inner.into_iter().enumerate() {
+                            if slots[slot] != value {
+                                stack.push(Backtrack::Restore(slot, slots[slot]));
+                                slots[slot] = value;
+                            }
+                        }
+                    }
+                    if found != *negate {
+                        pc = *end;
+                        continue;
+                    }
+                    false
+                }
+                Inst::Succeed => return Some(position),
+                Inst::Match => {
+                    if position == start && self.forbidden == Some(start) {
+                        false
+                    } else {
+                        return Some(position);
+                    }
+                }
+            };
+            if matched {
+                pc += 1;
+                continue;
+            }
+            loop {
+                match stack.pop()? {
+                    Backtrack::Restore(slot, value) => slots[slot] = value,
+                    Backtrack::Branch(next, from) => {
+                        pc = next;
+                        position = from;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    fn assert(&self, assertion: Assertion, position: usize) -> bool {
+        let text = self.text;
+        let multiline = self.program.flags.multiline;
+        let before = position.checked_sub(1).map(|i| text[i]);
+        let after = text.get(position).copied();
+        match assertion {
+            Assertion::Start => position == 0 || (multiline && before == Some('\n')),
+            // as in Python, also before a last '\n'
+            Assertion::End => {
+                after.is_none()
+                    || (after == Some('\n') && (multiline || position + 1 == text.len()))
+            }
+            Assertion::TextStart => position == 0,
+            Assertion::TextEnd => after.is_none(),
+            Assertion::WordBoundary => before.is_some_and(is_word) != after.is_some_and(is_word),
+            Assertion::NotWordBoundary => before.is_some_and(is_word) == after.is_some_and(is_word),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(pattern: &str, text: &str) -> Option<String> {
+        let pattern = Pattern::new(pattern, Flags::default()).unwrap();
+        pattern.search(text, false).unwrap()?.group(0)
+    }
+
+    #[test]
+    fn repetitions() {
+        assert_eq!(Some("aaa".into()), found("a{2,3}", "aaaa"));
+        assert_eq!(Some("aa".into()), found("a{2,3}?", "aaaa"));
+        assert_eq!(Some("aaaa".into()), found("a{2,}", "aaaa"));
+        assert_eq!(Some("a{,x}".into()), found("a{,x}", "a{,x}"));
+        assert_eq!(Some("{1}".into()), found("\\{1}", "{1}"));
+        // empty iterations end the loop instead of repeating forever
+        assert_eq!(None, found("(a*)*b", "aaac"));
+        assert_eq!(Some("a".into()), found("(?:a?)+?", "aa"));
+    }
+
+    #[test]
+    fn sets_and_escapes() {
+        assert_eq!(Some("-b]".into()), found("[]a-c-]+", "x-b]"));
+        assert_eq!(Some("é1_".into()), found("\\w+", " é1_ "));
+        assert_eq!(Some("x".into()), found("[^\\d\\s]", "1 x"));
+        assert_eq!(Some("\t".into()), found("\\x09|\\u0009", "a\tb"));
+        let pattern = Pattern::new(
+            "[a-c]+",
+            Flags {
+                ignore_case: true,
+                ..Flags::default()
+            },
+        );
+        assert_eq!(
+            Some("AbC".into()),
+            pattern
+                .unwrap()
+                .search("xAbCd", false)
+                .unwrap()
+                .unwrap()
+                .group(0)
+        );
+    }
+
+    #[test]
+    fn lookarounds() {
+        assert_eq!(Some("b".into()), found("(?<=a)b", "cbab"));
+        assert_eq!(Some("b".into()), found("(?<!a)b", "abb"));
+        assert_eq!(Some("a".into()), found("^(?<!x)a", "a"));
+        assert_eq!(Some("q".into()), found("q(?!u)", "quqa"));
+        // the groups of a lookahead are kept
+        let pattern = Pattern::new("(?=(\\d+))\\w", Flags::default()).unwrap();
+        let found = pattern.search("x12", false).unwrap().unwrap();
+        assert_eq!(
+            (Some("1".into()), Some("12".into())),
+            (found.group(0), found.group(1))
+        );
+    }
+
+    #[test]
+    fn programs_have_a_limit() {
+        assert!(Pattern::new("(abc){1000}", Flags::default()).is_ok());
+        assert!(Pattern::new("((abc){1000}){1000}", Flags::default()).is_err());
+    }
+}
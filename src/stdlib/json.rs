@@ -0,0 +1,205 @@
+//! `json`, reading and writing values as JSON text.
+//!
+//! Arrays are tuples. The language has no dictionaries yet, so reading an
+//! object is an error rather than a guess at another shape for its data, and
+//! nothing is written as an object.
+
+use crate::builtin::{argument, type_error};
+use crate::error::Error;
+use crate::native::{Args, Module, NativeFunction};
+use crate::object::Object;
+use crate::primitive::Primitive::*;
+
+use num_bigint::BigInt;
+use serde::Serialize;
+use serde_json::ser::{Formatter, PrettyFormatter, Serializer};
+use serde_json::{Number, Value};
+
+use std::io;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+pub(super) fn module() -> Module {
+    let module = Module::new("json");
+    module.register(
+        NativeFunction::new("carregue", load)
+            .params(&["texto"])
+            .doc(
+                "O valor escrito em JSON no texto. Listas viram tuplas; objetos \
+                 ainda não são aceitos.",
+            ),
+    );
+    module.register(
+        NativeFunction::new("converta", dump)
+            .params(&["valor"])
+            .keywords(&["indente"])
+            .doc(
+                "O valor escrito em JSON, numa linha só ou, com `indente`, um \
+                 elemento por linha com essa quantidade de espaços por nível.",
+            ),
+    );
+    module
+}
+
+fn load(args: Args) -> Result<Object> {
+    let text = match argument(&args, 0)? {
+        Object::Primitive(Str(text)) => text,
+        other => return Err(type_error("carregue", "um texto", other)),
+    };
+    let value: Value = serde_json::from_str(text).map_err(invalid)?;
+    from_json(value)
+}
+
+/// `JSON inválido: esperava um valor na linha 1, coluna 5`, with serde's
+/// messages said in Portuguese.
+fn invalid(error: serde_json::Error) -> Error {
+    let text = error.to_string();
+    let message = match text
+        .rsplit_once(" at line ")
+        .map_or(&text[..], |(message, _)| message)
+    {
+        "EOF while parsing a value" | "expected value" | "expected ident" => "esperava um valor",
+        "EOF while parsing a list" | "expected `,` or `]`" => "esperava ',' ou ']'",
+        "EOF while parsing an object" | "expected `,` or `}`" => "esperava ',' ou '}'",
+        "expected `:`" => "esperava ':'",
+        "key must be a string" => "esperava uma chave entre aspas",
+        "EOF while parsing a string" => "texto não terminado",
+        "invalid escape" => "escape inválido",
+        "invalid unicode code point"
+        | "lone leading surrogate in hex escape"
+        | "unexpected end of hex escape" => "escape \\u inválido",
+        "invalid number" | "number out of range" => "número inválido",
+        "control character (\\u0000-\\u001F) found while parsing a string" => {
+            "caractere de controle no texto"
+        }
+        "trailing comma" => "vírgula sobrando",
+        "trailing characters" => "dados a mais depois do valor",
+        "recursion limit exceeded" => "valores aninhados demais",
+        message => message,
+    };
+    Error::InvalidValue(format!(
+        "JSON inválido: {} na linha {}, coluna {}",
+        message,
+        error.line(),
+        error.column().max(1)
+    ))
+}
+
+/// Integers of any size become inteiros; a fraction or an exponent
+/// makes a real.
+fn from_json(value: Value) -> Result<Object> {
+    let object = match value {
+        Value::Null => Object::Unit,
+        Value::Bool(b) => b.into(),
+        Value::Number(n) => {
+            let text = n.to_string();
+            if text.contains(['.', 'e', 'E']) {
+                match text.parse::<f64>() {
+                    Ok(n) => n.into(),
+                    Err(_) => return Err(invalid_number(&text)),
+                }
+            } else {
+                match text.parse::<BigInt>() {
+                    Ok(n) => Object::Primitive(n.into()),
+                    Err(_) => return Err(invalid_number(&text)),
+                }
+            }
+        }
+        Value::String(s) => s.into(),
+        Value::Array(elements) => {
+            Object::Tuple(elements.into_iter().map(from_json).collect::<Result<_>>()?)
+        }
+        Value::Object(_) => {
+            return Err(Error::InvalidValue(
+                "objetos JSON ainda não são aceitos, pois a linguagem não tem dicionários".into(),
+            ))
+        }
+    };
+    Ok(object)
+}
+
+fn invalid_number(text: &str) -> Error {
+    Error::InvalidValue(format!("JSON inválido: número inválido: {}", text))
+}
+
+fn dump(args: Args) -> Result<Object> {
+    let indent = match args.keyword("indente") {
+        None | Some(Object::Unit) => None,
+        Some(Object::Primitive(Integer(n))) if *n >= 0 => Some(*n as usize),
+        Some(Object::Primitive(Integer(_))) => {
+            return Err(Error::InvalidValue("indente não pode ser negativo".into()))
+        }
+        Some(other) => return Err(type_error("converta", "um inteiro ou Vazio", other)),
+    };
+    let value = to_json(argument(&args, 0)?)?;
+    let mut text = Vec::new();
+    let written = match indent {
+        Some(indent) => {
+            let spaces = " ".repeat(indent);
+            let formatter = PrettyFormatter::with_indent(spaces.as_bytes());
+            value.serialize(&mut Serializer::with_formatter(&mut text, formatter))
+        }
+        None => value.serialize(&mut Serializer::with_formatter(&mut text, Spaced)),
+    };
+    written.map_err(|e| Error::InvalidValue(format!("não foi possível escrever o JSON: {}", e)))?;
+    String::from_utf8(text)
+        .map(Object::from)
+        .map_err(|e| Error::InvalidValue(format!("não foi possível escrever o JSON: {}", e)))
+}
+
+/// Python's `json.dumps`, but keeping characters outside ASCII as they are
+/// and refusing the reals JSON has no way to write.
+fn to_json(value: &Object) -> Result<Value> {
+    let json = match value {
+        Object::Unit => Value::Null,
+        Object::Primitive(Boolean(b)) => Value::Bool(*b),
+        Object::Primitive(Float(n)) if !n.is_finite() => {
+            return Err(Error::InvalidValue(format!(
+                "{} não pode ser escrito em JSON",
+                value
+            )))
+        }
+        Object::Primitive(Str(s)) => Value::String(s.clone()),
+        // numbers are written as the language writes them, `1e+20` included
+        Object::Primitive(_) => {
+            let text = value.to_string();
+            match text.parse::<Number>() {
+                Ok(n) => Value::Number(n),
+                Err(_) => {
+                    return Err(Error::InvalidValue(format!(
+                        "{} não pode ser escrito em JSON",
+                        text
+                    )))
+                }
+            }
+        }
+        Object::Tuple(elements) => {
+            Value::Array(elements.iter().map(to_json).collect::<Result<_>>()?)
+        }
+        other => {
+            return Err(Error::InvalidArgument(format!(
+                "um valor do tipo {} não pode ser escrito em JSON",
+                other.type_name()
+            )))
+        }
+    };
+    Ok(json)
+}
+
+/// A single line with a space after each separator, `[1, 2]`, as Python
+/// writes it.
+struct Spaced;
+
+impl Formatter for Spaced {
+    fn begin_array_value<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! `matematica`, the functions of Python's `math` on inteiros and reais.
+//!
+//! A result that makes no sense, such as `raiz(-1)`, is an ErroDeValor
+//! instead of a `nan` that would spread quietly through the program, and so
+//! is a finite computation that overflows the reals.
+
+use crate::builtin::{argument, float_to_integer, type_error};
+use crate::error::Error;
+use crate::native::{Args, Arity, Module, NativeFunction};
+use crate::object::Object;
+use crate::primitive::big_to_f64;
+use crate::primitive::Primitive::*;
+
+use itertools::Itertools;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use std::f64::consts;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+pub(super) fn module() -> Module {
+    let module = Module::new("matematica");
+    module.set("pi", consts::PI.into());
+    module.set("e", consts::E.into());
+    // `e` is also the operator, so `de matematica importe e` cannot name it
+    module.set("euler", consts::E.into());
+    module.set("infinito", f64::INFINITY.into());
+    module.set("nan", f64::NAN.into());
+
+    let functions = vec![
+        real_function("raiz", &["x"], |x| x[0].sqrt()).doc("A raiz quadrada de x."),
+        real_function("potencia", &["x", "y"], |x| {
+            // 0 ** -1 is a division by zero, not infinity
+            if x[0] == 0.0 && x[1] < 0.0 {
+                f64::NAN
+            } else {
+                x[0].powf(x[1])
+            }
+        })
+        .doc("x elevado a y, sempre como real."),
+        real_function("exp", &["x"], |x| x[0].exp()).doc("e elevado a x."),
+        NativeFunction::new("log", log)
+            .params(&["x", "base"])
+            .arity(Arity::Range(1, 2))
+            .doc(
+                "O logaritmo de x na base dada ou, sem ela, o natural. Funciona \
+                 também com inteiros grandes demais para um real.",
+            ),
+        NativeFunction::new("piso", |args| rounded("piso", &args, f64::floor))
+            .params(&["x"])
+            .doc("O maior inteiro menor ou igual a x."),
+        NativeFunction::new("teto", |args| rounded("teto", &args, f64::ceil))
+            .params(&["x"])
+            .doc("O menor inteiro maior ou igual a x."),
+        real_function("sen", &["x"], |x| x[0].sin()).doc("O seno de x, em radianos."),
+        real_function("cos", &["x"], |x| x[0].cos()).doc("O cosseno de x, em radianos."),
+        real_function("tan", &["x"], |x| x[0].tan()).doc("A tangente de x, em radianos."),
+        real_function("asen", &["x"], |x| x[0].asin()).doc("O arco, em radianos, cujo seno é x."),
+        real_function("acos", &["x"], |x| x[0].acos())
+            .doc("O arco, em radianos, cujo cosseno é x."),
+        real_function("atan", &["x"], |x| x[0].atan())
+            .doc("O arco, em radianos, cuja tangente é x."),
+        real_function("atan2", &["y", "x"], |x| x[0].atan2(x[1]))
+            .doc("O ângulo, em radianos, do ponto (x, y), considerando o quadrante."),
+        real_function("graus", &["x"], |x| x[0].to_degrees())
+            .doc("O ângulo x, em radianos, convertido para graus."),
+        real_function("radianos", &["x"], |x| x[0].to_radians())
+            .doc("O ângulo x, em graus, convertido para radianos."),
+        NativeFunction::new("mdc", gcd)
+            .rest("inteiros")
+            .doc("O máximo divisor comum dos inteiros; 0 sem nenhum."),
+        NativeFunction::new("fatorial", factorial)
+            .params(&["n"])
+            .doc("O produto dos inteiros de 1 a n."),
+        NativeFunction::new("e_proximo", is_close)
+            .params(&["a", "b"])
+            .keywords(&["tolerancia_rel", "tolerancia_abs"])
+            .doc(
+                "Verdadeiro se a e b diferem por no máximo `tolerancia_rel` (1e-09) \
+                 do maior deles ou por `tolerancia_abs` (0.0).",
+            ),
+    ];
+    for function in functions {
+        module.register(function);
+    }
+    module
+}
+
+/// A function of reals whose results are checked like those of Python's
+/// `math`: `nan` from numbers is outside the domain and infinity from finite
+/// numbers is an overflow.
+fn real_function(
+    name: &'static str,
+    params: &[&str],
+    function: fn(&[f64]) -> f64,
+) -> NativeFunction {
+    let count = params.len();
+    NativeFunction::new(name, move |args| {
+        let values = (0..count)
+            .map(|i| argument(&args, i))
+            .collect::<Result<Vec<_>>>()?;
+        let x = values
+            .iter()
+            .map(|value| real(name, value))
+            .collect::<Result<Vec<_>>>()?;
+
+        let result = function(&x);
+        if result.is_nan() && !x.iter().any(|x| x.is_nan()) {
+            return Err(domain_error(name, &values));
+        }
+        if result.is_infinite() && x.iter().all(|x| x.is_finite()) {
+            return Err(Error::InvalidValue(format!(
+                "{} é grande demais para um real",
+                call(name, &values)
+            )));
+        }
+        Ok(result.into())
+    })
+    .params(params)
+}
+
+fn real(function: &str, value: &Object) -> Result<f64> {
+    match value {
+        Object::Primitive(Integer(n)) => Ok(*n as f64),
+        Object::Primitive(BigInteger(n)) => Ok(big_to_f64(n)),
+        Object::Primitive(Float(n)) => Ok(*n),
+        other => Err(type_error(function, "um número", other)),
+    }
+}
+
+fn integer(function: &str, value: &Object) -> Result<BigInt> {
+    match value {
+        Object::Primitive(Integer(n)) => Ok(BigInt::from(*n)),
+        Object::Primitive(BigInteger(n)) => Ok(n.clone()),
+        other => Err(type_error(function, "um inteiro", other)),
+    }
+}
+
+/// How the call shows in error messages, e.g. `raiz(-1)`.
+fn call(function: &str, values: &[&Object]) -> String {
+    format!(
+        "{}({})",
+        function,
+        values.iter().map(|value| value.repr()).join(", ")
+    )
+}
+
+fn domain_error(function: &str, values: &[&Object]) -> Error {
+    Error::InvalidValue(format!("{} está fora do domínio", call(function, values)))
+}
+
+fn log(args: Args) -> Result<Object> {
+    let values: Vec<&Object> = args.positional().iter().collect();
+    let ln = |value: &Object| match value {
+        // past the reals, as Python does: ln(m * 2 ** k) = ln(m) + k ln(2),
+        // with m in [0.5, 1) from the leading bits
+        Object::Primitive(BigInteger(n)) if n.is_positive() && big_to_f64(n).is_infinite() => {
+            let shift = n.bits().saturating_sub(64);
+            let leading = (n >> shift).to_f64().expect("64 bits fit in a real");
+            let m = leading / 2f64.powi(64);
+            Ok(m.ln() + (shift + 64) as f64 * consts::LN_2)
+        }
+        value => match real("log", value)? {
+            x if x > 0.0 || x.is_nan() => Ok(x.ln()),
+            _ => Err(domain_error("log", &values)),
+        },
+    };
+
+    let x = ln(argument(&args, 0)?)?;
+    match args.get(1) {
+        None => Ok(x.into()),
+        Some(base) => match ln(base)? {
+            0.0 => Err(domain_error("log", &values)),
+            base => Ok((x / base).into()),
+        },
+    }
+}
+
+/// `piso` and `teto`, which are inteiros, as in Python.
+fn rounded(name: &str, args: &Args, round: fn(f64) -> f64) -> Result<Object> {
+    match argument(args, 0)? {
+        Object::Primitive(Float(n)) => float_to_integer(round(*n)),
+        value @ Object::Primitive(Integer(_)) | value @ Object::Primitive(BigInteger(_)) => {
+            Ok(value.clone())
+        }
+        other => Err(type_error(name, "um número", other)),
+    }
+}
+
+fn gcd(args: Args) -> Result<Object> {
+    let mut result = BigInt::zero();
+    for value in args.positional() {
+        result = result.gcd(&integer("mdc", value)?);
+    }
+    Ok(Object::Primitive(result.into()))
+}
+
+/// The largest argument of `fatorial`, whose result already has 35660
+/// digits; beyond it the product takes too long.
+const MAX_FACTORIAL: u64 = 10_000;
+
+fn factorial(args: Args) -> Result<Object> {
+    let n = integer("fatorial", argument(&args, 0)?)?;
+    if n.is_negative() {
+        return Err(Error::InvalidValue(
+            "fatorial() não é definido para negativos".into(),
+        ));
+    }
+    let n = match n.to_u64() {
+        Some(n) if n <= MAX_FACTORIAL => n,
+        _ => {
+            return Err(Error::InvalidValue(format!(
+                "fatorial() de {} passa do máximo de {}",
+                n, MAX_FACTORIAL
+            )))
+        }
+    };
+    let product = (2..=n).fold(BigInt::from(1), |product, i| product * i);
+    Ok(Object::Primitive(product.into()))
+}
+
+fn is_close(args: Args) -> Result<Object> {
+    let a = real("e_proximo", argument(&args, 0)?)?;
+    let b = real("e_proximo", argument(&args, 1)?)?;
+    let tolerance = |name, default| match args.keyword(name) {
+        None => Ok(default),
+        Some(value) => match real("e_proximo", value)? {
+            t if t < 0.0 => Err(Error::InvalidValue(format!(
+                "{} não pode ser negativa",
+                name
+            ))),
+            t => Ok(t),
+        },
+    };
+    let relative = tolerance("tolerancia_rel", 1e-9)?;
+    let absolute = tolerance("tolerancia_abs", 0.0)?;
+
+    if a == b {
+        return Ok(true.into());
+    }
+    if a.is_infinite() || b.is_infinite() {
+        return Ok(false.into());
+    }
+    let difference = (a - b).abs();
+    Ok((difference <= (relative * a.abs().max(b.abs())).max(absolute)).into())
+}
@@ -0,0 +1,24 @@
+//! The modules that come with the language. They are imported by name, like
+//! the modules in files, but are found before them.
+
+mod json;
+mod math;
+mod random;
+mod regex;
+mod system;
+mod time;
+
+use crate::native::Module;
+
+/// Fresh modules for an engine, so what a program sets on them stays in it.
+pub(crate) fn modules() -> Vec<Module> {
+    vec![
+        math::module(),
+        json::module(),
+        random::module(),
+        system::module(),
+        regex::module(),
+        time::tempo(),
+        time::datahora(),
+    ]
+}
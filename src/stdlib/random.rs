@@ -0,0 +1,233 @@
+//! `aleatorio`, pseudo-random numbers for games, exercises and simulations.
+//!
+//! The generator is xoshiro256**, written here so that a `semente` gives the
+//! same numbers on every platform and in every version. Each engine has its
+//! own generator, started from a random seed.
+
+use crate::builtin::{argument, type_error};
+use crate::error::Error;
+use crate::native::{Args, Arity, Module, NativeFunction};
+use crate::object::Object;
+use crate::primitive::Primitive::*;
+
+use num_bigint::BigInt;
+
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::rc::Rc;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+pub(super) fn module() -> Module {
+    let generator = Rc::new(RefCell::new(Generator::new(random_seed())));
+    let with = |function: fn(&mut Generator, Args) -> Result<Object>| {
+        let generator = Rc::clone(&generator);
+        move |args| function(&mut generator.borrow_mut(), args)
+    };
+
+    let module = Module::new("aleatorio");
+    let functions = vec![
+        NativeFunction::new("semente", with(seed))
+            .params(&["valor"])
+            .arity(Arity::Range(0, 1))
+            .doc(
+                "Reinicia o gerador a partir do valor, um inteiro ou um texto, para \
+                 repetir a mesma sequência; sem ele ou com Vazio, de um valor imprevisível.",
+            ),
+        NativeFunction::new("inteiro", with(integer))
+            .params(&["a", "b"])
+            .doc("Um inteiro entre a e b, incluindo os dois."),
+        NativeFunction::new("real", with(|generator, _| Ok(generator.real().into())))
+            .params(&[])
+            .doc("Um real a partir de 0.0 e menor que 1.0."),
+        NativeFunction::new("escolha", with(choice))
+            .params(&["sequencia"])
+            .doc("Um elemento da sequência."),
+        NativeFunction::new("embaralhe", with(shuffle))
+            .params(&["sequencia"])
+            .doc("Uma tupla com os elementos da sequência em ordem aleatória."),
+        NativeFunction::new("amostra", with(sample))
+            .params(&["sequencia", "k"])
+            .doc("Uma tupla com k elementos de posições diferentes da sequência."),
+    ];
+    for function in functions {
+        module.register(function);
+    }
+    module
+}
+
+/// xoshiro256** by Blackman and Vigna, seeded through splitmix64 as its
+/// authors recommend.
+struct Generator {
+    state: [u64; 4],
+}
+
+impl Generator {
+    fn new(seed: u64) -> Self {
+        let mut x = seed;
+        let mut state = [0; 4];
+        for word in &mut state {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            *word = mix(x);
+        }
+        Generator { state }
+    }
+
+    fn next(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// A number in `0..n`, without the bias of taking a remainder; `n` of 0
+    /// stands for 2 ** 64.
+    fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            return self.next();
+        }
+        // the largest multiple of n that fits, so every remainder is as likely
+        let zone = u64::MAX - (u64::MAX - n + 1) % n;
+        loop {
+            let x = self.next();
+            if x <= zone {
+                return x % n;
+            }
+        }
+    }
+
+    /// 53 random bits, all a real holds below 1.
+    fn real(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Fisher-Yates, from the end.
+    fn shuffle(&mut self, elements: &mut [Object]) {
+        for i in (1..elements.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            elements.swap(i, j);
+        }
+    }
+}
+
+/// The output function of splitmix64.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The standard library seeds its hash maps from the operating system.
+fn random_seed() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Folds the bytes into a seed, eight at a time.
+fn seed_from(bytes: &[u8]) -> u64 {
+    bytes.chunks(8).fold(bytes.len() as u64, |seed, chunk| {
+        let mut word = [0; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+        mix(seed ^ u64::from_le_bytes(word))
+    })
+}
+
+fn seed(generator: &mut Generator, args: Args) -> Result<Object> {
+    let seed = match args.get(0) {
+        None | Some(Object::Unit) => random_seed(),
+        Some(Object::Primitive(Integer(n))) => seed_from(&BigInt::from(*n).to_signed_bytes_le()),
+        Some(Object::Primitive(BigInteger(n))) => seed_from(&n.to_signed_bytes_le()),
+        Some(Object::Primitive(Str(s))) => !seed_from(s.as_bytes()),
+        Some(other) => return Err(type_error("semente", "um inteiro ou um texto", other)),
+    };
+    *generator = Generator::new(seed);
+    Ok(Object::Unit)
+}
+
+fn integer(generator: &mut Generator, args: Args) -> Result<Object> {
+    let bound = |i| match argument(&args, i)? {
+        Object::Primitive(Integer(n)) => Ok(*n),
+        other => Err(type_error("inteiro", "um inteiro", other)),
+    };
+    let (a, b) = (bound(0)?, bound(1)?);
+    if a > b {
+        return Err(Error::InvalidValue(format!(
+            "inteiro({}, {}) não tem nenhum inteiro",
+            a, b
+        )));
+    }
+    let span = (b as u64).wrapping_sub(a as u64).wrapping_add(1);
+    let n = (a as u64).wrapping_add(generator.below(span)) as i64;
+    Ok(n.into())
+}
+
+fn choice(generator: &mut Generator, args: Args) -> Result<Object> {
+    let elements = argument(&args, 0)?.items()?;
+    if elements.is_empty() {
+        return Err(Error::InvalidValue(
+            "escolha() recebeu uma sequência vazia".into(),
+        ));
+    }
+    let i = generator.below(elements.len() as u64) as usize;
+    Ok(elements[i].clone())
+}
+
+fn shuffle(generator: &mut Generator, args: Args) -> Result<Object> {
+    let mut elements = argument(&args, 0)?.items()?;
+    generator.shuffle(&mut elements);
+    Ok(Object::Tuple(elements))
+}
+
+/// The first k elements of a partial shuffle.
+fn sample(generator: &mut Generator, args: Args) -> Result<Object> {
+    let mut elements = argument(&args, 0)?.items()?;
+    let k = match argument(&args, 1)? {
+        Object::Primitive(Integer(k)) if *k >= 0 && *k as usize <= elements.len() => *k as usize,
+        Object::Primitive(Integer(k)) => {
+            return Err(Error::InvalidValue(format!(
+                "amostra() não pode ter {} elementos de uma sequência de {}",
+                k,
+                elements.len()
+            )))
+        }
+        other => return Err(type_error("amostra", "um inteiro", other)),
+    };
+    for i in 0..k {
+        let j = i + generator.below((elements.len() - i) as u64) as usize;
+        elements.swap(i, j);
+    }
+    elements.truncate(k);
+    Ok(Object::Tuple(elements))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_outputs() {
+        // from the authors' C implementation
+        let mut generator = Generator {
+            state: [1, 2, 3, 4],
+        };
+        let outputs: Vec<u64> = (0..4).map(|_| generator.next()).collect();
+        assert_eq!(vec![11520, 0, 1509978240, 1215971899390074240], outputs);
+    }
+
+    #[test]
+    fn below_covers_the_whole_range() {
+        let mut generator = Generator::new(7);
+        let mut seen = [false; 6];
+        for _ in 0..200 {
+            seen[generator.below(6) as usize] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+        assert!((0..1000).all(|_| (0.0..1.0).contains(&generator.real())));
+    }
+}
@@ -0,0 +1,157 @@
+//! `regex`, regular expressions with the syntax of Python's `re`.
+//!
+//! Every function takes the pattern first, as a text or compiled by
+//! `compile`, and does what the method of the same name of a compiled
+//! pattern does. Patterns given as texts are compiled once and kept.
+
+use crate::builtin::{argument, type_error};
+use crate::error::Error;
+use crate::native::{Args, Arity, Module, NativeFunction};
+use crate::object::Object;
+use crate::primitive::Primitive::*;
+use crate::regex::{Flags, Pattern};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+type Cache = Rc<RefCell<Patterns>>;
+
+/// As many patterns as Python keeps; when the cache is full, the pattern
+/// used longest ago makes room for the new one.
+const CACHE_SIZE: usize = 512;
+
+/// The patterns given as texts, each with when it was last used.
+#[derive(Default)]
+struct Patterns {
+    compiled: HashMap<(String, Flags), (Pattern, u64)>,
+    clock: u64,
+}
+
+const OPTIONS: &[&str] = &["ignore_caixa", "multilinha", "ponto_tudo"];
+
+pub(super) fn module() -> Module {
+    let cache: Cache = Rc::new(RefCell::new(Patterns::default()));
+    // the function of the module for the method, with the pattern first
+    let method = |name: &str, params: &[&str], arity: Arity, doc: &str| {
+        let cache = Rc::clone(&cache);
+        let function = name.to_string();
+        let count = params.len();
+        let params: Vec<&str> = Some("padrao")
+            .into_iter()
+            .chain(params.iter().copied())
+            .collect();
+        NativeFunction::new(name, move |args| {
+            let pattern = pattern(&cache, &function, &args)?;
+            let method = pattern
+                .attribute(&function)
+                .expect("the functions of the module are methods of patterns");
+            let args = (1..=count).map_while(|i| args.get(i).cloned()).collect();
+            method.call(args, vec![])
+        })
+        .params(&params)
+        .keywords(OPTIONS)
+        .arity(arity)
+        .doc(doc)
+    };
+
+    let module = Module::new("regex");
+    let compile_cache = Rc::clone(&cache);
+    let functions = vec![
+        NativeFunction::new("compile", move |args| {
+            Ok(pattern(&compile_cache, "compile", &args)?.into())
+        })
+        .params(&["padrao"])
+        .keywords(OPTIONS)
+        .doc(
+            "O padrão compilado, para usar várias vezes. Com ignore_caixa, as \
+             letras correspondem em qualquer caixa; com multilinha, ^ e $ \
+             valem em cada linha; com ponto_tudo, . corresponde também a '\\n'.",
+        ),
+        method(
+            "busque",
+            &["texto"],
+            Arity::Exact(2),
+            "A primeira correspondência do padrão no texto, ou Vazio quando não há.",
+        ),
+        method(
+            "corresponde",
+            &["texto"],
+            Arity::Exact(2),
+            "A correspondência do padrão no início do texto, ou Vazio quando não há.",
+        ),
+        method(
+            "encontre_todos",
+            &["texto"],
+            Arity::Exact(2),
+            "Uma tupla com os textos de todas as correspondências do padrão; com \
+             grupos, os textos dos grupos.",
+        ),
+        method(
+            "substitua",
+            &["substituto", "texto", "vezes"],
+            Arity::Range(3, 4),
+            "O texto com as correspondências do padrão trocadas pelo substituto, \
+             onde \\1 e \\g<nome> são os grupos; com vezes, só as primeiras.",
+        ),
+        method(
+            "divida",
+            &["texto", "vezes"],
+            Arity::Range(2, 3),
+            "Uma tupla com as partes do texto entre as correspondências do padrão \
+             e os grupos de cada uma; com vezes, divide só nas primeiras.",
+        ),
+    ];
+    for function in functions {
+        module.register(function);
+    }
+    module
+}
+
+/// The pattern of the first argument, compiled with the options given.
+fn pattern(cache: &Cache, function: &str, args: &Args) -> Result<Pattern> {
+    let option = |name| match args.keyword(name) {
+        None => Ok(false),
+        Some(Object::Primitive(Boolean(b))) => Ok(*b),
+        Some(other) => Err(type_error(function, "um logico", other)),
+    };
+    let flags = Flags {
+        ignore_case: option("ignore_caixa")?,
+        multiline: option("multilinha")?,
+        dot_all: option("ponto_tudo")?,
+    };
+
+    let source = match argument(args, 0)? {
+        Object::Pattern(pattern) if flags == Flags::default() => return Ok(pattern.clone()),
+        Object::Pattern(_) => {
+            return Err(Error::InvalidValue(
+                "as opções não se aplicam a um padrão já compilado".into(),
+            ))
+        }
+        Object::Primitive(Str(source)) => source,
+        other => return Err(type_error(function, "um texto ou um padrão", other)),
+    };
+    let mut cache = cache.borrow_mut();
+    cache.clock += 1;
+    let now = cache.clock;
+    let key = (source.clone(), flags);
+    if let Some((pattern, used)) = cache.compiled.get_mut(&key) {
+        *used = now;
+        return Ok(pattern.clone());
+    }
+    let pattern = Pattern::new(source, flags)?;
+    if cache.compiled.len() >= CACHE_SIZE {
+        let oldest = cache
+            .compiled
+            .iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            cache.compiled.remove(&oldest);
+        }
+    }
+    cache.compiled.insert(key, (pattern.clone(), now));
+    Ok(pattern)
+}
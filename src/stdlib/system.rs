@@ -0,0 +1,149 @@
+//! `sistema`, what the program knows of the process running it: its
+//! arguments, its environment and its exit status, and other programs it can
+//! run.
+
+use crate::builtin::{argument, type_error};
+use crate::error::Error;
+use crate::native::{Args, Arity, Module, NativeFunction};
+use crate::object::Object;
+use crate::primitive::Primitive::*;
+
+use std::env;
+use std::io;
+use std::process::Command;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+pub(super) fn module() -> Module {
+    let module = Module::new("sistema");
+    module.set("argumentos", Object::Tuple(vec![]));
+    module.set("plataforma", env::consts::OS.into());
+    let functions = vec![
+        NativeFunction::new("ambiente", environment)
+            .params(&["nome", "valor"])
+            .arity(Arity::Range(0, 2))
+            .doc(
+                "O valor da variável de ambiente, ou Vazio quando não existe; com \
+                 um valor, muda a variável, e com Vazio a remove. Sem nome, uma \
+                 tupla de pares (nome, valor) com todas elas.",
+            ),
+        NativeFunction::new("saia", exit)
+            .params(&["codigo"])
+            .arity(Arity::Range(0, 1))
+            .doc(
+                "Termina o programa com o código de saída, 0 quando omitido, \
+                 fechando antes o que foi aberto com 'com'.",
+            ),
+        NativeFunction::new("execute", execute)
+            .params(&["comando", "argumentos"])
+            .arity(Arity::Range(1, 2))
+            .doc(
+                "Executa o comando com os argumentos, esperando que termine, e \
+                 retorna a tupla (saida, erro, codigo); o código é Vazio quando \
+                 o comando é interrompido por um sinal.",
+            ),
+    ];
+    for function in functions {
+        module.register(function);
+    }
+    module
+}
+
+fn text<'a>(function: &str, value: &'a Object) -> Result<&'a str> {
+    match value {
+        Object::Primitive(Str(s)) => Ok(s),
+        other => Err(type_error(function, "um texto", other)),
+    }
+}
+
+fn environment(args: Args) -> Result<Object> {
+    let name = match args.get(0) {
+        Some(name) => text("ambiente", name)?,
+        None => {
+            let mut variables: Vec<(String, String)> = env::vars_os()
+                .filter_map(|(name, value)| {
+                    Some((name.into_string().ok()?, value.into_string().ok()?))
+                })
+                .collect();
+            variables.sort();
+            let pairs = variables
+                .into_iter()
+                .map(|(name, value)| Object::Tuple(vec![name.into(), value.into()]))
+                .collect();
+            return Ok(Object::Tuple(pairs));
+        }
+    };
+    if name.is_empty() || name.contains('=') || name.contains('\0') {
+        return Err(Error::InvalidValue(format!(
+            "nome de variável de ambiente inválido: '{}'",
+            name
+        )));
+    }
+    match args.get(1) {
+        None => Ok(env::var(name).map(Object::from).unwrap_or(Object::Unit)),
+        Some(Object::Unit) => {
+            env::remove_var(name);
+            Ok(Object::Unit)
+        }
+        Some(value) => {
+            let value = text("ambiente", value)?;
+            if value.contains('\0') {
+                return Err(Error::InvalidValue(
+                    "o valor de uma variável de ambiente não pode ter '\\0'".into(),
+                ));
+            }
+            env::set_var(name, value);
+            Ok(Object::Unit)
+        }
+    }
+}
+
+/// Unwinds both backends as an error that nothing catches, so the contexts
+/// of `com` are closed on the way out.
+fn exit(args: Args) -> Result<Object> {
+    let code = match args.get(0) {
+        None | Some(Object::Unit) => 0,
+        Some(Object::Primitive(Integer(n))) => *n,
+        Some(other) => return Err(type_error("saia", "um inteiro", other)),
+    };
+    if code < i64::from(i32::MIN) || code > i64::from(i32::MAX) {
+        return Err(Error::InvalidValue(format!(
+            "código de saída fora do intervalo: {}",
+            code
+        )));
+    }
+    Err(Error::Exit(code as i32))
+}
+
+fn execute(args: Args) -> Result<Object> {
+    let command = text("execute", argument(&args, 0)?)?;
+    let arguments = match args.get(1) {
+        None => vec![],
+        Some(value) => value
+            .items()?
+            .iter()
+            .map(|argument| text("execute", argument).map(String::from))
+            .collect::<Result<Vec<_>>>()?,
+    };
+    let output = Command::new(command)
+        .args(&arguments)
+        .output()
+        .map_err(|error| match error.kind() {
+            io::ErrorKind::NotFound => {
+                Error::FileError(format!("comando não encontrado: '{}'", command))
+            }
+            _ => Error::FileError(format!(
+                "não foi possível executar '{}': {}",
+                command, error
+            )),
+        })?;
+    let code = match output.status.code() {
+        Some(code) => Object::from(i64::from(code)),
+        None => Object::Unit,
+    };
+    Ok(Object::Tuple(vec![
+        String::from_utf8_lossy(&output.stdout).into_owned().into(),
+        String::from_utf8_lossy(&output.stderr).into_owned().into(),
+        code,
+    ]))
+}
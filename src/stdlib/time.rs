@@ -0,0 +1,189 @@
+//! `tempo`, the clocks, and `datahora`, dates and times of day.
+//!
+//! There is no time zone database, so `datahora.agora()` is in UTC, as is
+//! every conversion from a timestamp.
+
+use crate::builtin::{argument, type_error};
+use crate::datetime::DateTime;
+use crate::error::Error;
+use crate::native::{Args, Arity, Module, NativeFunction};
+use crate::object::Object;
+use crate::primitive::Primitive::*;
+
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+pub(super) fn tempo() -> Module {
+    let start = Instant::now();
+    let module = Module::new("tempo");
+    let functions = vec![
+        NativeFunction::new("agora", |_| Ok(since_epoch().as_secs_f64().into()))
+            .params(&[])
+            .doc("Os segundos desde 1970-01-01 00:00:00 UTC, com fração."),
+        NativeFunction::new("monotonico", move |_| {
+            Ok(start.elapsed().as_secs_f64().into())
+        })
+        .params(&[])
+        .doc(
+            "Os segundos de um relógio que nunca volta, para medir quanto algo \
+             demora; só a diferença entre duas leituras tem sentido.",
+        ),
+        NativeFunction::new("durma", sleep)
+            .params(&["segundos"])
+            .doc("Suspende o programa pelos segundos, que podem ter fração."),
+    ];
+    for function in functions {
+        module.register(function);
+    }
+    module
+}
+
+pub(super) fn datahora() -> Module {
+    let module = Module::new("datahora");
+    let functions = vec![
+        NativeFunction::new("datahora", new)
+            .params(&[
+                "ano",
+                "mes",
+                "dia",
+                "hora",
+                "minuto",
+                "segundo",
+                "microssegundo",
+            ])
+            .arity(Arity::Range(3, 7))
+            .doc("A data e a hora, que é meia-noite quando omitida."),
+        NativeFunction::new("agora", |_| {
+            Ok(DateTime::from_micros(since_epoch().as_micros() as i64)?.into())
+        })
+        .params(&[])
+        .doc("A data e a hora atuais, em UTC."),
+        NativeFunction::new("de_timestamp", from_timestamp)
+            .params(&["segundos"])
+            .doc("A data e a hora, em UTC, dos segundos desde 1970-01-01 00:00:00."),
+        NativeFunction::new("analise", parse)
+            .params(&["texto", "formato"])
+            .doc(
+                "A data e a hora escritas no texto segundo o formato, como \
+                 '%d/%m/%Y %H:%M'.",
+            ),
+        NativeFunction::new("de_iso", from_iso)
+            .params(&["texto"])
+            .doc("A data e a hora escritas em ISO 8601, como 2024-03-05T14:30:00."),
+        NativeFunction::new("duracao", duration)
+            .params(&["dias", "horas", "minutos", "segundos"])
+            .arity(Arity::Range(0, 4))
+            .doc(
+                "Os segundos que somam os dias, as horas, os minutos e os segundos, \
+                 para somar a uma datahora.",
+            ),
+    ];
+    for function in functions {
+        module.register(function);
+    }
+    module
+}
+
+fn since_epoch() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+fn seconds(function: &str, value: &Object) -> Result<f64> {
+    match value {
+        Object::Primitive(Integer(n)) => Ok(*n as f64),
+        Object::Primitive(Float(n)) => Ok(*n),
+        other => Err(type_error(function, "um número", other)),
+    }
+}
+
+fn text<'a>(function: &str, value: &'a Object) -> Result<&'a str> {
+    match value {
+        Object::Primitive(Str(s)) => Ok(s),
+        other => Err(type_error(function, "um texto", other)),
+    }
+}
+
+fn sleep(args: Args) -> Result<Object> {
+    let seconds = seconds("durma", argument(&args, 0)?)?;
+    let duration = Duration::try_from_secs_f64(seconds)
+        .map_err(|_| Error::InvalidValue(format!("durma() não aceita {} segundos", seconds)))?;
+    thread::sleep(duration);
+    Ok(Object::Unit)
+}
+
+fn new(args: Args) -> Result<Object> {
+    let mut fields = [0; 7];
+    for (i, field) in fields.iter_mut().enumerate() {
+        match args.get(i) {
+            None => {}
+            Some(Object::Primitive(Integer(n))) => *field = *n,
+            Some(other) => return Err(type_error("datahora", "um inteiro", other)),
+        }
+    }
+    let [year, month, day, hour, minute, second, micro] = fields;
+    Ok(DateTime::new(year, month, day, hour, minute, second, micro)?.into())
+}
+
+fn from_timestamp(args: Args) -> Result<Object> {
+    let seconds = seconds("de_timestamp", argument(&args, 0)?)?;
+    let micros = (seconds * 1e6).round();
+    if !micros.is_finite() || micros.abs() >= i64::MAX as f64 {
+        return Err(Error::InvalidValue("datahora fora do intervalo".into()));
+    }
+    Ok(DateTime::from_micros(micros as i64)?.into())
+}
+
+fn parse(args: Args) -> Result<Object> {
+    let texto = text("analise", argument(&args, 0)?)?;
+    let formato = text("analise", argument(&args, 1)?)?;
+    Ok(DateTime::parse(texto, formato)?.into())
+}
+
+/// The date alone, or with hours and minutes, seconds and a fraction,
+/// separated by `T` or a space, as Python's `fromisoformat`.
+fn from_iso(args: Args) -> Result<Object> {
+    let texto = text("de_iso", argument(&args, 0)?)?;
+    let numbers = texto
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|number| !number.is_empty())
+        .count();
+    let time = match numbers {
+        3 => "",
+        5 => "%H:%M",
+        6 => "%H:%M:%S",
+        7 => "%H:%M:%S.%f",
+        _ => return Err(not_iso(texto)),
+    };
+    let separator = if texto.contains(' ') { ' ' } else { 'T' };
+    let format = match time {
+        "" => "%Y-%m-%d".to_string(),
+        time => format!("%Y-%m-%d{}{}", separator, time),
+    };
+    match DateTime::parse(texto, &format) {
+        Ok(datetime) => Ok(datetime.into()),
+        Err(Error::InvalidValue(message)) if message.contains("não corresponde") => {
+            Err(not_iso(texto))
+        }
+        // a date that does not exist, such as 2023-02-29, says why
+        Err(error) => Err(error),
+    }
+}
+
+fn not_iso(texto: &str) -> Error {
+    Error::InvalidValue(format!("'{}' não é uma datahora ISO 8601", texto))
+}
+
+fn duration(args: Args) -> Result<Object> {
+    let units = [86_400.0, 3_600.0, 60.0, 1.0];
+    let mut total = 0.0;
+    for (i, unit) in units.iter().enumerate() {
+        if let Some(value) = args.get(i) {
+            total += seconds("duracao", value)? * unit;
+        }
+    }
+    Ok(total.into())
+}
@@ -1,7 +1,13 @@
-#[derive(Debug, Clone, PartialEq)]
+use num_bigint::BigInt;
+use serde::{Serialize, Serializer};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum Token {
     // int
     Inteiro(i64),
+    // int that does not fit in 64 bits
+    #[serde(serialize_with = "serialize_big_integer")]
+    InteiroGrande(BigInt),
     // float
     Real(f64),
     // str
@@ -42,7 +48,8 @@ pub enum Token {
     Em,
     // is , [E0658]: non-ascii idents are not fully supported. (see issue #55467)
     Is,
-    // not is
+    // not is, not produced by the tokenizer yet
+    #[allow(dead_code)]
     NotIs,
     // assert
     Verifique,
@@ -68,6 +75,14 @@ pub enum Token {
     Passe,
     // raise
     Provoque,
+    // import
+    Importe,
+    // from
+    De,
+    // as
+    Como,
+    // with
+    Com,
 
     // identifier == variables
     Identifier(String),
@@ -76,11 +91,16 @@ pub enum Token {
     Plus,
     Minus,
     Star,
+    // **
+    StarStar,
     Slash,
+    // //
+    SlashSlash,
     Equal,
     // %
     Percent,
-    Comment,
+    // the text after '#'
+    Comment(String),
     Bang,
     // :
     Colon,
@@ -101,7 +121,27 @@ pub enum Token {
     LessThan,
     BangEqual,
 
+    // augmented assignment
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    // //=
+    SlashSlashEqual,
+    // %=
+    PercentEqual,
+    // **=
+    StarStarEqual,
+
     // etc
     WhiteSpace,
-    EOF,
+}
+
+/// Big integers are written as strings, since JSON numbers lose precision past
+/// 64 bits.
+pub(crate) fn serialize_big_integer<S: Serializer>(
+    value: &BigInt,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
 }
@@ -1,21 +1,123 @@
 use itertools::{multipeek, MultiPeek};
 
+use num_bigint::BigInt;
+
 use crate::token::Token;
+use serde::Serialize;
+use std::fmt;
 use std::str;
 
+/// The reserved words and the token each one is scanned as.
+pub(crate) const KEYWORDS: &[(&str, Token)] = &[
+    ("Verdadeiro", Token::Logico(true)),
+    ("Falso", Token::Logico(false)),
+    ("Vazio", Token::Vazio),
+    ("classe", Token::Classe),
+    ("fun", Token::Fun),
+    ("lista", Token::Lista),
+    ("dicionario", Token::Dicionario),
+    ("tupla", Token::Tupla),
+    ("conjunto", Token::Conjunto),
+    ("imprima", Token::Imprima),
+    ("entrada", Token::Entrada),
+    ("se", Token::Se),
+    ("senao", Token::SeNao),
+    ("ouentaose", Token::OuEntaoSe),
+    ("e", Token::E),
+    ("ou", Token::Ou),
+    ("nao", Token::Nao),
+    ("é", Token::Is),
+    ("remova", Token::Remova),
+    ("interrompa", Token::Interrompa),
+    ("retorne", Token::Retorne),
+    ("continue", Token::Continue),
+    ("paracada", Token::ParaCada),
+    ("enquanto", Token::Enquanto),
+    ("verifique", Token::Verifique),
+    ("passe", Token::Passe),
+    ("tente", Token::Tente),
+    ("exceto", Token::Exceto),
+    ("provoque", Token::Provoque),
+    ("global", Token::Global),
+    ("em", Token::Em),
+    ("importe", Token::Importe),
+    ("de", Token::De),
+    ("como", Token::Como),
+    ("com", Token::Com),
+];
+
+/// Line and column (both starting at 1) of a character in the source code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Where a token starts and the position right after its last character.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LexErrorKind {
+    UnexpectedCharacter(char),
+    InvalidNumber { lexeme: String, reason: String },
+    UnterminatedString,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub position: Position,
+}
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexErrorKind::UnexpectedCharacter(c) => write!(f, "caractere inesperado {:?}", c),
+            LexErrorKind::InvalidNumber { lexeme, reason } => {
+                write!(f, "número inválido '{}': {}", lexeme, reason)
+            }
+            LexErrorKind::UnterminatedString => write!(f, "texto sem aspas de fechamento"),
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.position, self.kind)
+    }
+}
+
 #[derive(Debug)]
 pub struct Tokenizer<'a> {
     position: usize,
+    line: usize,
+    column: usize,
+    start: Position,
     current_lexeme: String,
     source: MultiPeek<str::Chars<'a>>,
+    errors: Vec<LexError>,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn init(source: &'a str) -> Self {
         Tokenizer {
             position: 0,
+            line: 1,
+            column: 1,
+            start: Position { line: 1, column: 1 },
             current_lexeme: "".into(),
             source: multipeek(source.chars()),
+            errors: Vec::new(),
         }
     }
 
@@ -23,6 +125,13 @@ impl<'a> Tokenizer<'a> {
         let current = self.source.next();
         if let Some(c) = current {
             self.current_lexeme.push(c);
+            self.position += 1;
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
         }
 
         current
@@ -52,35 +161,69 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
-    fn peek_check_two(
-        &mut self,
-        cond1: &dyn Fn(char) -> bool,
-        cond2: &dyn Fn(char) -> bool,
-    ) -> bool {
-        self.source.reset_peek();
-
-        match self.source.peek() {
-            Some(&c) => match self.source.peek() {
-                Some(&c2) => cond1(c) && cond2(c2),
-                None => false,
-            },
-            None => false,
+    /// The next token, skipping over any that are errors.
+    pub fn scan_next(&mut self) -> Option<Token> {
+        loop {
+            let errors = self.errors.len();
+            match self.scan_token() {
+                None if self.errors.len() > errors => continue,
+                token => return token,
+            }
         }
     }
 
-    pub fn scan_next(&mut self) -> Option<Token> {
+    /// The next token, or `None` at the end of the source or when the token
+    /// is an error.
+    fn scan_token(&mut self) -> Option<Token> {
         self.current_lexeme.clear();
-
-        let curr_char = match self.advance() {
-            Some(c) => c,
-            None => return None,
+        self.start = Position {
+            line: self.line,
+            column: self.column,
         };
 
+        let curr_char = self.advance()?;
+
         match curr_char {
-            '+' => Some(Token::Plus),
-            '-' => Some(Token::Minus),
-            '*' => Some(Token::Star),
-            '/' => Some(Token::Slash),
+            '+' => {
+                if self.advance_if_match('=') {
+                    Some(Token::PlusEqual)
+                } else {
+                    Some(Token::Plus)
+                }
+            }
+            '-' => {
+                if self.advance_if_match('=') {
+                    Some(Token::MinusEqual)
+                } else {
+                    Some(Token::Minus)
+                }
+            }
+            '*' => {
+                if self.advance_if_match('*') {
+                    if self.advance_if_match('=') {
+                        Some(Token::StarStarEqual)
+                    } else {
+                        Some(Token::StarStar)
+                    }
+                } else if self.advance_if_match('=') {
+                    Some(Token::StarEqual)
+                } else {
+                    Some(Token::Star)
+                }
+            }
+            '/' => {
+                if self.advance_if_match('/') {
+                    if self.advance_if_match('=') {
+                        Some(Token::SlashSlashEqual)
+                    } else {
+                        Some(Token::SlashSlash)
+                    }
+                } else if self.advance_if_match('=') {
+                    Some(Token::SlashEqual)
+                } else {
+                    Some(Token::Slash)
+                }
+            }
             '=' => {
                 if self.advance_if_match('=') {
                     Some(Token::EqualEqual)
@@ -88,10 +231,16 @@ impl<'a> Tokenizer<'a> {
                     Some(Token::Equal)
                 }
             }
-            '%' => Some(Token::Percent),
+            '%' => {
+                if self.advance_if_match('=') {
+                    Some(Token::PercentEqual)
+                } else {
+                    Some(Token::Percent)
+                }
+            }
             '#' => {
                 self.advance_while(&|c| c != '\n');
-                Some(Token::Comment)
+                Some(Token::Comment(self.current_lexeme[1..].into()))
             }
             '!' => {
                 if self.advance_if_match('=') {
@@ -102,6 +251,7 @@ impl<'a> Tokenizer<'a> {
             }
             ':' => Some(Token::Colon),
             ',' => Some(Token::Comma),
+            '.' if self.peek_check(&|c| c.is_ascii_digit()) => self.consume_numbers(),
             '.' => Some(Token::Dot),
             '>' => {
                 if self.advance_if_match('=') {
@@ -124,86 +274,191 @@ impl<'a> Tokenizer<'a> {
             '{' => Some(Token::BracketOpen),
             '}' => Some(Token::BracketClose),
             '"' => self.consume_string(),
-            c if c.is_numeric() => self.consume_numbers(),
+            c if c.is_ascii_digit() => self.consume_numbers(),
+            c if c.is_numeric() => {
+                self.advance_while(&|c| c.is_alphanumeric());
+                self.number_error("dígitos devem ser ASCII (0-9)")
+            }
             c if c.is_whitespace() => Some(Token::WhiteSpace),
-            c if c.is_alphabetic() => self.consumer_identifier(),
-            _ => Some(Token::EOF),
+            c if c.is_alphabetic() || c == '_' => self.consumer_identifier(),
+            c => self.error(LexErrorKind::UnexpectedCharacter(c)),
         }
     }
 
     fn consume_numbers(&mut self) -> Option<Token> {
-        let is_digit = |c: char| c.is_numeric();
-        self.advance_while(&is_digit);
+        if self.current_lexeme == "0" {
+            self.source.reset_peek();
+            let radix = match self.source.peek() {
+                Some('x') | Some('X') => Some(16),
+                Some('o') | Some('O') => Some(8),
+                Some('b') | Some('B') => Some(2),
+                _ => None,
+            };
+            if let Some(radix) = radix {
+                self.advance();
+                return self.consume_radix_number(radix);
+            }
+        }
 
-        if self.peek_check_two(&|c| c == '.', &is_digit) {
+        let mut is_float = self.current_lexeme == ".";
+        let mut valid = if is_float {
+            self.consume_digits()
+        } else {
+            self.consume_digits_after_first()
+        };
+
+        if !is_float && self.advance_if_match('.') {
+            is_float = true;
+            if self.peek_check(&|c| c.is_ascii_digit() || c == '_') {
+                valid &= self.consume_digits();
+            }
+        }
+
+        if self.peek_check(&|c| c == 'e' || c == 'E') {
+            is_float = true;
             self.advance();
-            self.advance_while(&is_digit)
+            if !self.advance_if_match('+') {
+                self.advance_if_match('-');
+            }
+            valid &= self.consume_digits();
         }
 
-        if self.current_lexeme.contains(".") {
-            let value = self
-                .current_lexeme
-                .parse::<f64>()
-                .expect("expected 'real' type");
+        if let Some(token) = self.invalid_number_suffix() {
+            return token;
+        }
+        if !valid {
+            return self.number_error("uso inválido de '_' ou expoente sem dígitos");
+        }
 
-            Some(Token::Real(value))
+        let literal: String = self.current_lexeme.chars().filter(|&c| c != '_').collect();
+        if is_float {
+            match literal.parse::<f64>() {
+                Ok(value) => Some(Token::Real(value)),
+                Err(_) => self.number_error("número real inválido"),
+            }
         } else {
-            let value = self
-                .current_lexeme
-                .parse::<i64>()
-                .expect("expected 'inteiro' type");
+            Self::integer_token(&literal, 10)
+        }
+    }
+
+    fn consume_radix_number(&mut self, radix: u32) -> Option<Token> {
+        self.advance_while(&|c| c.is_alphanumeric() || c == '_');
 
-            Some(Token::Inteiro(value))
+        let digits: String = self.current_lexeme.chars().skip(2).collect();
+        let group = digits.trim_start_matches('_');
+        if group.is_empty() {
+            return self.number_error("faltam dígitos depois do prefixo");
+        }
+        if let Some(c) = group.chars().find(|&c| c != '_' && !c.is_digit(radix)) {
+            let reason = format!("'{}' não é um dígito válido na base {}", c, radix);
+            return self.number_error(&reason);
         }
+        if !Self::valid_underscores(group) || digits.starts_with("__") {
+            return self.number_error("uso inválido de '_'");
+        }
+
+        let literal: String = group.chars().filter(|&c| c != '_').collect();
+        Self::integer_token(&literal, radix)
+    }
+
+    fn integer_token(literal: &str, radix: u32) -> Option<Token> {
+        match i64::from_str_radix(literal, radix) {
+            Ok(value) => Some(Token::Inteiro(value)),
+            Err(_) => BigInt::parse_bytes(literal.as_bytes(), radix).map(Token::InteiroGrande),
+        }
+    }
+
+    /// Consumes a group of digits that may be separated by single underscores.
+    /// Returns `false` when the group is empty or the underscores are misplaced.
+    fn consume_digits(&mut self) -> bool {
+        let start = self.current_lexeme.len();
+        self.advance_while(&|c| c.is_ascii_digit() || c == '_');
+        let group = &self.current_lexeme[start..];
+        !group.is_empty() && !group.starts_with('_') && Self::valid_underscores(group)
+    }
+
+    fn consume_digits_after_first(&mut self) -> bool {
+        self.advance_while(&|c| c.is_ascii_digit() || c == '_');
+        Self::valid_underscores(&self.current_lexeme)
+    }
+
+    fn valid_underscores(group: &str) -> bool {
+        !group.ends_with('_') && !group.contains("__")
+    }
+
+    /// A number immediately followed by letters or digits from other scripts
+    /// (`12abc`, `0x1G`, `3٣`) is a malformed literal, not two tokens.
+    fn invalid_number_suffix(&mut self) -> Option<Option<Token>> {
+        if self.peek_check(&|c| c.is_alphanumeric() || c == '_') {
+            self.advance_while(&|c| c.is_alphanumeric() || c == '_');
+            return Some(self.number_error("caracteres inválidos no número"));
+        }
+        None
+    }
+
+    fn number_error(&mut self, reason: &str) -> Option<Token> {
+        self.error(LexErrorKind::InvalidNumber {
+            lexeme: self.current_lexeme.clone(),
+            reason: reason.into(),
+        })
+    }
+
+    /// Records the error; `scan_next` carries on with the next token, so a
+    /// single scan reports every problem in the source.
+    fn error(&mut self, kind: LexErrorKind) -> Option<Token> {
+        self.errors.push(LexError {
+            kind,
+            position: self.start,
+        });
+        None
     }
 
+    /// `\n`, `\t`, `\r`, `\"` and `\\` are escapes; any other backslash is
+    /// kept, so `"\d"` is a backslash and a `d`, as regular expressions need.
     fn consume_string(&mut self) -> Option<Token> {
-        self.advance_while(&|c| c != '"' && c != '\n');
-        let literal: String = self.current_lexeme.chars().skip(1).collect();
-        // TODO: Return error!
-        if !self.advance_if_match('"') {
-            return None;
+        let mut literal = String::new();
+        loop {
+            self.source.reset_peek();
+            match self.source.peek().copied() {
+                None | Some('\n') => return self.error(LexErrorKind::UnterminatedString),
+                Some('"') => {
+                    self.advance();
+                    return Some(Token::Texto(literal));
+                }
+                Some('\\') => {
+                    self.advance();
+                    let escaped = match self.source.peek().copied() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        _ => {
+                            literal.push('\\');
+                            continue;
+                        }
+                    };
+                    self.advance();
+                    literal.push(escaped);
+                }
+                Some(c) => {
+                    self.advance();
+                    literal.push(c);
+                }
+            }
         }
-        Some(Token::Texto(literal))
     }
 
     fn consumer_identifier(&mut self) -> Option<Token> {
-        let is_alpha = |c: char| c.is_alphanumeric();
+        let is_alpha = |c: char| c.is_alphanumeric() || c == '_';
         self.advance_while(&is_alpha);
 
-        match self.current_lexeme.as_ref() {
-            "Verdadeiro" => Some(Token::Logico(true)),
-            "Falso" => Some(Token::Logico(false)),
-            "Vazio" => Some(Token::Vazio),
-            "classe" => Some(Token::Classe),
-            "fun" => Some(Token::Fun),
-            "lista" => Some(Token::Lista),
-            "dicionario" => Some(Token::Dicionario),
-            "tupla" => Some(Token::Tupla),
-            "conjunto" => Some(Token::Conjunto),
-            "imprima" => Some(Token::Imprima),
-            "entrada" => Some(Token::Entrada),
-            "se" => Some(Token::Se),
-            "senao" => Some(Token::SeNao),
-            "ouentaose" => Some(Token::OuEntaoSe),
-            "e" => Some(Token::E),
-            "ou" => Some(Token::Ou),
-            "nao" => Some(Token::Nao),
-            "é" => Some(Token::Is),
-            "remova" => Some(Token::Remova),
-            "interrompa" => Some(Token::Interrompa),
-            "retorne" => Some(Token::Retorne),
-            "continue" => Some(Token::Continue),
-            "paracada" => Some(Token::ParaCada),
-            "enquanto" => Some(Token::Enquanto),
-            "verifique" => Some(Token::Verifique),
-            "passe" => Some(Token::Passe),
-            "tente" => Some(Token::Tente),
-            "exceto" => Some(Token::Exceto),
-            "provoque" => Some(Token::Provoque),
-            "global" => Some(Token::Global),
-            "em" => Some(Token::Em),
-            identifier => Some(Token::Identifier(identifier.into())),
+        let keyword = KEYWORDS
+            .iter()
+            .find(|(keyword, _)| *keyword == self.current_lexeme);
+        match keyword {
+            Some((_, token)) => Some(token.clone()),
+            None => Some(Token::Identifier(self.current_lexeme.clone())),
         }
     }
 }
@@ -213,32 +468,54 @@ pub struct TokenizerIterator<'a> {
 }
 
 impl<'a> Iterator for TokenizerIterator<'a> {
-    type Item = Token;
-
-    fn next(&mut self) -> Option<Token> {
-        self.tokenizer.scan_next()
+    type Item = (Token, Span);
+
+    fn next(&mut self) -> Option<(Token, Span)> {
+        let token = self.tokenizer.scan_next()?;
+        let span = Span {
+            start: self.tokenizer.start,
+            end: Position {
+                line: self.tokenizer.line,
+                column: self.tokenizer.column,
+            },
+        };
+        Some((token, span))
     }
 }
 
-fn tokenizer_into_iterator<'a>(source: &'a str) -> impl Iterator<Item = Token> + 'a {
-    TokenizerIterator {
-        tokenizer: Tokenizer::init(source),
-    }
+/// The tokens the parser sees, without comments, or every error in the
+/// source.
+pub fn scan(source: &str) -> Result<Vec<Token>, Vec<LexError>> {
+    let tokens = scan_spanned(source)?
+        .into_iter()
+        .map(|(token, _)| token)
+        .filter(|token| !matches!(token, Token::Comment(_)))
+        .collect();
+    Ok(tokens)
 }
 
-pub(crate) fn scan(source: &str) -> Vec<Token> {
+/// The tokens of the source code, comments included, and where each one is.
+pub fn scan_spanned(source: &str) -> Result<Vec<(Token, Span)>, Vec<LexError>> {
     let mut tokens = Vec::new();
+    let mut iterator = TokenizerIterator {
+        tokenizer: Tokenizer::init(source),
+    };
 
-    for token in tokenizer_into_iterator(source) {
-        match token {
-            Token::WhiteSpace | Token::Comment => {}
-            _ => tokens.push(token),
+    for (token, span) in iterator.by_ref() {
+        if token != Token::WhiteSpace {
+            tokens.push((token, span));
         }
     }
 
-    tokens
+    let errors = iterator.tokenizer.errors;
+    if errors.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(errors)
+    }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -282,7 +559,10 @@ mod tests {
     fn test_scan_next_comment_cases() {
         let mut tokenizer = Tokenizer::init("# isto é um comentario");
 
-        assert_eq!(Some(Token::Comment), tokenizer.scan_next());
+        assert_eq!(
+            Some(Token::Comment(" isto é um comentario".into())),
+            tokenizer.scan_next()
+        );
         assert_eq!(None, tokenizer.scan_next())
     }
 
@@ -296,12 +576,46 @@ mod tests {
         assert_eq!(Some(Token::LessThan), tokenizer.scan_next());
     }
 
+    #[test]
+    fn test_scan_next_floor_div_and_power() {
+        let mut tokenizer = Tokenizer::init("// ** / *");
+
+        assert_eq!(Some(Token::SlashSlash), tokenizer.scan_next());
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(Some(Token::StarStar), tokenizer.scan_next());
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(Some(Token::Slash), tokenizer.scan_next());
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(Some(Token::Star), tokenizer.scan_next());
+    }
+
     #[test]
     fn test_scan_next_strings() {
         let mut tokenizer = Tokenizer::init(r#""coisei""#);
         assert_eq!(Some(Token::Texto("coisei".into())), tokenizer.scan_next())
     }
 
+    #[test]
+    fn test_scan_next_string_escapes() {
+        let mut tokenizer = Tokenizer::init(r#""a\nb\t\"c\"\\d\e" "\""#);
+        assert_eq!(
+            Some(Token::Texto("a\nb\t\"c\"\\d\\e".into())),
+            tokenizer.scan_next()
+        );
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(None, tokenizer.scan_next());
+        assert_eq!(
+            &[LexError {
+                kind: LexErrorKind::UnterminatedString,
+                position: Position {
+                    line: 1,
+                    column: 20
+                },
+            }],
+            tokenizer.errors.as_slice()
+        )
+    }
+
     #[test]
     fn test_scan_next_integers() {
         let mut tokenizer = Tokenizer::init("123");
@@ -312,9 +626,142 @@ mod tests {
     fn test_scan_next_float() {
         let mut tokenizer = Tokenizer::init("199.00");
         assert_eq!(Some(Token::Real(199.00)), tokenizer.scan_next());
-        //        FIXME: I can have .199 float values
-        //        let mut tokenizer = Tokenizer::init(".199");
-        //        assert_eq!(Some(Token::Real(0.199)), tokenizer.scan_next())
+        let mut tokenizer = Tokenizer::init(".199");
+        assert_eq!(Some(Token::Real(0.199)), tokenizer.scan_next());
+        let mut tokenizer = Tokenizer::init("5.");
+        assert_eq!(Some(Token::Real(5.0)), tokenizer.scan_next())
+    }
+
+    #[test]
+    fn test_scan_next_float_exponent() {
+        let mut tokenizer = Tokenizer::init("1e-3 2.5E+2 1.e2 3e4");
+        assert_eq!(Some(Token::Real(0.001)), tokenizer.scan_next());
+        tokenizer.scan_next();
+        assert_eq!(Some(Token::Real(250.0)), tokenizer.scan_next());
+        tokenizer.scan_next();
+        assert_eq!(Some(Token::Real(100.0)), tokenizer.scan_next());
+        tokenizer.scan_next();
+        assert_eq!(Some(Token::Real(30000.0)), tokenizer.scan_next());
+        assert!(tokenizer.errors.is_empty())
+    }
+
+    #[test]
+    fn test_scan_next_integer_bases() {
+        let mut tokenizer = Tokenizer::init("0x1F 0o17 0b1010 0X_ff");
+        assert_eq!(Some(Token::Inteiro(31)), tokenizer.scan_next());
+        tokenizer.scan_next();
+        assert_eq!(Some(Token::Inteiro(15)), tokenizer.scan_next());
+        tokenizer.scan_next();
+        assert_eq!(Some(Token::Inteiro(10)), tokenizer.scan_next());
+        tokenizer.scan_next();
+        assert_eq!(Some(Token::Inteiro(255)), tokenizer.scan_next());
+        assert!(tokenizer.errors.is_empty())
+    }
+
+    #[test]
+    fn test_scan_next_underscores() {
+        let mut tokenizer = Tokenizer::init("1_000_000 2.718_5 0b1111_0000");
+        assert_eq!(Some(Token::Inteiro(1_000_000)), tokenizer.scan_next());
+        tokenizer.scan_next();
+        assert_eq!(Some(Token::Real(2.718_5)), tokenizer.scan_next());
+        tokenizer.scan_next();
+        assert_eq!(Some(Token::Inteiro(0b1111_0000)), tokenizer.scan_next());
+        assert!(tokenizer.errors.is_empty())
+    }
+
+    #[test]
+    fn test_scan_next_big_integer() {
+        let mut tokenizer = Tokenizer::init("99999999999999999999");
+        assert_eq!(
+            Some(Token::InteiroGrande(
+                "99999999999999999999".parse().unwrap()
+            )),
+            tokenizer.scan_next()
+        )
+    }
+
+    #[test]
+    fn test_scan_next_malformed_numbers() {
+        for source in &[
+            "1__0", "1_", "0x", "0b102", "0o8", "1e", "2.5e+", "12abc", "1._5",
+        ] {
+            let mut tokenizer = Tokenizer::init(source);
+            assert_eq!(None, tokenizer.scan_next(), "{}", source);
+            match tokenizer.errors.as_slice() {
+                [LexError {
+                    kind: LexErrorKind::InvalidNumber { lexeme, .. },
+                    ..
+                }] => assert_eq!(source, lexeme),
+                errors => panic!("{}: {:?}", source, errors),
+            }
+        }
+    }
+
+    #[test]
+    fn test_scan_next_unexpected_characters() {
+        let mut tokenizer = Tokenizer::init("1 $\n  ; a");
+        assert_eq!(Some(Token::Inteiro(1)), tokenizer.scan_next());
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(Some(Token::Identifier("a".into())), tokenizer.scan_next());
+        assert_eq!(Position { line: 2, column: 5 }, tokenizer.start);
+        assert_eq!(
+            &[
+                LexError {
+                    kind: LexErrorKind::UnexpectedCharacter('$'),
+                    position: Position { line: 1, column: 3 },
+                },
+                LexError {
+                    kind: LexErrorKind::UnexpectedCharacter(';'),
+                    position: Position { line: 2, column: 3 },
+                },
+            ],
+            tokenizer.errors.as_slice()
+        )
+    }
+
+    #[test]
+    fn test_scan_next_unterminated_string() {
+        let mut tokenizer = Tokenizer::init("\"oi\n1");
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(Some(Token::Inteiro(1)), tokenizer.scan_next());
+        assert_eq!(
+            &[LexError {
+                kind: LexErrorKind::UnterminatedString,
+                position: Position { line: 1, column: 1 },
+            }],
+            tokenizer.errors.as_slice()
+        )
+    }
+
+    #[test]
+    fn test_scan_next_identifier_with_underscore() {
+        let mut tokenizer = Tokenizer::init("_minha_variavel2");
+        assert_eq!(
+            Some(Token::Identifier("_minha_variavel2".into())),
+            tokenizer.scan_next()
+        )
+    }
+
+    #[test]
+    fn test_scan_next_non_ascii_digits() {
+        let mut tokenizer = Tokenizer::init("١٢ + 3٣");
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(Some(Token::Plus), tokenizer.scan_next());
+        assert_eq!(Some(Token::WhiteSpace), tokenizer.scan_next());
+        assert_eq!(None, tokenizer.scan_next());
+        assert_eq!(2, tokenizer.errors.len())
+    }
+
+    #[test]
+    fn test_scan_next_skips_malformed_number() {
+        let mut tokenizer = Tokenizer::init("0b2+1");
+        assert_eq!(Some(Token::Plus), tokenizer.scan_next());
+        assert_eq!(Some(Token::Inteiro(1)), tokenizer.scan_next());
+        assert_eq!(1, tokenizer.errors.len())
     }
 
     #[test]
@@ -457,6 +904,30 @@ mod tests {
         assert_eq!(Some(Token::Global), tokenizer.scan_next())
     }
 
+    #[test]
+    fn test_scan_next_keywords_import_from_as_identifier() {
+        let mut tokenizer = Tokenizer::init("importe");
+        assert_eq!(Some(Token::Importe), tokenizer.scan_next());
+
+        let mut tokenizer = Tokenizer::init("de");
+        assert_eq!(Some(Token::De), tokenizer.scan_next());
+
+        let mut tokenizer = Tokenizer::init("como");
+        assert_eq!(Some(Token::Como), tokenizer.scan_next())
+    }
+
+    #[test]
+    fn test_scan_next_keywords_with() {
+        let mut tokenizer = Tokenizer::init("com");
+        assert_eq!(Some(Token::Com), tokenizer.scan_next());
+
+        let mut tokenizer = Tokenizer::init("comeco");
+        assert_eq!(
+            Some(Token::Identifier("comeco".into())),
+            tokenizer.scan_next()
+        )
+    }
+
     #[test]
     fn test_scan_next_variable_identifier() {
         let mut tokenizer = Tokenizer::init("num = 0");
@@ -464,13 +935,14 @@ mod tests {
     }
 }
 
+#[cfg(test)]
 mod scan {
     use super::*;
 
     #[test]
     fn test_scan() {
         let source = "se Verdadeiro:";
-        let tokens = scan(source);
+        let tokens = scan(source).unwrap();
 
         assert_eq!(3, tokens.len());
         assert_eq!(Token::Se, tokens[0]);
@@ -483,7 +955,7 @@ mod scan {
         let source = r#"se Verdadeiro:
             imprima("oi")
         "#;
-        let tokens = scan(source);
+        let tokens = scan(source).unwrap();
         assert_eq!(7, tokens.len());
         assert_eq!(Token::Se, tokens[0]);
         assert_eq!(Token::Logico(true), tokens[1]);
@@ -494,13 +966,35 @@ mod scan {
         assert_eq!(Token::ParentClose, tokens[6]);
     }
 
+    #[test]
+    fn test_scan_survives_long_runs_of_errors() {
+        let errors = scan(&"@".repeat(100_000)).unwrap_err();
+        assert_eq!(100_000, errors.len());
+    }
+
+    #[test]
+    fn test_scan_reports_every_error() {
+        let errors = scan("imprima(@) & 0b2").unwrap_err();
+
+        assert_eq!(3, errors.len());
+        assert_eq!(LexErrorKind::UnexpectedCharacter('@'), errors[0].kind);
+        assert_eq!(LexErrorKind::UnexpectedCharacter('&'), errors[1].kind);
+        assert_eq!(
+            Position {
+                line: 1,
+                column: 14
+            },
+            errors[2].position
+        );
+    }
+
     #[test]
     fn test_scan_code_with_more_lines_should_ignore_comments() {
         let source = r#"
         se Verdadeiro: # Devo ignorar este comentario
             imprima("oi")
         "#;
-        let tokens = scan(source);
+        let tokens = scan(source).unwrap();
         assert_eq!(7, tokens.len());
         assert_eq!(Token::Se, tokens[0]);
         assert_eq!(Token::Logico(true), tokens[1]);
@@ -510,4 +1004,21 @@ mod scan {
         assert_eq!(Token::Texto("oi".into()), tokens[5]);
         assert_eq!(Token::ParentClose, tokens[6]);
     }
+
+    #[test]
+    fn test_scan_augmented_assignment() {
+        let tokens = scan("+= -= *= /= //= %= **=").unwrap();
+        assert_eq!(
+            vec![
+                Token::PlusEqual,
+                Token::MinusEqual,
+                Token::StarEqual,
+                Token::SlashEqual,
+                Token::SlashSlashEqual,
+                Token::PercentEqual,
+                Token::StarStarEqual,
+            ],
+            tokens
+        );
+    }
 }
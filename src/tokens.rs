@@ -11,24 +11,16 @@ impl Tokens {
         Self { data, pos: 0 }
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
-    }
-
     pub fn pos(&self) -> usize {
         self.pos
     }
 
-    pub fn len(&self) -> usize {
-        self.data.len()
-    }
-
     pub fn current(&self) -> Option<Token> {
-        self.data.get(self.pos()).cloned().map(|s| s)
+        self.data.get(self.pos()).cloned()
     }
 
     pub fn peek_ahead(&self, pos: usize) -> Option<Token> {
-        self.data.get(self.pos + pos).cloned().map(|s| s)
+        self.data.get(self.pos + pos).cloned()
     }
 
     pub fn peek(&self) -> Option<Token> {
@@ -38,9 +30,4 @@ impl Tokens {
     pub fn advance(&mut self) {
         self.pos += 1
     }
-
-    pub fn next_token(&mut self) -> Option<Token> {
-        self.pos += 1;
-        self.current()
-    }
 }
\ No newline at end of file
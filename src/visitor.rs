@@ -0,0 +1,396 @@
+//! A read-only traversal of the syntax tree.
+//!
+//! `Visitor` walks the tree by reference and has one method per node, so a
+//! pass only overrides the nodes it cares about; the default of each method is
+//! the `walk_*` function of the same node, which visits the children.
+
+use crate::ast::{Alias, BooleanOperation, Comparison, Expression, Keyword, Number, Operator};
+use crate::ast::{Program, Statement, UnaryOperation, WithItem};
+
+pub(crate) trait Visitor: Sized {
+    type Output;
+
+    /// What a node produces when its method is not overridden.
+    fn default_output(&mut self) -> Self::Output;
+
+    fn visit_program(&mut self, program: &Program) -> Self::Output {
+        walk_program(self, program)
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) -> Self::Output {
+        walk_statement(self, statement)
+    }
+
+    fn visit_break(&mut self) -> Self::Output {
+        self.default_output()
+    }
+
+    fn visit_continue(&mut self) -> Self::Output {
+        self.default_output()
+    }
+
+    fn visit_pass(&mut self) -> Self::Output {
+        self.default_output()
+    }
+
+    fn visit_return(&mut self, value: Option<&[Expression]>) -> Self::Output {
+        walk_return(self, value)
+    }
+
+    fn visit_assert(&mut self, test: &Expression, msg: Option<&Expression>) -> Self::Output {
+        walk_assert(self, test, msg)
+    }
+
+    fn visit_assign(&mut self, targets: &[Expression], value: &Expression) -> Self::Output {
+        walk_assign(self, targets, value)
+    }
+
+    fn visit_aug_assign(
+        &mut self,
+        target: &Expression,
+        op: Operator,
+        value: &Expression,
+    ) -> Self::Output {
+        walk_aug_assign(self, target, op, value)
+    }
+
+    fn visit_expr_statement(&mut self, expression: &Expression) -> Self::Output {
+        walk_expr_statement(self, expression)
+    }
+
+    fn visit_import(&mut self, _names: &[Alias]) -> Self::Output {
+        self.default_output()
+    }
+
+    fn visit_import_from(&mut self, _module: &str, _names: &[Alias]) -> Self::Output {
+        self.default_output()
+    }
+
+    fn visit_with(&mut self, items: &[WithItem], body: &Statement) -> Self::Output {
+        walk_with(self, items, body)
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) -> Self::Output {
+        walk_expression(self, expression)
+    }
+
+    fn visit_compare(&mut self, a: &Expression, op: Comparison, b: &Expression) -> Self::Output {
+        walk_compare(self, a, op, b)
+    }
+
+    fn visit_bool_op(
+        &mut self,
+        a: &Expression,
+        op: BooleanOperation,
+        b: &Expression,
+    ) -> Self::Output {
+        walk_bool_op(self, a, op, b)
+    }
+
+    fn visit_bin_op(&mut self, a: &Expression, op: Operator, b: &Expression) -> Self::Output {
+        walk_bin_op(self, a, op, b)
+    }
+
+    fn visit_un_op(&mut self, op: UnaryOperation, a: &Expression) -> Self::Output {
+        walk_un_op(self, op, a)
+    }
+
+    fn visit_str(&mut self, _value: &str) -> Self::Output {
+        self.default_output()
+    }
+
+    fn visit_num(&mut self, _value: &Number) -> Self::Output {
+        self.default_output()
+    }
+
+    fn visit_if_expression(
+        &mut self,
+        test: &Expression,
+        body: &Expression,
+        orelse: &Expression,
+    ) -> Self::Output {
+        walk_if_expression(self, test, body, orelse)
+    }
+
+    fn visit_call(
+        &mut self,
+        function: &Expression,
+        args: &[Expression],
+        keywords: &[Keyword],
+    ) -> Self::Output {
+        walk_call(self, function, args, keywords)
+    }
+
+    fn visit_keyword(&mut self, keyword: &Keyword) -> Self::Output {
+        walk_keyword(self, keyword)
+    }
+
+    fn visit_identifier(&mut self, _name: &str) -> Self::Output {
+        self.default_output()
+    }
+
+    fn visit_tuple(&mut self, elements: &[Expression]) -> Self::Output {
+        walk_tuple(self, elements)
+    }
+
+    fn visit_subscript(&mut self, value: &Expression, index: &Expression) -> Self::Output {
+        walk_subscript(self, value, index)
+    }
+
+    fn visit_attribute(&mut self, value: &Expression, name: &str) -> Self::Output {
+        walk_attribute(self, value, name)
+    }
+
+    fn visit_true(&mut self) -> Self::Output {
+        self.default_output()
+    }
+
+    fn visit_false(&mut self) -> Self::Output {
+        self.default_output()
+    }
+
+    fn visit_none(&mut self) -> Self::Output {
+        self.default_output()
+    }
+}
+
+pub(crate) fn walk_program<V: Visitor>(visitor: &mut V, program: &Program) -> V::Output {
+    for statement in &program.statements {
+        visitor.visit_statement(statement);
+    }
+    visitor.default_output()
+}
+
+/// Calls the method of the kind of statement.
+pub(crate) fn walk_statement<V: Visitor>(visitor: &mut V, statement: &Statement) -> V::Output {
+    match statement {
+        Statement::Break => visitor.visit_break(),
+        Statement::Continue => visitor.visit_continue(),
+        Statement::Pass => visitor.visit_pass(),
+        Statement::Return { value } => visitor.visit_return(value.as_deref()),
+        Statement::Assert { test, msg } => visitor.visit_assert(test, msg.as_ref()),
+        Statement::Assign { targets, value } => visitor.visit_assign(targets, value),
+        Statement::AugAssign { target, op, value } => visitor.visit_aug_assign(target, *op, value),
+        Statement::Expr { expression } => visitor.visit_expr_statement(expression),
+        Statement::Import { names } => visitor.visit_import(names),
+        Statement::ImportFrom { module, names } => visitor.visit_import_from(module, names),
+        Statement::With { items, body } => visitor.visit_with(items, body),
+    }
+}
+
+pub(crate) fn walk_return<V: Visitor>(visitor: &mut V, value: Option<&[Expression]>) -> V::Output {
+    for expression in value.unwrap_or_default() {
+        visitor.visit_expression(expression);
+    }
+    visitor.default_output()
+}
+
+pub(crate) fn walk_assert<V: Visitor>(
+    visitor: &mut V,
+    test: &Expression,
+    msg: Option<&Expression>,
+) -> V::Output {
+    visitor.visit_expression(test);
+    if let Some(msg) = msg {
+        visitor.visit_expression(msg);
+    }
+    visitor.default_output()
+}
+
+pub(crate) fn walk_assign<V: Visitor>(
+    visitor: &mut V,
+    targets: &[Expression],
+    value: &Expression,
+) -> V::Output {
+    for target in targets {
+        visitor.visit_expression(target);
+    }
+    visitor.visit_expression(value);
+    visitor.default_output()
+}
+
+pub(crate) fn walk_aug_assign<V: Visitor>(
+    visitor: &mut V,
+    target: &Expression,
+    _op: Operator,
+    value: &Expression,
+) -> V::Output {
+    visitor.visit_expression(target);
+    visitor.visit_expression(value);
+    visitor.default_output()
+}
+
+pub(crate) fn walk_expr_statement<V: Visitor>(
+    visitor: &mut V,
+    expression: &Expression,
+) -> V::Output {
+    visitor.visit_expression(expression)
+}
+
+pub(crate) fn walk_with<V: Visitor>(
+    visitor: &mut V,
+    items: &[WithItem],
+    body: &Statement,
+) -> V::Output {
+    for item in items {
+        visitor.visit_expression(&item.context_expr);
+        if let Some(target) = &item.optional_vars {
+            visitor.visit_expression(target);
+        }
+    }
+    visitor.visit_statement(body);
+    visitor.default_output()
+}
+
+/// Calls the method of the kind of expression.
+pub(crate) fn walk_expression<V: Visitor>(visitor: &mut V, expression: &Expression) -> V::Output {
+    match expression {
+        Expression::Compare { a, op, b } => visitor.visit_compare(a, *op, b),
+        Expression::BoolOp { a, op, b } => visitor.visit_bool_op(a, *op, b),
+        Expression::BinOp { a, op, b } => visitor.visit_bin_op(a, *op, b),
+        Expression::UnOp { op, a } => visitor.visit_un_op(*op, a),
+        Expression::Str { value } => visitor.visit_str(value),
+        Expression::Num { value } => visitor.visit_num(value),
+        Expression::IfExpression { test, body, orelse } => {
+            visitor.visit_if_expression(test, body, orelse)
+        }
+        Expression::Call {
+            function,
+            args,
+            keywords,
+        } => visitor.visit_call(function, args, keywords),
+        Expression::Identifier { name } => visitor.visit_identifier(name),
+        Expression::Tuple { elements } => visitor.visit_tuple(elements),
+        Expression::Subscript { value, index } => visitor.visit_subscript(value, index),
+        Expression::Attribute { value, name } => visitor.visit_attribute(value, name),
+        Expression::True => visitor.visit_true(),
+        Expression::False => visitor.visit_false(),
+        Expression::None => visitor.visit_none(),
+    }
+}
+
+pub(crate) fn walk_compare<V: Visitor>(
+    visitor: &mut V,
+    a: &Expression,
+    _op: Comparison,
+    b: &Expression,
+) -> V::Output {
+    visitor.visit_expression(a);
+    visitor.visit_expression(b);
+    visitor.default_output()
+}
+
+pub(crate) fn walk_bool_op<V: Visitor>(
+    visitor: &mut V,
+    a: &Expression,
+    _op: BooleanOperation,
+    b: &Expression,
+) -> V::Output {
+    visitor.visit_expression(a);
+    visitor.visit_expression(b);
+    visitor.default_output()
+}
+
+pub(crate) fn walk_bin_op<V: Visitor>(
+    visitor: &mut V,
+    a: &Expression,
+    _op: Operator,
+    b: &Expression,
+) -> V::Output {
+    visitor.visit_expression(a);
+    visitor.visit_expression(b);
+    visitor.default_output()
+}
+
+pub(crate) fn walk_un_op<V: Visitor>(
+    visitor: &mut V,
+    _op: UnaryOperation,
+    a: &Expression,
+) -> V::Output {
+    visitor.visit_expression(a);
+    visitor.default_output()
+}
+
+pub(crate) fn walk_if_expression<V: Visitor>(
+    visitor: &mut V,
+    test: &Expression,
+    body: &Expression,
+    orelse: &Expression,
+) -> V::Output {
+    visitor.visit_expression(test);
+    visitor.visit_expression(body);
+    visitor.visit_expression(orelse);
+    visitor.default_output()
+}
+
+pub(crate) fn walk_call<V: Visitor>(
+    visitor: &mut V,
+    function: &Expression,
+    args: &[Expression],
+    keywords: &[Keyword],
+) -> V::Output {
+    visitor.visit_expression(function);
+    for arg in args {
+        visitor.visit_expression(arg);
+    }
+    for keyword in keywords {
+        visitor.visit_keyword(keyword);
+    }
+    visitor.default_output()
+}
+
+pub(crate) fn walk_keyword<V: Visitor>(visitor: &mut V, keyword: &Keyword) -> V::Output {
+    visitor.visit_expression(&keyword.value)
+}
+
+pub(crate) fn walk_tuple<V: Visitor>(visitor: &mut V, elements: &[Expression]) -> V::Output {
+    for element in elements {
+        visitor.visit_expression(element);
+    }
+    visitor.default_output()
+}
+
+pub(crate) fn walk_subscript<V: Visitor>(
+    visitor: &mut V,
+    value: &Expression,
+    index: &Expression,
+) -> V::Output {
+    visitor.visit_expression(value);
+    visitor.visit_expression(index);
+    visitor.default_output()
+}
+
+pub(crate) fn walk_attribute<V: Visitor>(
+    visitor: &mut V,
+    value: &Expression,
+    _name: &str,
+) -> V::Output {
+    visitor.visit_expression(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_program;
+
+    /// Collects every identifier, overriding a single node.
+    struct Names(Vec<String>);
+
+    impl Visitor for Names {
+        type Output = ();
+
+        fn default_output(&mut self) {}
+
+        fn visit_identifier(&mut self, name: &str) {
+            self.0.push(name.into());
+        }
+    }
+
+    #[test]
+    fn visitor_reaches_every_identifier() {
+        let program = parse_program("a, b = se c: f(x, y=z) + t[i].w senao: -d").unwrap();
+        let mut names = Names(Vec::new());
+        names.visit_program(&program);
+        assert_eq!(vec!["a", "b", "c", "f", "x", "z", "t", "i", "d"], names.0);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::ast::Program;
+use crate::compiler::{compile, Chunk, Instruction};
+use crate::error::Error;
+use crate::importer::{import_name, Importer};
+use crate::object::Object;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+/// A stack machine running the bytecode produced by `compiler::compile`.
+///
+/// Variables live in slots while a chunk runs; they are loaded from the
+/// globals before it starts and written back when it stops, even on error.
+#[derive(Debug)]
+pub(crate) struct Vm {
+    globals: RefCell<HashMap<String, Object>>,
+    importer: Rc<Importer>,
+}
+
+impl Vm {
+    pub fn with_importer(importer: Rc<Importer>) -> Self {
+        Self {
+            globals: RefCell::new(importer.builtins()),
+            importer,
+        }
+    }
+
+    pub fn eval(&self, program: Program) -> Result<Object> {
+        self.run(&compile(program))
+    }
+
+    pub fn set_global(&self, name: &str, value: Object) {
+        self.globals.borrow_mut().insert(name.into(), value);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Object> {
+        self.globals.borrow().get(name).cloned()
+    }
+
+    pub fn globals(&self) -> HashMap<String, Object> {
+        self.globals.borrow().clone()
+    }
+
+    pub(crate) fn run(&self, chunk: &Chunk) -> Result<Object> {
+        let mut slots: Vec<Option<Object>> = {
+            let globals = self.globals.borrow();
+            chunk
+                .names
+                .iter()
+                .map(|name| globals.get(name).cloned())
+                .collect()
+        };
+
+        let mut contexts = Vec::new();
+        let result = self.execute(chunk, &mut slots, &mut contexts);
+        // an error inside `com` still closes what it entered, innermost first;
+        // the first error is the one reported
+        for context in contexts.into_iter().rev() {
+            let _ = context.exit();
+        }
+
+        let mut globals = self.globals.borrow_mut();
+        for (name, value) in chunk.names.iter().zip(slots) {
+            if let Some(value) = value {
+                globals.insert(name.clone(), value);
+            }
+        }
+        result
+    }
+
+    /// Runs the code, pushing onto `contexts` the values of the `com`
+    /// statements it is inside of.
+    fn execute(
+        &self,
+        chunk: &Chunk,
+        slots: &mut [Option<Object>],
+        contexts: &mut Vec<Object>,
+    ) -> Result<Object> {
+        let mut stack: Vec<Object> = Vec::new();
+        let mut result = Object::Unit;
+        let mut ip = 0;
+
+        while let Some(&instruction) = chunk.code.get(ip) {
+            ip += 1;
+            match instruction {
+                Instruction::LoadConst(index) => stack.push(chunk.constants[index].clone()),
+                Instruction::LoadName(slot) => match &slots[slot] {
+                    Some(value) => stack.push(value.clone()),
+                    None => return Err(Error::UndefinedName(chunk.names[slot].clone())),
+                },
+                Instruction::StoreName(slot) => slots[slot] = Some(pop(&mut stack)),
+                Instruction::Dup => {
+                    let top = stack.last().cloned().expect("pilha vazia");
+                    stack.push(top);
+                }
+                Instruction::Swap => {
+                    let len = stack.len();
+                    stack.swap(len - 1, len - 2);
+                }
+                Instruction::Binary(op) => {
+                    let b = pop(&mut stack);
+                    let a = pop(&mut stack);
+                    stack.push(a.binary(op, &b)?);
+                }
+                Instruction::Compare(op) => {
+                    let b = pop(&mut stack);
+                    let a = pop(&mut stack);
+                    stack.push(a.compare(op, &b)?);
+                }
+                Instruction::Boolean(op) => {
+                    let b = pop(&mut stack);
+                    let a = pop(&mut stack);
+                    stack.push(a.boolean(op, &b)?);
+                }
+                Instruction::Unary(op) => {
+                    let a = pop(&mut stack);
+                    stack.push(a.unary(op)?);
+                }
+                Instruction::BuildTuple(count) => {
+                    let elements = stack.split_off(stack.len() - count);
+                    stack.push(Object::Tuple(elements));
+                }
+                Instruction::Unpack(count) => {
+                    let values = pop(&mut stack).unpack(count)?;
+                    stack.extend(values.into_iter().rev());
+                }
+                Instruction::Subscript => {
+                    let index = pop(&mut stack);
+                    let value = pop(&mut stack);
+                    stack.push(value.subscript(&index)?);
+                }
+                Instruction::LoadAttribute(name) => {
+                    let object = pop(&mut stack);
+                    stack.push(object.get_attribute(&chunk.attributes[name])?);
+                }
+                Instruction::StoreAttribute(name) => {
+                    let object = pop(&mut stack);
+                    let value = pop(&mut stack);
+                    object.set_attribute(&chunk.attributes[name], value)?;
+                }
+                Instruction::Call(count, names) => {
+                    let names = &chunk.keywords[names];
+                    let values = stack.split_off(stack.len() - names.len());
+                    let keywords = names.iter().cloned().zip(values).collect();
+                    let args = stack.split_off(stack.len() - count);
+                    let function = pop(&mut stack);
+                    stack.push(function.call(args, keywords)?);
+                }
+                Instruction::Import(module) => {
+                    let module = self.importer.import(&chunk.modules[module])?;
+                    stack.push(module.into());
+                }
+                Instruction::ImportFrom(module, name) => {
+                    let module = self.importer.import(&chunk.modules[module])?;
+                    stack.push(import_name(&module, &chunk.attributes[name])?);
+                }
+                Instruction::EnterWith => {
+                    let context = pop(&mut stack);
+                    context.enter()?;
+                    contexts.push(context);
+                }
+                Instruction::ExitWith => {
+                    let context = contexts.pop().expect("nenhum contexto");
+                    context.exit()?;
+                }
+                Instruction::Jump(target) => ip = target,
+                Instruction::JumpIfFalse(target) => {
+                    if !pop(&mut stack).test()? {
+                        ip = target;
+                    }
+                }
+                Instruction::SetResult => result = pop(&mut stack),
+                Instruction::ClearResult => result = Object::Unit,
+                Instruction::Fail(message) => {
+                    return Err(Error::OtherError(chunk.failures[message].clone()))
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+fn pop(stack: &mut Vec<Object>) -> Object {
+    stack.pop().expect("pilha vazia")
+}
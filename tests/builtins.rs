@@ -0,0 +1,264 @@
+use apl::{Backend, Engine, EngineError, NativeFunction, Object};
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+const BACKENDS: &[Backend] = &[Backend::TreeWalker, Backend::Bytecode];
+
+/// What the expression evaluates to, shown the way `imprima` would, on both
+/// backends.
+fn eval(source: &str) -> String {
+    let results: Vec<String> = BACKENDS
+        .iter()
+        .map(
+            |&backend| match Engine::with_backend(backend).eval_expr(source) {
+                Ok(value) => value.to_string(),
+                Err(error) => panic!("{}: {:?}", source, error),
+            },
+        )
+        .collect();
+    assert_eq!(results[0], results[1], "{}", source);
+    results[0].clone()
+}
+
+fn error(source: &str) -> String {
+    match Engine::new().eval_expr(source) {
+        Err(EngineError::Runtime(error)) => error.to_string(),
+        other => panic!("{}: expected an error, got {:?}", source, other),
+    }
+}
+
+#[derive(Clone, Default)]
+struct Capture(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn types_and_sizes() {
+    assert_eq!("inteiro", eval("tipo(2 ** 100)"));
+    assert_eq!("real", eval("tipo(1.5)"));
+    assert_eq!("tupla", eval("tipo((1, 2))"));
+    assert_eq!("função nativa", eval("tipo(tipo)"));
+    assert_eq!("Vazio", eval("tipo(Vazio)"));
+    assert_eq!("3", eval(r#"tamanho("olá")"#));
+    assert_eq!("2", eval("tamanho((1, (2, 3)))"));
+    assert_eq!("ErroDeTipo: inteiro não tem tamanho", error("tamanho(5)"));
+}
+
+#[test]
+fn conversions() {
+    assert_eq!("-12", eval(r#"inteiro(" -12 ")"#));
+    assert_eq!("255", eval(r#"inteiro("ff", base=16)"#));
+    assert_eq!("-3", eval("inteiro(-3.9)"));
+    assert_eq!("1", eval("inteiro(Verdadeiro)"));
+    assert_eq!("0", eval("inteiro()"));
+    assert_eq!("100000000000000000000", eval("inteiro(1e20)"));
+    assert_eq!("2.5", eval(r#"real("2.5")"#));
+    assert_eq!("-infinito", eval(r#"real("-infinito")"#));
+    assert_eq!("3.0", eval("real(3)"));
+    assert_eq!("(1, \"a\")", eval(r#"texto((1, "a"))"#));
+    assert_eq!("Falso", eval("logico(0.0)"));
+    assert_eq!("Verdadeiro", eval(r#"logico("0")"#));
+    assert_eq!("Falso", eval("logico(())"));
+
+    assert_eq!(
+        "ErroDeValor: não é possível converter 'doze' para inteiro",
+        error(r#"inteiro("doze")"#)
+    );
+    assert_eq!(
+        "ErroDeValor: não é possível converter infinito para inteiro",
+        error(r#"inteiro(real("infinito"))"#)
+    );
+    assert_eq!(
+        "ErroDeValor: a base deve estar entre 2 e 36",
+        error(r#"inteiro("1", 1)"#)
+    );
+    assert_eq!(
+        "ErroDeTipo: inteiro() só aceita uma base para textos",
+        error("inteiro(1.5, 2)")
+    );
+    assert_eq!(
+        "ErroDeTipo: real() esperava um número, texto ou logico, não tupla",
+        error("real(())")
+    );
+}
+
+#[test]
+fn numbers() {
+    assert_eq!("9223372036854775808", eval("abs(-9223372036854775807 - 1)"));
+    assert_eq!("1.5", eval("abs(-1.5)"));
+    assert_eq!("2", eval("arredonde(2.5)"));
+    assert_eq!("4", eval("arredonde(3.5)"));
+    assert_eq!("-2", eval("arredonde(-1.6)"));
+    assert_eq!("3.14", eval("arredonde(3.14159, 2)"));
+    assert_eq!("1200", eval("arredonde(1250, -2)"));
+    assert_eq!("1400", eval("arredonde(1350, -2)"));
+    assert_eq!("7", eval("arredonde(7, 3)"));
+    assert_eq!("(9.0, -10.0)", eval("1 // 0.1, -1 // 0.1"));
+    assert_eq!("0.0", eval("arredonde(1e308, -400)"));
+    assert_eq!("-0.0", eval("arredonde(-1e308, -400)"));
+    assert_eq!("1e+308", eval("arredonde(1e308, 400)"));
+    assert_eq!(
+        "(2.67, 0.12, 0.38)",
+        eval("arredonde(2.675, 2), arredonde(0.125, 2), arredonde(0.375, 2)")
+    );
+    assert_eq!(
+        "(20.0, 20.0, 30.0, -20.0)",
+        eval("arredonde(15.0, -1), arredonde(25.0, -1), arredonde(25.5, -1), arredonde(-25.0, -1)")
+    );
+    assert_eq!(
+        "ErroDeValor: arredonde(1.7976931348623157e+308, -308) é grande demais para um real",
+        error("arredonde(1.7976931348623157e308, -308)")
+    );
+    assert_eq!(
+        "ErroDeTipo: abs() esperava um número, não texto",
+        error(r#"abs("1")"#)
+    );
+}
+
+#[test]
+fn aggregates() {
+    assert_eq!("1", eval("min(3, 1, 2)"));
+    assert_eq!("c", eval(r#"max("abc")"#));
+    assert_eq!(
+        "uva",
+        eval(r#"min(("uva", "pera", "kiwi"), chave=tamanho)"#)
+    );
+    assert_eq!(
+        "pera",
+        eval(r#"max(("uva", "pera", "kiwi"), chave=tamanho)"#)
+    );
+    assert_eq!("6", eval("soma((1, 2, 3))"));
+    assert_eq!("10.5", eval("soma((1, 2, 3), 4.5)"));
+    assert_eq!("Verdadeiro", eval("qualquer((0, Vazio, 3))"));
+    assert_eq!("Falso", eval("todos((1, \"\"))"));
+    assert_eq!("Verdadeiro", eval("todos(())"));
+    assert_eq!(
+        "ErroDeValor: max() recebeu um iterável vazio",
+        error("max(())")
+    );
+    assert_eq!("ErroDeTipo: inteiro não é iterável", error("soma(3)"));
+}
+
+#[test]
+fn sequences() {
+    assert_eq!("(1, 2, 3)", eval("ordenado((3, 1, 2))"));
+    assert_eq!(
+        "(\"uva\", \"pera\", \"kiwi\")",
+        eval(r#"ordenado(("pera", "uva", "kiwi"), invertido=Verdadeiro)"#)
+    );
+    assert_eq!(
+        "(\"uva\", \"kiwi\", \"pera\")",
+        eval(r#"ordenado(("kiwi", "uva", "pera"), chave=tamanho)"#)
+    );
+    assert_eq!("(3, 2, 1)", eval("invertido((1, 2, 3))"));
+    assert_eq!("(0, 1, 2)", eval("intervalo(3)"));
+    assert_eq!("(10, 7, 4)", eval("intervalo(10, 2, -3)"));
+    assert_eq!("()", eval("intervalo(5, 1)"));
+    assert_eq!("((1, \"a\"), (2, \"b\"))", eval(r#"enumere("ab", 1)"#));
+    assert_eq!("((1, \"a\"), (2, \"b\"))", eval(r#"zipe((1, 2, 3), "ab")"#));
+    assert_eq!("(\"1\", \"2\")", eval("mapeie(texto, (1, 2))"));
+    assert_eq!("(1, 5)", eval("mapeie(min, (1, 7), (3, 5))"));
+    assert_eq!("(1, \"a\")", eval(r#"filtre(Vazio, (0, 1, "", "a"))"#));
+    assert_eq!("((1, 2),)", eval(r#"filtre(tamanho, ((), (1, 2), ""))"#));
+
+    assert_eq!(
+        "ErroDeValor: o passo de intervalo() não pode ser zero",
+        error("intervalo(1, 5, 0)")
+    );
+    assert_eq!(
+        "ErroDeValor: intervalo() teria 10000000000 números, mais que o máximo de 10000000",
+        error("intervalo(0, 10000000000)")
+    );
+    assert_eq!("(4, 2)", eval("intervalo(4, 1, -2)"));
+    assert_eq!(
+        "ErroDeTipo: operação '<' inválida entre 'a' e 1",
+        error(r#"ordenado((1, "a"))"#)
+    );
+}
+
+#[test]
+fn keywords_do_not_stand_in_for_required_arguments() {
+    assert_eq!(
+        "ErroDeTipo: soma() não recebeu o argumento obrigatório 'iteravel'",
+        error("soma(inicio=1)")
+    );
+    assert_eq!(
+        "ErroDeTipo: enumere() não recebeu o argumento obrigatório 'iteravel'",
+        error("enumere(inicio=1)")
+    );
+    assert_eq!(
+        "ErroDeTipo: arredonde() não recebeu o argumento obrigatório 'numero'",
+        error("arredonde(casas=2)")
+    );
+    assert_eq!(
+        "ErroDeTipo: inteiro() recebeu uma base sem o valor",
+        error("inteiro(base=2)")
+    );
+    assert_eq!(
+        "(2, 2)",
+        eval("soma(inicio=1, iteravel=(1,)), arredonde(casas=0, numero=2)")
+    );
+}
+
+#[test]
+fn errors_show_values_as_written() {
+    assert_eq!(
+        "ErroDeDivisaoPorZero: 9 / 0: divisão por zero",
+        error("9 / 0")
+    );
+    assert_eq!(
+        "ErroDeDivisaoPorZero: 0 ** -1: divisão por zero",
+        error("0 ** -1")
+    );
+    assert_eq!(
+        "ErroDeValor: não é possível converter 'abc' para inteiro",
+        error(r#"inteiro("abc")"#)
+    );
+    assert_eq!(
+        "ErroDeAtributo: 2 não possui o atributo 'x'",
+        error("(2).x")
+    );
+    assert_eq!(
+        "ErroDeTipo: a condição deve ser Verdadeiro ou Falso, não 1",
+        error("se 1: 2 senao: 3")
+    );
+    assert_eq!("Erro: 'f' não é chamável", error(r#""f"()"#));
+}
+
+#[test]
+fn ajuda_describes_functions_and_modules() {
+    let output = Capture::default();
+    let engine = Engine::new().output(output.clone());
+    engine.run_str("ajuda(tamanho) ajuda(3)").unwrap();
+    assert_eq!(
+        "tamanho(objeto)\n    O número de elementos de uma tupla ou de caracteres de um texto.\n\
+         3 é do tipo inteiro\n",
+        String::from_utf8(output.0.borrow().clone()).unwrap()
+    );
+
+    output.0.borrow_mut().clear();
+    let medidas = engine.module("medidas");
+    medidas.set("metro", Object::from(100));
+    medidas.register(NativeFunction::new("km", |_| Ok(Object::Unit)));
+    engine.run_str("ajuda(medidas)").unwrap();
+    assert_eq!(
+        "módulo medidas\nkm()\nmetro = 100\n",
+        String::from_utf8(output.0.borrow().clone()).unwrap()
+    );
+
+    output.0.borrow_mut().clear();
+    engine.run_str("ajuda()").unwrap();
+    let listing = String::from_utf8(output.0.borrow().clone()).unwrap();
+    assert!(listing.starts_with("Funções disponíveis:\n"), "{}", listing);
+    assert!(listing.contains("    ordenado(iteravel, chave=…, invertido=…)\n"));
+}
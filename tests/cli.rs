@@ -0,0 +1,180 @@
+use apl::cli;
+use serde_json::{json, Value};
+
+use std::fs;
+use std::io::prelude::*;
+use std::process::Command;
+
+fn apl(command: &str, args: &[&str]) -> i32 {
+    let mut argv = vec!["apl".to_string(), command.to_string()];
+    argv.extend(args.iter().map(|arg| arg.to_string()));
+    cli::run(&argv)
+}
+
+fn fmt(args: &[&str]) -> i32 {
+    apl("fmt", args)
+}
+
+#[test]
+fn check_reports_unformatted_files() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(file, "x=1 #um").unwrap();
+    let path = file.path().to_str().unwrap();
+
+    assert_eq!(1, fmt(&["--check", path]));
+    assert_eq!("x=1 #um", fs::read_to_string(path).unwrap());
+
+    assert_eq!(0, fmt(&[path]));
+    assert_eq!("x = 1  # um\n", fs::read_to_string(path).unwrap());
+    assert_eq!(0, fmt(&["--check", path]));
+}
+
+#[test]
+fn errors_leave_the_file_alone() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(file, "x = (1").unwrap();
+    let path = file.path().to_str().unwrap();
+
+    assert_eq!(1, fmt(&[path]));
+    assert_eq!("x = (1", fs::read_to_string(path).unwrap());
+    assert_eq!(1, fmt(&["nao/existe.apl"]));
+    assert_eq!(2, fmt(&["--check"]));
+}
+
+#[test]
+fn lint_fails_on_findings() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(file, "x = 1\nimprima(x == Vazio)").unwrap();
+    let path = file.path().to_str().unwrap();
+    assert_eq!(1, apl("lint", &[path]));
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(file, "x = 1\nimprima(x é Vazio)").unwrap();
+    let path = file.path().to_str().unwrap();
+    assert_eq!(0, apl("lint", &[path]));
+    assert_eq!(2, apl("lint", &[]));
+}
+
+/// Runs the binary, so the output can be checked.
+fn output(args: &[&str]) -> (i32, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_apl"))
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn tokens_are_dumped_with_positions() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(file, "x = 1 # c\n  f(x)").unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let expected = "1:1-1:2 Identifier(\"x\")\n1:3-1:4 Equal\n1:5-1:6 Inteiro(1)\n\
+                    2:3-2:4 Identifier(\"f\")\n2:4-2:5 ParentOpen\n\
+                    2:5-2:6 Identifier(\"x\")\n2:6-2:7 ParentClose\n";
+    assert_eq!((0, expected.to_string()), output(&["tokens", path]));
+
+    let (status, json) = output(&["tokens", "--json", path]);
+    assert_eq!(0, status);
+    let tokens: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        json!({ "token": { "Inteiro": 1 }, "start": { "line": 1, "column": 5 }, "end": { "line": 1, "column": 6 } }),
+        tokens[2]
+    );
+}
+
+#[test]
+fn ast_is_dumped_as_json_or_tree() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(file, "x += -2").unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let (status, json) = output(&["ast", "--json", path]);
+    assert_eq!(0, status);
+    assert_eq!(
+        json!({ "statements": [{ "AugAssign": {
+            "target": { "Identifier": { "name": "x" } },
+            "op": "Add",
+            "value": { "UnOp": { "op": "Minus", "a": { "Num": { "value": { "Integer": { "value": 2 } } } } } },
+        }}]}),
+        serde_json::from_str::<Value>(&json).unwrap()
+    );
+
+    let expected = "statements\n  AugAssign\n    target\n      Identifier\n        name: \"x\"\n    \
+                    op: \"Add\"\n    value\n      UnOp\n        op: \"Minus\"\n        a\n          \
+                    Num\n            value\n              Integer\n                value: 2\n";
+    assert_eq!((0, expected.to_string()), output(&["ast", path]));
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(file, "x = (1").unwrap();
+    assert_eq!(1, output(&["ast", file.path().to_str().unwrap()]).0);
+    assert_eq!(2, output(&["ast", "--json"]).0);
+}
+
+#[test]
+fn modules_are_found_through_aplpath() {
+    let programs = tempfile::tempdir().unwrap();
+    let library = tempfile::tempdir().unwrap();
+    fs::write(library.path().join("saudacao.apl"), "texto = \"olá\"").unwrap();
+    let main = programs.path().join("principal.apl");
+    fs::write(&main, "de saudacao importe texto imprima(texto)").unwrap();
+
+    let run = |aplpath: Option<&std::path::Path>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_apl"));
+        command.arg(&main).env_remove("APLPATH");
+        if let Some(aplpath) = aplpath {
+            command.env("APLPATH", aplpath);
+        }
+        let output = command.output().unwrap();
+        (
+            output.status.code().unwrap(),
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+
+    assert_eq!(
+        (0, "olá\n".to_string(), String::new()),
+        run(Some(library.path()))
+    );
+    let (status, _, stderr) = run(None);
+    assert_eq!(1, status);
+    assert_eq!(
+        "ErroDeImportacao: módulo 'saudacao' não encontrado\n",
+        stderr
+    );
+}
+
+#[test]
+fn programs_see_their_arguments_and_set_the_exit_status() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        file,
+        "importe sistema \
+         imprima(sistema.argumentos[1], \"|\", sistema.argumentos[2]) \
+         sistema.saia(tamanho(sistema.argumentos))"
+    )
+    .unwrap();
+    let path = file.path().to_str().unwrap();
+
+    assert_eq!((3, "a | b c\n".to_string()), output(&[path, "a", "b c"]));
+}
+
+#[test]
+fn truncated_programs_are_not_run() {
+    for source in &[
+        "imprima(1",
+        "imprima(2 +",
+        "imprima(1)\n(1",
+        "imprima(1) 2 +",
+    ] {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "{}", source).unwrap();
+        let path = file.path().to_str().unwrap();
+        assert_eq!((1, String::new()), output(&[path]), "{}", source);
+    }
+}
@@ -0,0 +1,146 @@
+//! Runs the same programs on both backends, with and without optimisations,
+//! and expects identical results, including the errors and the variables left
+//! behind.
+
+use apl::{Backend, Engine};
+
+const PROGRAMS: &[&str] = &[
+    "",
+    "1 + 2 * 3 - 4 / 2",
+    "7 // 2 7 % -3 -7 // 2",
+    "2 ** 3 ** 2",
+    "-2 ** 2",
+    "2 ** -1",
+    "9223372036854775807 + 1",
+    "(9223372036854775807 + 1) * 2 - 1",
+    "0x_ff + 0o17 + 0b101 + 1_000",
+    "1.5e3 + .5",
+    "1 == 1.0",
+    "1 < 2 e 2 < 3",
+    "Verdadeiro ou Falso",
+    "nao Falso",
+    "1 é 1",
+    "se 1 > 2: 10 senao: 20",
+    "se 1 < 0: 1 + 1 senao: se Verdadeiro: 5 - 1",
+    "se 1: 2 senao: 3",
+    r#""texto""#,
+    "Vazio",
+    "1 / 0",
+    "1 // 0",
+    "5 % 0.0",
+    "0 ** -1",
+    r#"1 + "a""#,
+    "a = 1 a",
+    "a = b = 2 a + b",
+    "a, b = 1, 2 a, b = b, a a - b",
+    "a, (b, c) = 1, (2, 3) a + b + c",
+    "a, b = 1, 2, 3",
+    "a, b = 1",
+    "x = 10 x -= 3 x **= 2 x //= 5 x %= 4 x",
+    "x = 1.5 x *= 2 x /= 4 x",
+    "x += 1",
+    "desconhecido",
+    "t = (1, 2, 3) t[0] + t[-1]",
+    "t = (1, 2) t[2]",
+    r#"s = "olá" s[1]"#,
+    "t = (1, 2) t[0] = 5",
+    "t = (1, 2) t[0] += 5",
+    "t = (1, 2) t.x",
+    "t = (1, 2) t.x = 1",
+    "()",
+    "(1, 2)[0]",
+    "imprima",
+    "1(2)",
+    "entrada(1, 2)",
+    "imprima(1, sep=2)",
+    "a = 1 interrompa a = 2",
+    "passe",
+    "a = 1 passe",
+    "verifique 1",
+    "60 * 60 * 24",
+    "x = se Verdadeiro: 1 senao: desconhecido x",
+    "x = se Falso: 1 x",
+    "x = 2 ** 64 - 1 x",
+    "x = 1 / (3 - 3)",
+    "x = 1 se 2 > 1: x + 1 senao: x - 1",
+    "nao (1 < 2 e 2 < 3) ou 4 é 4",
+    "-(2 ** 2) + +3 - -1.5",
+    "(-0.0, 0.0)",
+    "-0.0 * 1",
+    "com 1 como x: x",
+    "com 1: 2",
+    r#"com abra("/nao/existe") como a: 1"#,
+    r#"a = 1 com abra("/nao/existe") como a: 2"#,
+    "com imprima como (a, b): 1",
+];
+
+const NAMES: &[&str] = &["a", "b", "c", "s", "t", "x"];
+
+#[test]
+fn backends_agree() {
+    for program in PROGRAMS {
+        let tree = Engine::with_backend(Backend::TreeWalker);
+        let bytecode = Engine::with_backend(Backend::Bytecode);
+
+        assert_eq!(
+            format!("{:?}", tree.run_str(program)),
+            format!("{:?}", bytecode.run_str(program)),
+            "results differ for {:?}",
+            program
+        );
+        for name in NAMES {
+            assert_eq!(
+                tree.get_global(name),
+                bytecode.get_global(name),
+                "{} differs for {:?}",
+                name,
+                program
+            );
+        }
+    }
+}
+
+#[test]
+fn optimizations_do_not_change_results() {
+    for &backend in &[Backend::TreeWalker, Backend::Bytecode] {
+        for program in PROGRAMS {
+            let plain = Engine::with_backend(backend);
+            let optimized = Engine::with_backend(backend).optimize(true);
+
+            assert_eq!(
+                format!("{:?}", plain.run_str(program)),
+                format!("{:?}", optimized.run_str(program)),
+                "results differ for {:?} on {:?}",
+                program,
+                backend
+            );
+            for name in NAMES {
+                assert_eq!(
+                    plain.get_global(name),
+                    optimized.get_global(name),
+                    "{} differs for {:?} on {:?}",
+                    name,
+                    program,
+                    backend
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn globals_persist_between_runs() {
+    for &backend in &[Backend::TreeWalker, Backend::Bytecode] {
+        let engine = Engine::with_backend(backend);
+        engine.set_global("x", 1);
+        engine.run_str("x += 1").unwrap();
+        let _ = engine.run_str("y = x * 10 desconhecido");
+        assert_eq!(
+            Some(apl::Object::from(20)),
+            engine.get_global("y"),
+            "{:?}",
+            backend
+        );
+        assert_eq!(apl::Object::from(21), engine.eval_expr("y + 1").unwrap());
+    }
+}
@@ -0,0 +1,193 @@
+use apl::{Backend, Engine, EngineError, Error, NativeFunction, Object, ParseError};
+
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::io::{self, Cursor, Write};
+use std::rc::Rc;
+
+/// A writer whose contents can be read after the engine wrote to it.
+#[derive(Clone, Default)]
+struct Capture(Rc<RefCell<Vec<u8>>>);
+
+impl Capture {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.borrow_mut().split_off(0)).unwrap()
+    }
+}
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn run_str_returns_last_value() {
+    let engine = Engine::new();
+    let result = engine.run_str("a = 2 b = 3 a ** b");
+    assert_eq!(Object::from(8), result.unwrap());
+}
+
+#[test]
+fn globals_are_shared_with_rust() {
+    let engine = Engine::new();
+    engine.set_global("nome", "mundo");
+    engine.set_global("pontos", vec![1, 2, 3]);
+    engine.run_str("saudacao = nome").unwrap();
+    engine.run_str("a, b, c = pontos").unwrap();
+
+    let saudacao = String::try_from(engine.get_global("saudacao").unwrap());
+    assert_eq!(Ok("mundo".to_string()), saudacao);
+    assert_eq!(Some(Object::from(3)), engine.get_global("c"));
+    assert_eq!(None, engine.get_global("d"));
+}
+
+#[test]
+fn eval_expr_only_accepts_expressions() {
+    let engine = Engine::new();
+    assert_eq!(Object::from(7), engine.eval_expr("1 + 2 * 3").unwrap());
+
+    match engine.eval_expr("x = 1") {
+        Err(EngineError::Parse(ParseError::Message(_))) => {}
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn errors_are_reported() {
+    let engine = Engine::new();
+    match engine.run_str("1 @ 2") {
+        Err(EngineError::Parse(ParseError::Lex(errors))) => assert_eq!(1, errors.len()),
+        other => panic!("expected a lex error, got {:?}", other),
+    }
+    match engine.run_str("desconhecido + 1") {
+        Err(EngineError::Runtime(Error::UndefinedName(name))) => {
+            assert_eq!("desconhecido", name)
+        }
+        other => panic!("expected a runtime error, got {:?}", other),
+    }
+}
+
+#[test]
+fn conversions() {
+    assert_eq!(Ok(42), i64::try_from(Object::from(42)));
+    assert_eq!(Ok(2.0), f64::try_from(Object::from(2)));
+    assert_eq!(Ok(true), bool::try_from(Object::from(true)));
+    assert_eq!(
+        Ok(vec![1, 2]),
+        Vec::<i64>::try_from(Object::from(vec![1, 2]))
+    );
+    assert_eq!(
+        Err(Error::InvalidConversion(Object::from("1"), "inteiro")),
+        i64::try_from(Object::from("1"))
+    );
+}
+
+#[test]
+fn run_file() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(file, "total = 10\ntotal //= 3\ntotal").unwrap();
+
+    let engine = Engine::new();
+    assert_eq!(Object::from(3), engine.run_file(file.path()).unwrap());
+
+    match engine.run_file("nao/existe.apl") {
+        Err(EngineError::Io(_)) => {}
+        other => panic!("expected an io error, got {:?}", other),
+    }
+}
+
+#[test]
+fn imprima_writes_to_the_engine_output() {
+    for &backend in &[Backend::TreeWalker, Backend::Bytecode] {
+        let output = Capture::default();
+        let engine = Engine::with_backend(backend).output(output.clone());
+
+        engine
+            .run_str("imprima(1, \"dois\", 3.0, Verdadeiro, Vazio, (1, \"a\"))")
+            .unwrap();
+        assert_eq!("1 dois 3.0 Verdadeiro Vazio (1, \"a\")\n", output.text());
+
+        engine.run_str("imprima() imprima(imprima)").unwrap();
+        assert_eq!("\n<função nativa imprima>\n", output.text());
+
+        engine
+            .run_str(
+                "imprima(1, 2, sep=\", \", fim=\"!\") imprima(\"a\", \"b\", sep=Vazio, fim=Vazio)",
+            )
+            .unwrap();
+        assert_eq!("1, 2!a b\n", output.text());
+
+        match engine.run_str("imprima(1, fim=2)") {
+            Err(EngineError::Runtime(Error::InvalidArgument(message))) => {
+                assert_eq!("fim deve ser texto ou Vazio, não inteiro", message)
+            }
+            other => panic!("expected an invalid argument, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn imprima_writes_to_any_object_with_escreva() {
+    let written = Rc::new(RefCell::new(String::new()));
+    let log = Rc::clone(&written);
+    let output = Capture::default();
+    let engine = Engine::new().output(output.clone());
+    engine.register_in(
+        "registro",
+        NativeFunction::new("escreva", move |args| {
+            log.borrow_mut()
+                .push_str(&String::try_from(args.get(0).cloned().unwrap())?);
+            Ok(Object::Unit)
+        })
+        .params(&["texto"]),
+    );
+
+    engine
+        .run_str("imprima(\"x\", 1, arquivo=registro) imprima(2, arquivo=Vazio)")
+        .unwrap();
+    assert_eq!("x 1\n", *written.borrow());
+    assert_eq!("2\n", output.text());
+
+    match engine.run_str("imprima(1, arquivo=3)") {
+        Err(EngineError::Runtime(Error::InvalidAttribute(_, name))) => assert_eq!("escreva", name),
+        other => panic!("expected an attribute error, got {:?}", other),
+    }
+}
+
+#[test]
+fn entrada_reads_from_the_engine_input() {
+    for &backend in &[Backend::TreeWalker, Backend::Bytecode] {
+        let output = Capture::default();
+        let engine = Engine::with_backend(backend)
+            .output(output.clone())
+            .input(Cursor::new("Ana\r\n 42 \n2.5\núltima"));
+
+        engine
+            .run_str("nome = entrada(\"Nome: \") idade = entrada_inteiro() altura = entrada_real(\"Altura: \") resto = entrada()")
+            .unwrap();
+        assert_eq!("Nome: Altura: ", output.text());
+        assert_eq!(Some(Object::from("Ana")), engine.get_global("nome"));
+        assert_eq!(Some(Object::from(42)), engine.get_global("idade"));
+        assert_eq!(Some(Object::from(2.5)), engine.get_global("altura"));
+        assert_eq!(Some(Object::from("última")), engine.get_global("resto"));
+
+        match engine.run_str("entrada()") {
+            Err(EngineError::Runtime(Error::EndOfInput)) => {}
+            other => panic!("expected the end of the input, got {:?}", other),
+        }
+    }
+
+    let engine = Engine::new().input(Cursor::new("\ndoze\n"));
+    assert_eq!(Object::from(""), engine.eval_expr("entrada()").unwrap());
+    assert_eq!(
+        Err("ErroDeValor: não é possível converter 'doze' para inteiro".to_string()),
+        engine
+            .eval_expr("entrada_inteiro()")
+            .map_err(|e| e.to_string())
+    );
+}
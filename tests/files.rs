@@ -0,0 +1,230 @@
+use apl::{Backend, Engine, EngineError};
+
+use std::fs;
+use std::path::Path;
+
+const BACKENDS: &[Backend] = &[Backend::TreeWalker, Backend::Bytecode];
+
+/// Runs the program on each backend in a fresh directory, prepared by
+/// `setup`, whose path replaces `DIR` in the source. Returns the value of the
+/// last statement or the error, which must be the same on both backends,
+/// and the directory the bytecode ran in.
+fn run(source: &str, setup: impl Fn(&Path)) -> (String, tempfile::TempDir) {
+    let mut results = Vec::new();
+    let mut dirs = Vec::new();
+    for &backend in BACKENDS {
+        let dir = tempfile::tempdir().unwrap();
+        setup(dir.path());
+        let path = dir.path().to_str().unwrap();
+        let result = match Engine::with_backend(backend).run_str(&source.replace("DIR", path)) {
+            Ok(value) => value.to_string(),
+            Err(EngineError::Runtime(error)) => error.to_string(),
+            Err(error) => panic!("{}: {:?}", source, error),
+        };
+        results.push(result.replace(path, "DIR"));
+        dirs.push(dir);
+    }
+    assert_eq!(results[0], results[1], "{}", source);
+    (results.remove(0), dirs.remove(1))
+}
+
+fn nothing(_: &Path) {}
+
+fn file(name: &'static str, contents: &'static [u8]) -> impl Fn(&Path) {
+    move |dir| fs::write(dir.join(name), contents).unwrap()
+}
+
+#[test]
+fn reading() {
+    let setup = file("a.txt", b"um\r\ndois\ntres");
+    assert_eq!(
+        "um\ndois\ntres",
+        run(r#"abra("DIR/a.txt").leia()"#, &setup).0
+    );
+    assert_eq!(
+        r#"("um\n", "dois\n", "tres", "")"#,
+        run(
+            r#"f = abra("DIR/a.txt")
+               linhas = f.leia_linha(), f.leia_linha(), f.leia_linha(), f.leia_linha()
+               linhas"#,
+            &setup
+        )
+        .0
+    );
+    assert_eq!(
+        r#"("dois\n", "tres")"#,
+        run(r#"f = abra("DIR/a.txt") f.leia_linha() f.linhas()"#, &setup).0
+    );
+    assert_eq!(
+        "(3, 5, 4)",
+        run(r#"mapeie(tamanho, abra("DIR/a.txt"))"#, &setup).0
+    );
+    assert_eq!(
+        r#"(<arquivo 'DIR/a.txt' modo 'r'>, "arquivo", "r", "utf-8", Falso)"#,
+        run(
+            r#"f = abra("DIR/a.txt") f, tipo(f), f.modo, f.codificacao, f.fechado"#,
+            &setup
+        )
+        .0
+    );
+}
+
+#[test]
+fn writing() {
+    let (result, dir) = run(
+        r#"f = abra("DIR/a.txt", "w")
+           n = f.escreva("olá")
+           imprima("", 1, 2, arquivo=f)
+           f.feche() f.feche()
+           n, f.fechado"#,
+        nothing,
+    );
+    assert_eq!("(3, Verdadeiro)", result);
+    assert_eq!(
+        "olá 1 2\n",
+        fs::read_to_string(dir.path().join("a.txt")).unwrap()
+    );
+
+    let (_, dir) = run(
+        r#"com abra("DIR/a.txt", "a") como f: f.escreva("mais")"#,
+        file("a.txt", b"um "),
+    );
+    assert_eq!(
+        "um mais",
+        fs::read_to_string(dir.path().join("a.txt")).unwrap()
+    );
+
+    assert_eq!(
+        "ErroDeArquivo: o arquivo já existe: 'DIR/a.txt'",
+        run(r#"abra("DIR/a.txt", "x")"#, file("a.txt", b"")).0
+    );
+    let (_, dir) = run(
+        r#"com abra("DIR/b.txt", "x") como f: f.escreva("b")"#,
+        nothing,
+    );
+    assert_eq!("b", fs::read_to_string(dir.path().join("b.txt")).unwrap());
+}
+
+#[test]
+fn encodings() {
+    let (_, dir) = run(
+        r#"com abra("DIR/a.txt", "w", codificacao="latin-1") como f: f.escreva("ção")"#,
+        nothing,
+    );
+    assert_eq!(
+        b"\xe7\xe3o",
+        &fs::read(dir.path().join("a.txt")).unwrap()[..]
+    );
+
+    let latin = file("a.txt", b"\xe7\xe3o");
+    assert_eq!(
+        "ção",
+        run(
+            r#"abra("DIR/a.txt", codificacao="ISO-8859-1").leia()"#,
+            &latin
+        )
+        .0
+    );
+    assert_eq!(
+        "ErroDeValor: o conteúdo não é utf-8 válido",
+        run(r#"abra("DIR/a.txt").leia()"#, &latin).0
+    );
+    assert_eq!(
+        "ErroDeValor: 'ç' não pode ser escrito em ascii",
+        run(
+            r#"abra("DIR/a.txt", "w", codificacao="ascii").escreva("ç")"#,
+            nothing
+        )
+        .0
+    );
+    assert_eq!(
+        "ErroDeValor: codificação desconhecida: 'utf-16'",
+        run(r#"abra("DIR/a.txt", codificacao="utf-16")"#, &latin).0
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        "ErroDeArquivo: arquivo não encontrado: 'DIR/nada.txt'",
+        run(r#"abra("DIR/nada.txt")"#, nothing).0
+    );
+    assert_eq!(
+        "ErroDeValor: modo inválido: 'rw'; use 'r', 'w', 'a' ou 'x'",
+        run(r#"abra("DIR/a.txt", "rw")"#, nothing).0
+    );
+    assert_eq!(
+        "ErroDeTipo: abra() esperava um texto, não inteiro",
+        run("abra(1)", nothing).0
+    );
+    assert_eq!(
+        "ErroDeArquivo: 'DIR/a.txt' não foi aberto para leitura",
+        run(r#"abra("DIR/a.txt", "w").leia()"#, nothing).0
+    );
+    assert_eq!(
+        "ErroDeArquivo: 'DIR/a.txt' não foi aberto para escrita",
+        run(r#"abra("DIR/a.txt").escreva("a")"#, file("a.txt", b"")).0
+    );
+    assert_eq!(
+        "ErroDeValor: operação em arquivo fechado",
+        run(
+            r#"f = abra("DIR/a.txt") f.feche() f.leia()"#,
+            file("a.txt", b"")
+        )
+        .0
+    );
+}
+
+#[test]
+fn com_closes_the_file() {
+    assert_eq!(
+        "(Verdadeiro, \"a\")",
+        run(
+            r#"com abra("DIR/a.txt") como f: x = f.leia()
+               f.fechado, x"#,
+            file("a.txt", b"a")
+        )
+        .0
+    );
+    assert_eq!(
+        "(Verdadeiro, Verdadeiro)",
+        run(
+            r#"com abra("DIR/a.txt") como f, abra("DIR/a.txt") como (g): passe
+               f.fechado, g.fechado"#,
+            file("a.txt", b"a")
+        )
+        .0
+    );
+
+    // the error inside wins, and what was written before it is kept
+    let (result, dir) = run(
+        r#"com abra("DIR/a.txt", "w") como f: f.escreva("a") + desconhecido"#,
+        nothing,
+    );
+    assert_eq!("ErroDeNome: nome 'desconhecido' não está definido", result);
+    assert_eq!("a", fs::read_to_string(dir.path().join("a.txt")).unwrap());
+    let (result, dir) = run(
+        r#"importe sistema com abra("DIR/a.txt", "w") como f: f.escreva("b") sistema.saia(3)"#,
+        nothing,
+    );
+    assert_eq!("SaidaDoSistema: código 3", result);
+    assert_eq!("b", fs::read_to_string(dir.path().join("a.txt")).unwrap());
+    for &backend in BACKENDS {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        let engine = Engine::with_backend(backend);
+        let source = format!(r#"com abra("{}", "w") como f: 1 / 0"#, path.display());
+        assert!(engine.run_str(&source).is_err());
+        assert_eq!(
+            apl::Object::from(true),
+            engine.eval_expr("f.fechado").unwrap(),
+            "{:?}",
+            backend
+        );
+    }
+
+    assert_eq!(
+        "ErroDeTipo: inteiro não pode ser usado com 'com'",
+        run("com 1 como x: x", nothing).0
+    );
+}
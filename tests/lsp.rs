@@ -0,0 +1,271 @@
+use serde_json::{json, Value};
+
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+const URI: &str = "file:///tmp/exemplo.apl";
+
+/// Talks to `apl-lsp` the way an editor would.
+struct Client {
+    server: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut server = Command::new(env!("CARGO_BIN_EXE_apl-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let input = server.stdin.take().unwrap();
+        let output = BufReader::new(server.stdout.take().unwrap());
+        let mut client = Client {
+            server,
+            input,
+            output,
+            next_id: 0,
+        };
+
+        let result = client.request("initialize", json!({ "capabilities": {} }));
+        assert_eq!("apl-lsp", result["serverInfo"]["name"]);
+        client.notify("initialized", json!({}));
+        client
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.input, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.input.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            self.output.read_line(&mut header).unwrap();
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.output.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// The result of the request; notifications that come before it are skipped.
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        loop {
+            let message = self.receive();
+            if message["id"] == json!(id) {
+                assert_eq!(Value::Null, message["error"], "{}", message);
+                return message["result"].clone();
+            }
+        }
+    }
+
+    fn open(&mut self, text: &str) -> Value {
+        self.notify(
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": URI, "languageId": "apl", "version": 1, "text": text } }),
+        );
+        self.receive()
+    }
+
+    fn at(&mut self, method: &str, line: u64, character: u64) -> Value {
+        self.request(
+            method,
+            json!({
+                "textDocument": { "uri": URI },
+                "position": { "line": line, "character": character },
+            }),
+        )
+    }
+
+    fn stop(mut self) -> i32 {
+        assert_eq!(Value::Null, self.request("shutdown", Value::Null));
+        self.notify("exit", Value::Null);
+        self.server.wait().unwrap().code().unwrap()
+    }
+}
+
+#[test]
+fn diagnostics_follow_changes() {
+    let mut client = Client::start();
+
+    let published = client.open("x = 1\nimprima(x + y)");
+    assert_eq!("textDocument/publishDiagnostics", published["method"]);
+    let diagnostics = published["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(1, diagnostics.len());
+    assert_eq!("nome-indefinido", diagnostics[0]["code"]);
+    assert_eq!(1, diagnostics[0]["severity"]);
+    assert_eq!(
+        json!({ "line": 1, "character": 0 }),
+        diagnostics[0]["range"]["start"]
+    );
+
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "text": "x = \"sem fim" }],
+        }),
+    );
+    let published = client.receive();
+    let diagnostics = published["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!("texto sem aspas de fechamento", diagnostics[0]["message"]);
+    assert_eq!(
+        json!({ "line": 0, "character": 4 }),
+        diagnostics[0]["range"]["start"]
+    );
+
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 3 },
+            "contentChanges": [{ "text": "x = 1\nimprima(x ]" }],
+        }),
+    );
+    let published = client.receive();
+    let diagnostics = published["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!("')' esperado, encontrado ']'", diagnostics[0]["message"]);
+    assert_eq!(
+        json!({
+            "start": { "line": 1, "character": 10 },
+            "end": { "line": 1, "character": 11 },
+        }),
+        diagnostics[0]["range"]
+    );
+
+    client.notify(
+        "textDocument/didClose",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    let published = client.receive();
+    assert_eq!(json!([]), published["params"]["diagnostics"]);
+
+    assert_eq!(0, client.stop());
+}
+
+#[test]
+fn navigation_and_hover() {
+    let mut client = Client::start();
+    client.open("total = 1\ntotal += 2\nimprima(total)");
+
+    let hover = client.at("textDocument/hover", 2, 3);
+    let contents = hover["contents"]["value"].as_str().unwrap();
+    assert!(
+        contents.contains("imprima(*valores, sep=…, fim=…, arquivo=…)"),
+        "{}",
+        contents
+    );
+    assert!(contents.contains("Escreve os valores na saída padrão"));
+    assert_eq!(Value::Null, client.at("textDocument/hover", 0, 0));
+
+    let definition = client.at("textDocument/definition", 2, 10);
+    assert_eq!(URI, definition["uri"]);
+    assert_eq!(
+        json!({ "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 5 } }),
+        definition["range"]
+    );
+
+    let symbols = client.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    assert_eq!(1, symbols.as_array().unwrap().len());
+    assert_eq!("total", symbols[0]["name"]);
+
+    assert_eq!(0, client.stop());
+}
+
+#[test]
+fn completion_and_semantic_tokens() {
+    let mut client = Client::start();
+    client.open("antes = 1\n\ndepois = 2");
+
+    let completion = client.at("textDocument/completion", 1, 0);
+    let labels: Vec<&str> = completion
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect();
+    assert!(labels.contains(&"se"));
+    assert!(labels.contains(&"enquanto"));
+    assert!(labels.contains(&"imprima"));
+    assert!(labels.contains(&"antes"));
+    assert!(!labels.contains(&"depois"));
+
+    let tokens = client.request(
+        "textDocument/semanticTokens/full",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    assert_eq!(
+        json!([
+            0, 0, 5, 2, 0, 0, 6, 1, 5, 0, 0, 2, 1, 4, 0, 2, 0, 6, 2, 0, 0, 7, 1, 5, 0, 0, 2, 1, 4,
+            0
+        ]),
+        tokens["data"]
+    );
+
+    assert_eq!(0, client.stop());
+}
+
+#[test]
+fn protocol_errors() {
+    let mut client = Client::start();
+
+    client.send(json!({ "jsonrpc": "2.0", "id": 99, "method": "desconhecido" }));
+    let response = client.receive();
+    assert_eq!(-32601, response["error"]["code"]);
+
+    let body = "{nao e json";
+    write!(
+        client.input,
+        "Content-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .unwrap();
+    client.input.flush().unwrap();
+    let response = client.receive();
+    assert_eq!(-32700, response["error"]["code"]);
+
+    // exit without shutdown is an error
+    client.notify("exit", Value::Null);
+    assert_eq!(1, client.server.wait().unwrap().code().unwrap());
+}
+
+#[test]
+fn unfinished_code_is_a_diagnostic() {
+    let mut client = Client::start();
+
+    for text in &["x = se", "se Verdadeiro: 1 senao:", "imprima(se 1"] {
+        let published = client.open(text);
+        let diagnostics = published["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(1, diagnostics.len(), "{}", text);
+        assert_eq!(1, diagnostics[0]["severity"]);
+    }
+    let published = client.open("x = se");
+    assert_eq!(
+        "expressão esperada depois de 'se'",
+        published["params"]["diagnostics"][0]["message"]
+    );
+
+    assert_eq!(0, client.stop());
+}
@@ -0,0 +1,160 @@
+use apl::{Backend, Engine, EngineError, Error, NativeFunction, Object};
+
+use std::fs;
+use std::path::Path;
+
+const BACKENDS: &[Backend] = &[Backend::TreeWalker, Backend::Bytecode];
+
+fn write(directory: &Path, file: &str, source: &str) {
+    fs::write(directory.join(file), source).unwrap();
+}
+
+fn import_error(result: Result<Object, EngineError>) -> String {
+    match result {
+        Err(EngineError::Runtime(Error::ImportError(message))) => message,
+        other => panic!("expected an import error, got {:?}", other),
+    }
+}
+
+#[test]
+fn modules_next_to_the_file_are_imported() {
+    let directory = tempfile::tempdir().unwrap();
+    write(
+        directory.path(),
+        "medidas.apl",
+        "metro = 100 km = metro * 1000",
+    );
+    write(
+        directory.path(),
+        "principal.apl",
+        "importe medidas como m de medidas importe km, metro como cm\ntotal = m.km + km + cm",
+    );
+
+    for &backend in BACKENDS {
+        let engine = Engine::with_backend(backend);
+        engine
+            .run_file(directory.path().join("principal.apl"))
+            .unwrap();
+        assert_eq!(Some(Object::from(200_100)), engine.get_global("total"));
+        assert_eq!(None, engine.get_global("medidas"));
+    }
+}
+
+#[test]
+fn imports_inside_modules_are_relative_to_them() {
+    let directory = tempfile::tempdir().unwrap();
+    let package = directory.path().join("pacote");
+    fs::create_dir(&package).unwrap();
+    write(&package, "interno.apl", "valor = 41");
+    write(
+        &package,
+        "externo.apl",
+        "de interno importe valor valor += 1",
+    );
+
+    for &backend in BACKENDS {
+        let engine = Engine::with_backend(backend).search_path(&package);
+        let result = engine.run_str("importe externo externo.valor");
+        assert_eq!(Object::from(42), result.unwrap());
+    }
+}
+
+#[test]
+fn search_path_is_used_in_order() {
+    let first = tempfile::tempdir().unwrap();
+    let second = tempfile::tempdir().unwrap();
+    write(first.path(), "config.apl", "origem = \"primeiro\"");
+    write(second.path(), "config.apl", "origem = \"segundo\"");
+    write(second.path(), "extra.apl", "origem = \"extra\"");
+
+    for &backend in BACKENDS {
+        let engine = Engine::with_backend(backend)
+            .search_path(first.path())
+            .search_path(second.path());
+        let result =
+            engine.run_str("de config importe origem de extra importe origem como o origem, o");
+        assert_eq!(Object::from(vec!["primeiro", "extra"]), result.unwrap());
+    }
+}
+
+#[test]
+fn modules_are_loaded_once() {
+    let directory = tempfile::tempdir().unwrap();
+    write(directory.path(), "contador.apl", "n = 0");
+
+    for &backend in BACKENDS {
+        let engine = Engine::with_backend(backend).search_path(directory.path());
+        engine.run_str("importe contador contador.n += 1").unwrap();
+        let result = engine.run_str("importe contador como outro de contador importe n outro.n, n");
+        assert_eq!(Object::from(vec![1, 1]), result.unwrap());
+    }
+}
+
+#[test]
+fn circular_imports_are_reported() {
+    let directory = tempfile::tempdir().unwrap();
+    write(directory.path(), "a.apl", "importe b");
+    write(directory.path(), "b.apl", "importe c");
+    write(directory.path(), "c.apl", "de a importe x");
+
+    for &backend in BACKENDS {
+        let engine = Engine::with_backend(backend);
+        assert_eq!(
+            "importação circular: a.apl -> b.apl -> c.apl -> a.apl",
+            import_error(engine.run_file(directory.path().join("a.apl")))
+        );
+    }
+}
+
+#[test]
+fn import_errors() {
+    let directory = tempfile::tempdir().unwrap();
+    write(directory.path(), "util.apl", "x = 1");
+    write(directory.path(), "quebrado.apl", "x = (1");
+    write(directory.path(), "falha.apl", "x = 1 / 0");
+
+    for &backend in BACKENDS {
+        let engine = Engine::with_backend(backend).search_path(directory.path());
+        assert_eq!(
+            "módulo 'nada' não encontrado",
+            import_error(engine.run_str("importe nada"))
+        );
+        assert_eq!(
+            "não é possível importar 'y' de 'util'",
+            import_error(engine.run_str("de util importe x, y"))
+        );
+        assert_eq!(Some(Object::from(1)), engine.get_global("x"));
+        assert!(import_error(engine.run_str("importe quebrado")).starts_with("quebrado.apl: "));
+        match engine.run_str("importe falha") {
+            Err(EngineError::Runtime(Error::ZeroDivision(..))) => {}
+            other => panic!("expected a division by zero, got {:?}", other),
+        }
+        match engine.run_str("importe util util.y") {
+            Err(EngineError::Runtime(Error::InvalidAttribute(_, name))) => assert_eq!("y", name),
+            other => panic!("expected an attribute error, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn native_modules_can_be_imported() {
+    let directory = tempfile::tempdir().unwrap();
+    write(
+        directory.path(),
+        "usa.apl",
+        "de ferramentas importe dobro resultado = dobro(21)",
+    );
+
+    for &backend in BACKENDS {
+        let engine = Engine::with_backend(backend).search_path(directory.path());
+        engine.register_in(
+            "ferramentas",
+            NativeFunction::new("dobro", |args| {
+                args.get(0).unwrap().add(args.get(0).unwrap())
+            })
+            .params(&["n"]),
+        );
+        let result = engine.run_str("importe usa usa.resultado");
+        assert_eq!(Object::from(42), result.unwrap());
+    }
+}
@@ -0,0 +1,105 @@
+use apl::{Arity, Engine, EngineError, Error, NativeFunction, Object};
+
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+fn soma() -> NativeFunction {
+    NativeFunction::new("soma", |args| {
+        let mut total = 0;
+        for arg in args.into_positional() {
+            total += i64::try_from(arg)?;
+        }
+        Ok(Object::from(total))
+    })
+    .arity(Arity::AtLeast(1))
+}
+
+#[test]
+fn variadic_function() {
+    let engine = Engine::new();
+    engine.register(soma());
+    assert_eq!(
+        Object::from(10),
+        engine.eval_expr("soma(1, 2, 3, 4)").unwrap()
+    );
+}
+
+#[test]
+fn closures_keep_their_state() {
+    let chamadas = Rc::new(RefCell::new(Vec::new()));
+    let registro = Rc::clone(&chamadas);
+    let anote = NativeFunction::new("anote", move |args| {
+        registro.borrow_mut().push(args.get(0).cloned().unwrap());
+        Ok(Object::Unit)
+    })
+    .params(&["valor"]);
+
+    let engine = Engine::new();
+    engine.register(anote);
+    engine.run_str(r#"anote(1) anote(valor="dois")"#).unwrap();
+    assert_eq!(
+        vec![Object::from(1), Object::from("dois")],
+        *chamadas.borrow()
+    );
+}
+
+#[test]
+fn keyword_only_arguments() {
+    let junte = NativeFunction::new("junte", |args| {
+        let sep = match args.keyword("sep") {
+            Some(sep) => String::try_from(sep.clone())?,
+            None => " ".into(),
+        };
+        let partes = args
+            .into_positional()
+            .into_iter()
+            .map(String::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Object::from(partes.join(&sep)))
+    })
+    .keywords(&["sep"]);
+
+    let engine = Engine::new();
+    engine.register(junte);
+    assert_eq!(
+        Object::from("a-b"),
+        engine.eval_expr(r#"junte("a", "b", sep="-")"#).unwrap()
+    );
+}
+
+#[test]
+fn arity_errors_name_the_parameters() {
+    let engine = Engine::new();
+    engine.register(NativeFunction::new("par", |_| Ok(Object::Unit)).params(&["a", "b"]));
+
+    match engine.eval_expr("par(1)") {
+        Err(EngineError::Runtime(error)) => assert_eq!(
+            "ErroDeTipo: par(a, b) recebe 2 argumento(s), mas recebeu 1",
+            error.to_string()
+        ),
+        other => panic!("expected an arity error, got {:?}", other),
+    }
+    match engine.eval_expr("par(1, a=2)") {
+        Err(EngineError::Runtime(Error::InvalidArgument(_))) => {}
+        other => panic!("expected a duplicated argument error, got {:?}", other),
+    }
+    match engine.eval_expr("par(1, c=2)") {
+        Err(EngineError::Runtime(Error::InvalidArgument(_))) => {}
+        other => panic!("expected an unknown keyword error, got {:?}", other),
+    }
+}
+
+#[test]
+fn functions_in_modules() {
+    let engine = Engine::new();
+    engine.register_in("util", soma());
+    engine.module("util").set("versao", Object::from(2));
+
+    assert_eq!(
+        Object::from(5),
+        engine.eval_expr("util.soma(util.versao, 3)").unwrap()
+    );
+    // the global is the builtin, not the function of the module
+    assert_ne!(engine.module("util").get("soma"), engine.get_global("soma"));
+}
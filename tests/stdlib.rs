@@ -0,0 +1,709 @@
+use apl::{Backend, Engine, EngineError};
+
+const BACKENDS: &[Backend] = &[Backend::TreeWalker, Backend::Bytecode];
+
+/// The value of the last statement, shown the way `imprima` would, which
+/// must be the same on both backends.
+fn run(source: &str) -> String {
+    let results: Vec<String> = BACKENDS
+        .iter()
+        .map(
+            |&backend| match Engine::with_backend(backend).run_str(source) {
+                Ok(value) => value.to_string(),
+                Err(error) => panic!("{}: {}", source, error),
+            },
+        )
+        .collect();
+    assert_eq!(results[0], results[1], "{}", source);
+    results[0].clone()
+}
+
+fn error(source: &str) -> String {
+    match Engine::new().run_str(source) {
+        Err(EngineError::Runtime(error)) => error.to_string(),
+        other => panic!("{}: expected an error, got {:?}", source, other),
+    }
+}
+
+#[test]
+fn matematica() {
+    let m = "importe matematica como m ";
+    assert_eq!("4.0", run(&format!("{}m.raiz(16)", m)));
+    assert_eq!("1024.0", run(&format!("{}m.potencia(2, 10)", m)));
+    assert_eq!(
+        "(3, -3, 4)",
+        run(&format!("{}(m.piso(3.7), m.piso(-2.5), m.teto(3.2))", m))
+    );
+    assert_eq!("3.0", run(&format!("{}m.log(8, 2)", m)));
+    assert_eq!("1.0", run(&format!("{}m.log(m.e)", m)));
+    assert_eq!("693.1471805599454", run(&format!("{}m.log(2 ** 1000)", m)));
+    assert_eq!("6931.471805599453", run(&format!("{}m.log(2 ** 10000)", m)));
+    assert_eq!("180.0", run(&format!("{}m.graus(m.pi)", m)));
+    assert_eq!(
+        "Verdadeiro",
+        run(&format!(
+            "{}m.e_proximo(m.sen(m.pi), 0, tolerancia_abs=1e-12)",
+            m
+        ))
+    );
+    assert_eq!("Falso", run(&format!("{}m.e_proximo(1.0, 1.001)", m)));
+    assert_eq!("(infinito, nan)", run(&format!("{}(m.infinito, m.nan)", m)));
+    assert_eq!("6", run("de matematica importe mdc mdc(12, 18, -30)"));
+    assert_eq!(
+        "2.718281828459045",
+        run("de matematica importe euler euler")
+    );
+    assert_eq!("Verdadeiro", run(&format!("{}m.e == m.euler", m)));
+    assert_eq!(
+        "(1, 2432902008176640000, 51090942171709440000)",
+        run("de matematica importe fatorial (fatorial(0), fatorial(20), fatorial(21))")
+    );
+}
+
+#[test]
+fn matematica_errors() {
+    let m = "importe matematica como m ";
+    assert_eq!(
+        "ErroDeValor: raiz(-1) está fora do domínio",
+        error(&format!("{}m.raiz(-1)", m))
+    );
+    assert_eq!(
+        "ErroDeValor: log(0) está fora do domínio",
+        error(&format!("{}m.log(0)", m))
+    );
+    assert_eq!(
+        "ErroDeTipo: log() não recebeu o argumento obrigatório 'x'",
+        error(&format!("{}m.log(base=2)", m))
+    );
+    assert_eq!(
+        "ErroDeValor: asen(2) está fora do domínio",
+        error(&format!("{}m.asen(2)", m))
+    );
+    assert_eq!(
+        "ErroDeValor: potencia(0, -1) está fora do domínio",
+        error(&format!("{}m.potencia(0, -1)", m))
+    );
+    assert_eq!(
+        "ErroDeValor: exp(1000) é grande demais para um real",
+        error(&format!("{}m.exp(1000)", m))
+    );
+    assert_eq!(
+        "ErroDeValor: fatorial() não é definido para negativos",
+        error(&format!("{}m.fatorial(-1)", m))
+    );
+    assert_eq!(
+        "ErroDeValor: fatorial() de 100000 passa do máximo de 10000",
+        error(&format!("{}m.fatorial(100000)", m))
+    );
+    assert_eq!(
+        "ErroDeTipo: mdc() esperava um inteiro, não real",
+        error(&format!("{}m.mdc(1.5)", m))
+    );
+    assert_eq!(
+        "ErroDeTipo: raiz() esperava um número, não texto",
+        error(&format!("{}m.raiz(\"4\")", m))
+    );
+}
+
+/// Like `run`, with `texto` set to `text`, since literals cannot hold quotes.
+fn run_with(text: &str, source: &str) -> String {
+    let results: Vec<String> = BACKENDS
+        .iter()
+        .map(|&backend| {
+            let engine = Engine::with_backend(backend);
+            engine.set_global("texto", text);
+            match engine.run_str(source) {
+                Ok(value) => value.to_string(),
+                Err(EngineError::Runtime(error)) => error.to_string(),
+                Err(error) => panic!("{}: {:?}", source, error),
+            }
+        })
+        .collect();
+    assert_eq!(results[0], results[1], "{}", source);
+    results[0].clone()
+}
+
+#[test]
+fn json() {
+    let load = "importe json json.carregue(texto)";
+    assert_eq!(
+        r#"("Ana", 30, (9.5, 10, -0.001), Verdadeiro, Vazio, ((), ()))"#,
+        run_with(
+            r#" ["Ana", 30, [9.5, 10, -1e-3],
+                 true, null, [[], []]] "#,
+            load
+        )
+    );
+    assert_eq!(
+        "123456789012345678901234567890",
+        run_with("123456789012345678901234567890", load)
+    );
+    assert_eq!(
+        "aspas \" barra \\ é 😀\n",
+        run_with(r#""aspas \" barra \\ \u00e9 \ud83d\ude00\n""#, load)
+    );
+
+    assert_eq!(
+        r#"[1, 2.0, -7, "x", null, false, [true, []]]"#,
+        run(r#"importe json json.converta((1, 2.0, -7, "x", Vazio, Falso, (1 == 1, ())))"#)
+    );
+    let json = r#"[1, "olá\t\"ok\"", [], [["a", [null]]]]"#;
+    assert_eq!(
+        json,
+        run_with(json, "importe json json.converta(json.carregue(texto))")
+    );
+    assert_eq!(
+        "[\n  1,\n  [\n    \"a\"\n  ]\n]",
+        run_with(
+            r#"[1, ["a"]]"#,
+            "importe json json.converta(json.carregue(texto), indente=2)"
+        )
+    );
+}
+
+#[test]
+fn json_errors() {
+    let load = "importe json json.carregue(texto)";
+    let cases = &[
+        ("", "esperava um valor na linha 1, coluna 1"),
+        ("[1, 2", "esperava ',' ou ']' na linha 1, coluna 5"),
+        ("[1,\n 2,]", "vírgula sobrando na linha 2, coluna 4"),
+        (r#"{"a" 1}"#, "esperava ':' na linha 1, coluna 6"),
+        (
+            "{1: 2}",
+            "esperava uma chave entre aspas na linha 1, coluna 2",
+        ),
+        (r#"["abc"#, "texto não terminado na linha 1, coluna 5"),
+        (r#""\x""#, "escape inválido na linha 1, coluna 3"),
+        (r#""\ud800""#, "escape \\u inválido na linha 1, coluna 8"),
+        ("01", "número inválido na linha 1, coluna 2"),
+        ("1.", "esperava um valor na linha 1, coluna 2"),
+        ("tru", "esperava um valor na linha 1, coluna 3"),
+        ("1 2", "dados a mais depois do valor na linha 1, coluna 3"),
+    ];
+    for (text, message) in cases {
+        assert_eq!(
+            format!("ErroDeValor: JSON inválido: {}", message),
+            run_with(text, load),
+            "{:?}",
+            text
+        );
+    }
+
+    assert_eq!(
+        "ErroDeValor: objetos JSON ainda não são aceitos, pois a linguagem não tem dicionários",
+        run_with(r#"[1, {"a": 1}]"#, load)
+    );
+    assert_eq!(
+        "ErroDeTipo: um valor do tipo função nativa não pode ser escrito em JSON",
+        error("importe json json.converta((1, imprima))")
+    );
+    assert_eq!(
+        "ErroDeValor: infinito não pode ser escrito em JSON",
+        error("importe json json.converta(10.0 ** 400)")
+    );
+    assert_eq!(
+        "ErroDeTipo: carregue() esperava um texto, não inteiro",
+        error("importe json json.carregue(1)")
+    );
+}
+
+#[test]
+fn aleatorio() {
+    let a = "importe aleatorio como a a.semente(42) ";
+    // pinned, so a change to the generator shows up here
+    assert_eq!(
+        "(6, 2, 3, 0.9515812027711864)",
+        run(&format!(
+            "{}a.inteiro(1, 6), a.inteiro(1, 6), a.inteiro(1, 6), a.real()",
+            a
+        ))
+    );
+    assert_eq!(
+        run(&format!("{}a.embaralhe(intervalo(20))", a)),
+        run(&format!("{}a.embaralhe(intervalo(20))", a))
+    );
+    assert_ne!(
+        run(&format!("{}a.embaralhe(intervalo(20))", a)),
+        run("importe aleatorio como a a.semente(\"42\") a.embaralhe(intervalo(20))")
+    );
+    assert_eq!(
+        "(0, 1, 2, 3, 4, 5, 6, 7, 8, 9)",
+        run(&format!("{}ordenado(a.embaralhe(intervalo(10)))", a))
+    );
+    assert_eq!(
+        "3",
+        run(&format!(
+            "{}amostra = a.amostra(\"abcdef\", 3) tamanho(amostra)",
+            a
+        ))
+    );
+    assert_eq!("()", run(&format!("{}a.amostra((1, 2), 0)", a)));
+    assert_eq!("x", run(&format!("{}a.escolha(\"x\")", a)));
+    assert_eq!("7", run(&format!("{}a.inteiro(7, 7)", a)));
+    let lows = format!("({})", vec!["-3"; 200].join(", "));
+    let highs = format!("({})", vec!["3"; 200].join(", "));
+    assert_eq!(
+        "(-3, 3)",
+        run(&format!(
+            "{}dados = mapeie(a.inteiro, {}, {}) min(dados), max(dados)",
+            a, lows, highs
+        ))
+    );
+}
+
+#[test]
+fn aleatorio_errors() {
+    let a = "importe aleatorio como a ";
+    assert_eq!(
+        "ErroDeValor: inteiro(6, 1) não tem nenhum inteiro",
+        error(&format!("{}a.inteiro(6, 1)", a))
+    );
+    assert_eq!(
+        "ErroDeValor: escolha() recebeu uma sequência vazia",
+        error(&format!("{}a.escolha(())", a))
+    );
+    assert_eq!(
+        "ErroDeValor: amostra() não pode ter 3 elementos de uma sequência de 2",
+        error(&format!("{}a.amostra((1, 2), 3)", a))
+    );
+    assert_eq!(
+        "ErroDeTipo: semente() esperava um inteiro ou um texto, não real",
+        error(&format!("{}a.semente(1.5)", a))
+    );
+}
+
+#[test]
+fn tempo() {
+    let t = "importe tempo ";
+    assert_eq!(
+        "Verdadeiro",
+        run(&format!("{}tempo.agora() > 1700000000", t))
+    );
+    assert_eq!(
+        "Verdadeiro",
+        run(&format!(
+            "{}inicio = tempo.monotonico() tempo.durma(0.01) tempo.monotonico() - inicio >= 0.01",
+            t
+        ))
+    );
+    assert_eq!(
+        "ErroDeValor: durma() não aceita -1 segundos",
+        error(&format!("{}tempo.durma(-1)", t))
+    );
+}
+
+#[test]
+fn datahora() {
+    let d = "de datahora importe datahora, analise, de_iso, duracao ";
+    assert_eq!(
+        "(2024-03-05 14:30:00, \"2024-03-05T14:30:00\", \"datahora\")",
+        run(&format!(
+            "{}d = datahora(2024, 3, 5, 14, 30) d, d.iso(), tipo(d)",
+            d
+        ))
+    );
+    assert_eq!(
+        "(2024, 3, 5, 14, 30, 0, 0, 1)",
+        run(&format!(
+            "{}d = datahora(2024, 3, 5, 14, 30)
+               d.ano, d.mes, d.dia, d.hora, d.minuto, d.segundo, d.microssegundo, d.dia_da_semana",
+            d
+        ))
+    );
+    assert_eq!(
+        "05/03/2024 terça-feira, março",
+        run(&format!(
+            "{}datahora(2024, 3, 5).formate(\"%d/%m/%Y %A, %B\")",
+            d
+        ))
+    );
+    assert_eq!(
+        "2024-03-05 09:07:00",
+        run(&format!(
+            "{}analise(\"5/3/2024 9:07\", \"%d/%m/%Y %H:%M\")",
+            d
+        ))
+    );
+    assert_eq!(
+        "(2024-03-05 00:00:00, 2024-03-05 14:30:15.500000)",
+        run(&format!(
+            "{}de_iso(\"2024-03-05\"), de_iso(\"2024-03-05T14:30:15.5\")",
+            d
+        ))
+    );
+
+    // durations are seconds
+    assert_eq!(
+        "(2024-03-01 00:00:00, 2024-02-28 23:59:59, 2024-03-01 12:00:00)",
+        run(&format!(
+            "{}d = datahora(2024, 2, 29) d + duracao(dias=1), d - 1, duracao(horas=36) + d",
+            d
+        ))
+    );
+    assert_eq!(
+        "(86400.0, -0.5)",
+        run(&format!(
+            "{}a = datahora(2024, 1, 2) b = datahora(2024, 1, 1)
+               a - b, b - (b + 0.5)",
+            d
+        ))
+    );
+    assert_eq!(
+        "(Verdadeiro, Falso, Verdadeiro, Verdadeiro)",
+        run(&format!(
+            "{}a = datahora(2024, 1, 2) b = datahora(2024, 1, 1)
+               a > b, a == b, a != b, b <= datahora(2024, 1, 1)",
+            d
+        ))
+    );
+    assert_eq!(
+        "1970-01-02 00:00:00",
+        run("de datahora importe de_timestamp de_timestamp(86400)")
+    );
+}
+
+#[test]
+fn datahora_errors() {
+    let d = "de datahora importe datahora, analise, de_iso ";
+    assert_eq!(
+        "ErroDeValor: dia deve estar entre 1 e 28, não 29",
+        error(&format!("{}datahora(2023, 2, 29)", d))
+    );
+    assert_eq!(
+        "ErroDeValor: '5-3-2024' não corresponde ao formato '%d/%m/%Y'",
+        error(&format!("{}analise(\"5-3-2024\", \"%d/%m/%Y\")", d))
+    );
+    assert_eq!(
+        "ErroDeValor: '05/03/2024' não é uma datahora ISO 8601",
+        error(&format!("{}de_iso(\"05/03/2024\")", d))
+    );
+    assert_eq!(
+        "ErroDeValor: diretiva de formato inválida: '%Q'",
+        error(&format!("{}datahora(2024, 1, 1).formate(\"%Q\")", d))
+    );
+    assert_eq!(
+        "ErroDeValor: datahora fora do intervalo",
+        error(&format!("{}datahora(9999, 12, 31) + 86400", d))
+    );
+    assert!(error(&format!("{}datahora(2024, 1, 1) + \"1\"", d)).starts_with("ErroDeTipo"));
+}
+
+#[test]
+fn sistema() {
+    let s = "importe sistema ";
+    assert_eq!("()", run(&format!("{}sistema.argumentos", s)));
+    let engine = Engine::new().arguments(vec!["a.apl", "b"]);
+    assert_eq!(
+        "(\"a.apl\", \"b\")",
+        engine
+            .run_str("importe sistema sistema.argumentos")
+            .unwrap()
+            .to_string()
+    );
+    assert_eq!(
+        std::env::consts::OS,
+        run(&format!("{}sistema.plataforma", s))
+    );
+
+    assert_eq!(
+        "(Vazio, \"um\", Vazio)",
+        run(&format!(
+            "{}antes = sistema.ambiente(\"APL_TESTE_SISTEMA\") \
+             sistema.ambiente(\"APL_TESTE_SISTEMA\", \"um\") \
+             depois = sistema.ambiente(\"APL_TESTE_SISTEMA\") \
+             sistema.ambiente(\"APL_TESTE_SISTEMA\", Vazio) \
+             antes, depois, sistema.ambiente(\"APL_TESTE_SISTEMA\")",
+            s
+        ))
+    );
+    assert_eq!(
+        "(Verdadeiro, 2)",
+        run(&format!(
+            "{}todas = sistema.ambiente() tamanho(todas) > 0, tamanho(todas[0])",
+            s
+        ))
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn sistema_execute() {
+    let s = "importe sistema ";
+    assert_eq!(
+        "(\"oi\\n\", \"erro\\n\", 3)",
+        run(&format!(
+            "{}sistema.execute(\"sh\", (\"-c\", \"echo oi; echo erro >&2; exit 3\"))",
+            s
+        ))
+    );
+    assert_eq!(
+        "(\"\", \"\", 0)",
+        run(&format!("{}sistema.execute(\"true\")", s))
+    );
+}
+
+#[test]
+fn sistema_errors() {
+    let s = "importe sistema ";
+    assert_eq!(
+        "SaidaDoSistema: código 0",
+        error(&format!("{}sistema.saia()", s))
+    );
+    assert_eq!(
+        "SaidaDoSistema: código 2",
+        error(&format!("{}sistema.saia(2) imprima(1)", s))
+    );
+    assert_eq!(
+        "ErroDeTipo: saia() esperava um inteiro, não texto",
+        error(&format!("{}sistema.saia(\"2\")", s))
+    );
+    assert_eq!(
+        "ErroDeValor: código de saída fora do intervalo: 4294967296",
+        error(&format!("{}sistema.saia(2 ** 32)", s))
+    );
+    assert_eq!(
+        "ErroDeArquivo: comando não encontrado: 'apl-comando-que-nao-existe'",
+        error(&format!(
+            "{}sistema.execute(\"apl-comando-que-nao-existe\")",
+            s
+        ))
+    );
+    assert_eq!(
+        "ErroDeTipo: execute() esperava um texto, não inteiro",
+        error(&format!("{}sistema.execute(\"true\", (1,2))", s))
+    );
+    assert_eq!(
+        "ErroDeValor: nome de variável de ambiente inválido: 'A=B'",
+        error(&format!("{}sistema.ambiente(\"A=B\")", s))
+    );
+}
+
+#[test]
+fn regex() {
+    let r = "importe regex ";
+    assert_eq!(
+        "(\"2024\", \"03\", \"05\")",
+        run(&format!(
+            r#"{}m = regex.busque("(\d+)-(\d+)-(\d+)", "em 2024-03-05, às 14h") m.grupos()"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "(\"2024-03-05\", (3, 13), \"03\", (\"03\", \"05\"))",
+        run(&format!(
+            r#"{}m = regex.busque("(\d+)-(\d+)-(\d+)", "em 2024-03-05, às 14h")
+               m.grupo(), m.intervalo(), m.grupo(2), m.grupo(2, 3)"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "(Vazio, \"ab\")",
+        run(&format!(
+            r#"{}regex.corresponde("b+", "abb"), regex.corresponde("a\w", "abb").grupo()"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "(\"ana\", \"31\", ((\"nome\", \"ana\"), (\"idade\", \"31\")))",
+        run(&format!(
+            r#"{}m = regex.busque("(?P<nome>[a-z]+)=(?<idade>\d+)", "x: ana=31;")
+               m.grupo("nome"), m.grupo("idade"), m.nomeados()"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "(\"a\", Vazio, \"-\", -1, (-1, -1))",
+        run(&format!(
+            r#"{}m = regex.corresponde("(a)|(b)", "a")
+               m.grupo(1), m.grupo(2), m.grupos("-")[1], m.inicio(2), m.intervalo(2)"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "((\"a\", \"1\"), (\"b\", \"22\"))",
+        run(&format!(
+            r#"{}regex.encontre_todos("([a-z])=(\d+)", "a=1, b=22")"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "((\"1\", \"22\", \"333\"), (\"a\", \"b\"))",
+        run(&format!(
+            r#"{}regex.encontre_todos("\d+", "1 22 333"), regex.encontre_todos("(\w)=", "a=1 b=2")"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "(\"05/03/2024\", \"2024-03-05 e x-y\", \"-a-b--d-\")",
+        run(&format!(
+            r#"{}regex.substitua("(\d+)-(\d+)-(\d+)", "\3/\2/\1", "2024-03-05"),
+               regex.substitua("(?P<a>\w+)=(?P<b>\w+)", "\g<b>-\g<a>", "2024-03-05 e y=x", vezes=1),
+               regex.substitua("x*", "-", "abxd")"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "((\"a\", \"b\", \"c\"), (\"a\", \",\", \"b\", \";\", \"c\"), (\"a\", \"b;c\"))",
+        run(&format!(
+            r#"{}regex.divida("[,;] *", "a, b;c"), regex.divida("([,;])", "a,b;c"),
+               regex.divida("[,;]", "a,b;c", 1)"#,
+            r
+        ))
+    );
+
+    // options, lazy quantifiers, backreferences and lookarounds
+    assert_eq!(
+        "(\"ABC\", \"ABC\", \"c\")",
+        run(&format!(
+            r#"{}(regex.busque("abc", "xABC", ignore_caixa=Verdadeiro).grupo(),
+               regex.busque("(?i)a[b-c]+", "xABC").grupo(),
+               regex.busque("c$", "a b c").grupo())"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "((\"a\", \"b\", \"c\"), \"c\", \"b\\nc\", Vazio)",
+        run_with(
+            "a\nb\nc\n",
+            r#"importe regex p = regex.compile("^\w$", multilinha=Verdadeiro)
+               p.encontre_todos(texto), regex.busque("c$", texto).grupo(),
+               regex.busque("b.c", texto, ponto_tudo=Verdadeiro).grupo(), regex.busque("b.c", texto)"#
+        )
+    );
+    assert_eq!(
+        "(\"<a>\", \"<a><b>\", \"abab\", \"100\", \"R$\")",
+        run(&format!(
+            r#"{}(regex.busque("<.+?>", "<a><b>").grupo(), regex.busque("<.+>", "<a><b>").grupo(),
+               regex.busque("(ab)\1", "xababx").grupo(),
+               regex.busque("\d+(?= reais)", "1 real, 100 reais").grupo(),
+               regex.busque("(?<!\d)R\$", "1R$ R$").grupo())"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "((\"\", \"\", \"\"), \"x\")",
+        run(&format!(
+            r#"{}regex.encontre_todos("(a*)*", "ba"), regex.busque("(a|)*x", "x").grupo()"#,
+            r
+        ))
+    );
+}
+
+#[test]
+fn regex_patterns() {
+    let r = "importe regex ";
+    assert_eq!(
+        r#"(<padrão '(\d+)-(?P<mes>\d+)'>, "(\\d+)-(?P<mes>\\d+)", 2, (("mes", 2),))"#,
+        run(&format!(
+            r#"{}p = regex.compile("(\d+)-(?P<mes>\d+)") p, p.padrao, p.grupos, p.nomes"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "(<correspondência (1, 4) '1-2'>, \"a1-2\", Verdadeiro)",
+        run(&format!(
+            r#"{}p = regex.compile("(\d+)-(?P<mes>\d+)") m = p.busque("a1-2")
+               m, m.texto, m.padrao == p"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "(\"1-2\", \"x\", (\"a\", \"b\"), Verdadeiro)",
+        run(&format!(
+            r#"{}p = regex.compile("\d-\d")
+               p.corresponde("1-2").grupo(), p.substitua("x", "3-4"), p.divida("a1-2b"),
+               regex.compile("\d-\d") == p"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "2",
+        run(&format!(
+            r#"{}tamanho(regex.encontre_todos(regex.compile("a", ignore_caixa=Verdadeiro), "aA"))"#,
+            r
+        ))
+    );
+}
+
+#[test]
+fn regex_errors() {
+    let r = "importe regex ";
+    for (pattern, message) in &[
+        ("(a", "parêntese não fechado na posição 0"),
+        ("a)", "')' sem '(' correspondente na posição 1"),
+        ("[a-", "conjunto não terminado na posição 0"),
+        ("[z-a]", "intervalo inválido na posição 1"),
+        ("*a", "nada para repetir na posição 0"),
+        ("a**", "repetição múltipla na posição 2"),
+        (
+            "a{3,2}",
+            "repetição com mínimo maior que o máximo na posição 1",
+        ),
+        ("(a)\\2", "referência a um grupo inexistente na posição 3"),
+        ("(a\\1)", "referência a um grupo aberto na posição 2"),
+        ("\\q", "escape inválido \\q na posição 0"),
+        (
+            "(?<=a+)b",
+            "lookbehind requer um padrão de tamanho fixo na posição 0",
+        ),
+        (
+            "(?P<x>a)(?P<x>b)",
+            "nome de grupo repetido 'x' na posição 12",
+        ),
+        (
+            "a(?i)",
+            "as opções devem ficar no início do padrão na posição 1",
+        ),
+    ] {
+        assert_eq!(
+            format!("ErroDeValor: expressão regular inválida: {}", message),
+            error(&format!(r#"{}regex.compile("{}")"#, r, pattern)),
+            "{}",
+            pattern
+        );
+    }
+    assert_eq!(
+        "ErroDeValor: grupo inexistente: 2",
+        error(&format!(r#"{}regex.busque("(a)", "a").grupo(2)"#, r))
+    );
+    assert_eq!(
+        "ErroDeValor: grupo inexistente: \"x\"",
+        error(&format!(r#"{}regex.substitua("(a)", "\g<x>", "a")"#, r))
+    );
+    assert_eq!(
+        "ErroDeTipo: busque() esperava um texto, não inteiro",
+        error(&format!(r#"{}regex.busque("a", 1)"#, r))
+    );
+    assert_eq!(
+        "ErroDeTipo: compile() esperava um texto ou um padrão, não inteiro",
+        error(&format!(r#"{}regex.compile(1)"#, r))
+    );
+    assert_eq!(
+        "ErroDeValor: as opções não se aplicam a um padrão já compilado",
+        error(&format!(
+            r#"{}regex.busque(regex.compile("a"), "a", ignore_caixa=Verdadeiro)"#,
+            r
+        ))
+    );
+    assert_eq!(
+        "ErroDeValor: vezes não pode ser negativo",
+        error(&format!(r#"{}regex.divida("a", "bab", -1)"#, r))
+    );
+    assert_eq!(
+        "ErroDeValor: a busca por '(a*)*b' tentou caminhos demais",
+        error(&format!(
+            r#"{}regex.busque("(a*)*b", "{}")"#,
+            r,
+            "a".repeat(30)
+        ))
+    );
+    assert_eq!(
+        "ErroDeValor: a busca por '(x+x+)+y' tentou caminhos demais",
+        error(&format!(
+            r#"{}regex.encontre_todos("(x+x+)+y", "{}")"#,
+            r,
+            "x".repeat(40)
+        ))
+    );
+}